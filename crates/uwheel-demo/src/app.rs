@@ -61,8 +61,9 @@ pub const DAY_COLOR: Color32 = Color32::from_rgb(222, 0, 204);
 pub const WEEK_COLOR: Color32 = Color32::from_rgb(255, 237, 73);
 pub const YEAR_COLOR: Color32 = Color32::from_rgb(255, 143, 154);
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum Granularity {
+    #[default]
     Second,
     Minute,
     Hour,
@@ -70,11 +71,6 @@ pub enum Granularity {
     Week,
     Year,
 }
-impl Default for Granularity {
-    fn default() -> Self {
-        Self::Second
-    }
-}
 
 fn calculate_granularity(position: usize) -> Option<(Granularity, usize)> {
     if (1..=59).contains(&position) {
@@ -760,7 +756,7 @@ impl eframe::App for TemplateApp {
                             Err(_) => {
                                 log.push_front(LogEntry::Red(format!(
                                     "Cannot parse {} to a u64",
-                                    &aggregate,
+                                    aggregate,
                                 )));
                             }
                         }
@@ -833,7 +829,7 @@ impl eframe::App for TemplateApp {
 
                     log.push_front(LogEntry::Green(format!(
                         "Advanced time by {} {:?}",
-                        ticks, &tick_granularity
+                        ticks, tick_granularity
                     )));
                     update_haw_labels(labels, star_wheel);
                 }
@@ -0,0 +1,347 @@
+//! Derive macros for µWheel.
+//!
+//! This crate is re-exported by `uwheel` when its `derive` feature is enabled and should not be
+//! used directly.
+#![deny(nonstandard_style, missing_docs)]
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input,
+    parse_quote,
+    spanned::Spanned,
+    Data,
+    DeriveInput,
+    Error,
+    Fields,
+    Index,
+    LitStr,
+    Member,
+    Type,
+};
+
+/// Derives an `Aggregator` for a struct of input data
+///
+/// Every field annotated with `#[aggregate(...)]` becomes a field of a generated partial aggregate
+/// while other fields are ignored. The following functions are supported:
+///
+/// - `sum`: sums the field
+/// - `min`: keeps the smallest value of the field
+/// - `max`: keeps the largest value of the field
+/// - `count`: counts the inputs as `u64` (the value of the field is ignored)
+///
+/// Annotated fields must be primitive numbers. For a struct `Name`, the macro generates an
+/// aggregator `NameAggregator` and a partial aggregate `NamePartial` with the same visibility as
+/// `Name`. The partial aggregate is also the lowered aggregate. Both names may be overridden using
+/// `#[aggregate(aggregator = "..", partial = "..")]` on the struct.
+///
+/// If every field is a `sum` or `count`, the aggregator also implements `combine_inverse` and
+/// may be used with prefix-sum wheels.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{Aggregator, Entry, NumericalDuration, RwWheel};
+///
+/// #[derive(Debug, Clone, Copy, Aggregator)]
+/// # #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Request {
+///     #[aggregate(sum)]
+///     bytes: u64,
+///     #[aggregate(max)]
+///     latency: u32,
+///     #[aggregate(count)]
+///     requests: (),
+/// }
+///
+/// let mut wheel: RwWheel<RequestAggregator> = RwWheel::new(0);
+/// wheel.insert(Entry::new(Request { bytes: 100, latency: 5, requests: () }, 1000));
+/// wheel.insert(Entry::new(Request { bytes: 50, latency: 12, requests: () }, 2000));
+/// wheel.advance(5.seconds());
+///
+/// let partial = wheel.read().interval(5.seconds()).unwrap();
+/// assert_eq!(partial.bytes, 150);
+/// assert_eq!(partial.latency, 12);
+/// assert_eq!(partial.requests, 2);
+/// ```
+#[proc_macro_derive(Aggregator, attributes(aggregate))]
+pub fn derive_aggregator(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Aggregation function of a single field
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Sum,
+    Min,
+    Max,
+    Count,
+}
+
+impl Function {
+    fn invertible(self) -> bool {
+        matches!(self, Function::Sum | Function::Count)
+    }
+}
+
+/// A field of the input struct that is part of the partial aggregate
+struct AggregateField {
+    function: Function,
+    /// How the field is accessed on the input
+    input: Member,
+    /// How the field is accessed on the partial aggregate
+    partial: Member,
+    /// Type of the field within the partial aggregate
+    ty: Type,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "#[derive(Aggregator)] does not support generic structs",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "#[derive(Aggregator)] is only supported on structs",
+        ));
+    };
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let mut aggregator = format_ident!("{}Aggregator", name);
+    let mut partial = format_ident!("{}Partial", name);
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("aggregate"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let ident = if meta.path.is_ident("aggregator") {
+                &mut aggregator
+            } else if meta.path.is_ident("partial") {
+                &mut partial
+            } else {
+                return Err(meta.error("expected `aggregator` or `partial`"));
+            };
+            let value: LitStr = meta.value()?.parse()?;
+            *ident = value.parse()?;
+            Ok(())
+        })?;
+    }
+
+    let named = matches!(data.fields, Fields::Named(_));
+    let fields = aggregate_fields(&data.fields)?;
+    if fields.is_empty() {
+        return Err(Error::new(
+            name.span(),
+            "#[derive(Aggregator)] requires at least one field annotated with #[aggregate(..)]",
+        ));
+    }
+
+    let numeric = quote!(::uwheel::aggregator::derive::Numeric);
+    let build = |values: Vec<TokenStream2>| -> TokenStream2 {
+        if named {
+            let members = fields.iter().map(|f| &f.partial);
+            quote!(#partial { #(#members: #values),* })
+        } else {
+            quote!(#partial(#(#values),*))
+        }
+    };
+
+    let identity = build(
+        fields
+            .iter()
+            .map(|f| {
+                let ty = &f.ty;
+                match f.function {
+                    Function::Sum | Function::Count => quote!(<#ty as #numeric>::ZERO),
+                    Function::Min => quote!(<#ty as #numeric>::MAX),
+                    Function::Max => quote!(<#ty as #numeric>::MIN),
+                }
+            })
+            .collect(),
+    );
+    let lift = build(
+        fields
+            .iter()
+            .map(|f| match f.function {
+                Function::Count => quote!(1),
+                _ => {
+                    let member = &f.input;
+                    quote!(input.#member)
+                }
+            })
+            .collect(),
+    );
+    let combine = build(
+        fields
+            .iter()
+            .map(|f| {
+                let member = &f.partial;
+                match f.function {
+                    Function::Sum | Function::Count => quote!(a.#member + b.#member),
+                    Function::Min => {
+                        quote!(if b.#member < a.#member { b.#member } else { a.#member })
+                    }
+                    Function::Max => {
+                        quote!(if b.#member > a.#member { b.#member } else { a.#member })
+                    }
+                }
+            })
+            .collect(),
+    );
+    let combine_inverse = if fields.iter().all(|f| f.function.invertible()) {
+        let inverse = build(
+            fields
+                .iter()
+                .map(|f| {
+                    let member = &f.partial;
+                    quote!(a.#member - b.#member)
+                })
+                .collect(),
+        );
+        quote! {
+            #[inline]
+            fn combine_inverse() -> ::core::option::Option<
+                ::uwheel::aggregator::InverseFn<Self::PartialAggregate>,
+            > {
+                ::core::option::Option::Some(|a, b| #inverse)
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    let definition = if named {
+        let members = fields.iter().map(|f| &f.partial);
+        let types = fields.iter().map(|f| &f.ty);
+        quote!(#vis struct #partial { #(pub #members: #types),* })
+    } else {
+        let types = fields.iter().map(|f| &f.ty);
+        quote!(#vis struct #partial(#(pub #types),*);)
+    };
+
+    let aggregator_doc = format!("Aggregator derived for [{name}]");
+    let partial_doc = format!("Partial aggregate of [{aggregator}]");
+
+    Ok(quote! {
+        #[doc = #aggregator_doc]
+        #[derive(Default, Debug, Clone, Copy)]
+        #vis struct #aggregator;
+
+        ::uwheel::__derive_partial! {
+            #[doc = #partial_doc]
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            #definition
+        }
+
+        impl ::core::default::Default for #partial {
+            fn default() -> Self {
+                <#aggregator as ::uwheel::aggregator::Aggregator>::IDENTITY
+            }
+        }
+
+        impl ::uwheel::aggregator::PartialAggregateType for #partial {}
+
+        impl ::uwheel::aggregator::Aggregator for #aggregator {
+            const IDENTITY: Self::PartialAggregate = #identity;
+            const NAME: ::core::option::Option<&'static str> = ::core::option::Option::Some(
+                ::core::concat!(::core::module_path!(), "::", ::core::stringify!(#aggregator))
+            );
+
+            type Input = #name;
+            type MutablePartialAggregate = #partial;
+            type PartialAggregate = #partial;
+            type Aggregate = #partial;
+
+            #[inline]
+            fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+                #lift
+            }
+
+            #[inline]
+            fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+                *mutable = <Self as ::uwheel::aggregator::Aggregator>::combine(
+                    *mutable,
+                    <Self as ::uwheel::aggregator::Aggregator>::lift(input),
+                );
+            }
+
+            fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+                mutable
+            }
+
+            #[inline]
+            fn combine(a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+                #combine
+            }
+
+            #[inline]
+            fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+                a
+            }
+
+            #combine_inverse
+        }
+    })
+}
+
+fn aggregate_fields(fields: &Fields) -> syn::Result<Vec<AggregateField>> {
+    let mut aggregate_fields = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let mut function = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("aggregate"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let parsed = if meta.path.is_ident("sum") {
+                    Function::Sum
+                } else if meta.path.is_ident("min") {
+                    Function::Min
+                } else if meta.path.is_ident("max") {
+                    Function::Max
+                } else if meta.path.is_ident("count") {
+                    Function::Count
+                } else {
+                    return Err(meta.error("expected one of `sum`, `min`, `max` or `count`"));
+                };
+                if function.replace(parsed).is_some() {
+                    return Err(meta.error("a field can only have a single aggregate function"));
+                }
+                Ok(())
+            })?;
+        }
+        let Some(function) = function else {
+            continue;
+        };
+
+        let (input, partial) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), Member::Named(ident.clone())),
+            None => (
+                Member::Unnamed(Index::from(index)),
+                Member::Unnamed(Index::from(aggregate_fields.len())),
+            ),
+        };
+        let ty = match function {
+            Function::Count => parse_quote!(u64),
+            _ => field.ty.clone(),
+        };
+        aggregate_fields.push(AggregateField {
+            function,
+            input,
+            partial,
+            ty,
+        });
+    }
+    Ok(aggregate_fields)
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use uwheel::{Aggregator, Entry, NumericalDuration, RwWheel, WheelRange};
+
+#[derive(Debug, Clone, Copy, Aggregator, Serialize, Deserialize)]
+struct Request {
+    #[aggregate(sum)]
+    bytes: u64,
+    #[aggregate(min)]
+    min_latency: f64,
+    #[aggregate(max)]
+    max_latency: f64,
+    #[aggregate(count)]
+    requests: (),
+    #[allow(dead_code)]
+    status: u16,
+}
+
+#[derive(Debug, Clone, Copy, Aggregator, Serialize, Deserialize)]
+#[aggregate(aggregator = "TrafficAggregator", partial = "Traffic")]
+struct Packet(#[aggregate(sum)] u32, u8, #[aggregate(count)] ());
+
+fn request(bytes: u64, latency: f64) -> Request {
+    Request {
+        bytes,
+        min_latency: latency,
+        max_latency: latency,
+        requests: (),
+        status: 200,
+    }
+}
+
+#[test]
+fn derive_struct_test() {
+    assert_eq!(
+        RequestAggregator::IDENTITY,
+        RequestPartial {
+            bytes: 0,
+            min_latency: f64::MAX,
+            max_latency: f64::MIN,
+            requests: 0,
+        }
+    );
+    assert_eq!(RequestPartial::default(), RequestAggregator::IDENTITY);
+    // min and max are not invertible
+    assert!(RequestAggregator::combine_inverse().is_none());
+
+    let mut wheel: RwWheel<RequestAggregator> = RwWheel::new(0);
+    wheel.insert(Entry::new(request(100, 5.0), 1000));
+    wheel.insert(Entry::new(request(50, 1.5), 1000));
+    wheel.insert(Entry::new(request(25, 12.0), 3000));
+    wheel.advance(5.seconds());
+
+    let partial = wheel.read().interval_and_lower(5.seconds()).unwrap();
+    assert_eq!(
+        partial,
+        RequestPartial {
+            bytes: 175,
+            min_latency: 1.5,
+            max_latency: 12.0,
+            requests: 3,
+        }
+    );
+}
+
+#[test]
+fn derive_tuple_struct_test() {
+    // derived aggregators are named after their path so snapshots can tell them apart
+    assert_eq!(TrafficAggregator::NAME, Some("derive::TrafficAggregator"));
+    assert_eq!(RequestAggregator::name(), "derive::RequestAggregator");
+
+    let inverse = TrafficAggregator::combine_inverse().unwrap();
+    assert_eq!(inverse(Traffic(10, 4), Traffic(3, 1)), Traffic(7, 3));
+
+    let mut wheel: RwWheel<TrafficAggregator> = RwWheel::new(0);
+    for second in 0..10u64 {
+        wheel.insert(Entry::new(Packet(second as u32, 0, ()), second * 1000));
+    }
+    wheel.advance(10.seconds());
+
+    // every field is invertible so prefix-sum wheels are supported
+    wheel.read().to_prefix_wheels();
+    let traffic = wheel
+        .read()
+        .combine_range(WheelRange::new_unchecked(2000, 6000))
+        .unwrap();
+    assert_eq!(traffic, Traffic(2 + 3 + 4 + 5, 4));
+}
@@ -1,8 +1,7 @@
 //! uwheel-stats is a sub-crate of uwheel which contains profiling utility.
-use core::cell::RefCell;
 use minstant::Instant;
 use sketches_ddsketch::{Config, DDSketch};
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 pub fn sketch_percentiles(sketch: &DDSketch) -> Percentiles {
     Percentiles {
@@ -59,31 +58,30 @@ impl std::fmt::Debug for Percentiles {
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Clone)]
 pub struct Sketch {
-    inner: Rc<RefCell<DDSketch>>,
+    inner: Arc<Mutex<DDSketch>>,
 }
 impl Default for Sketch {
     fn default() -> Self {
         Self {
-            inner: Rc::new(RefCell::new(DDSketch::new(Config::new(0.01, 2048, 1.0e-9)))),
+            inner: Arc::new(Mutex::new(DDSketch::new(Config::new(0.01, 2048, 1.0e-9)))),
         }
     }
 }
 impl Sketch {
     #[inline]
     pub fn add(&self, data: f64) {
-        self.inner.borrow_mut().add(data)
+        self.inner.lock().unwrap().add(data)
     }
     pub fn merge(&self, other: Self) {
-        self.inner
-            .borrow_mut()
-            .merge(&other.inner.borrow())
-            .unwrap()
+        // clone first as `other` may share the same sketch
+        let other = other.inner.lock().unwrap().clone();
+        self.inner.lock().unwrap().merge(&other).unwrap()
     }
     pub fn percentiles(&self) -> Percentiles {
-        sketch_percentiles(&self.inner.borrow())
+        sketch_percentiles(&self.inner.lock().unwrap())
     }
     pub fn count(&self) -> usize {
-        self.inner.borrow().count()
+        self.inner.lock().unwrap().count()
     }
 }
 
@@ -98,7 +96,7 @@ impl Measure {
     #[allow(unused_variables)]
     pub fn new(sketch: &Sketch) -> Measure {
         Measure {
-            sketch: sketch.clone(), // clones Rc
+            sketch: sketch.clone(), // clones Arc
             start: Instant::now(),
         }
     }
@@ -3,26 +3,52 @@
 use core::hash::Hash;
 use uwheel::{
     aggregator::Aggregator,
-    wheels::read::{hierarchical::WheelRange, ReaderWheel},
+    wheels::read::{hierarchical::WheelRange, ExecutionPlan, ReaderWheel},
 };
 
 pub trait Key: PartialEq + Ord + Hash + Eq + Send + Sync + Clone + 'static {}
 impl<T> Key for T where T: PartialEq + Ord + Hash + Eq + Send + Sync + Clone + 'static {}
 
 use concurrent_map::{ConcurrentMap, Minimum};
-use core::{borrow::Borrow, ops::RangeBounds};
+use core::{borrow::Borrow, mem, ops::RangeBounds};
 use std::{
     cmp::Ordering,
-    collections::{BinaryHeap, HashMap},
+    collections::{btree_map::Entry, BTreeMap, BinaryHeap, HashMap},
     ops::Bound,
 };
-use uwheel::{wheels::read::aggregation::combine_or_insert, DeltaState, Duration, OffsetDateTime};
+use uwheel::{
+    wheels::read::aggregation::combine_or_insert,
+    DeltaState,
+    Duration,
+    OffsetDateTime,
+    Window,
+};
 
 pub enum TopKQuery {
     TimeFilter(OffsetDateTime, OffsetDateTime),
     Landmark,
 }
 
+/// An execution plan of a range query across a range of keys
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum TreePlan<K> {
+    /// The query is answered by the star wheel
+    Star(ExecutionPlan),
+    /// The query is answered by combining the result of each keyed wheel
+    Keys(Vec<(K, ExecutionPlan)>),
+}
+
+impl<K> TreePlan<K> {
+    /// Returns the total cost of the plan
+    pub fn cost(&self) -> usize {
+        match self {
+            TreePlan::Star(plan) => plan.cost(),
+            TreePlan::Keys(plans) => plans.iter().map(|(_, plan)| plan.cost()).sum(),
+        }
+    }
+}
+
 /// A concurrent Wheel-Tree data structure
 ///
 /// It is backed by a lock-free B+tree [implementation](https://github.com/komora-io/concurrent-map)
@@ -31,6 +57,7 @@ pub enum TopKQuery {
 pub struct WheelTree<K: Key + Minimum, A: Aggregator + Clone>
 where
     A::PartialAggregate: Sync,
+    ReaderWheel<A>: Send + Sync,
 {
     star: ReaderWheel<A>,
     inner: ConcurrentMap<K, ReaderWheel<A>>,
@@ -38,6 +65,7 @@ where
 impl<K: Key + Minimum, A: Aggregator + Clone + 'static> Default for WheelTree<K, A>
 where
     A::PartialAggregate: Sync,
+    ReaderWheel<A>: Send + Sync,
 {
     fn default() -> Self {
         Self::new()
@@ -47,6 +75,7 @@ where
 impl<K: Key + Minimum, A: Aggregator + Clone + 'static> WheelTree<K, A>
 where
     A::PartialAggregate: Sync,
+    ReaderWheel<A>: Send + Sync,
 {
     pub fn new() -> Self {
         Self {
@@ -152,6 +181,242 @@ where
         }
     }
 
+    /// Combines partial aggregates within the given date range [start, end) across a range of keys
+    ///
+    /// An unbounded key range is answered directly by the star wheel.
+    #[inline]
+    pub fn combine_range<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<A::PartialAggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        Self::combine_wheels(&self.wheels(keys), range.into())
+    }
+
+    /// Combines aggregates within the given date range [start, end) across a range of keys and lowers the result
+    #[inline]
+    pub fn combine_range_and_lower<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<A::Aggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        self.combine_range(keys, range).map(A::lower)
+    }
+
+    /// Returns the execution plan of a combine range query across a range of keys
+    ///
+    /// Returns `None` if none of the wheels can answer the range.
+    pub fn explain_combine_range<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<TreePlan<K>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        let range = range.into();
+        match (keys.start_bound(), keys.end_bound()) {
+            (Bound::Unbounded, Bound::Unbounded) => self
+                .star
+                .as_ref()
+                .explain_combine_range(range)
+                .map(TreePlan::Star),
+            _ => {
+                let plans: Vec<_> = self
+                    .inner
+                    .range(keys)
+                    .filter_map(|(key, wheel)| {
+                        wheel
+                            .as_ref()
+                            .explain_combine_range(range)
+                            .map(|plan| (key, plan))
+                    })
+                    .collect();
+                (!plans.is_empty()).then_some(TreePlan::Keys(plans))
+            }
+        }
+    }
+
+    /// Groups the data of a range of keys into aggregates based on the given range and interval
+    ///
+    /// Each keyed wheel is grouped on its own (see [uwheel::Haw::group_by_partial]) and partial aggregates
+    /// of the same group are combined across wheels before being lowered.
+    /// Returns `None` if the range or interval is invalid or if none of the wheels can answer the range.
+    pub fn group_by<Q, R>(
+        &self,
+        keys: R,
+        range: WheelRange,
+        interval: Duration,
+    ) -> Option<Vec<(u64, A::Aggregate)>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        let groups = self.merge_by_time(keys, |wheel| {
+            wheel.as_ref().group_by_partial(range, interval)
+        })?;
+        Some(
+            groups
+                .into_iter()
+                .map(|(ts, partial)| (ts, A::lower(partial)))
+                .collect(),
+        )
+    }
+
+    /// Returns partial aggregates within the given date range [start, end) across a range of keys
+    ///
+    /// The series of each keyed wheel is combined slot by slot using the lowest granularity of the range.
+    /// Returns `None` if none of the wheels can answer the range.
+    pub fn range<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<Vec<(u64, A::PartialAggregate)>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        let range = range.into();
+        self.merge_by_time(keys, |wheel| wheel.range(range))
+    }
+
+    /// Returns aggregates within the given date range [start, end) across a range of keys
+    pub fn range_and_lower<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<Vec<(u64, A::Aggregate)>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        self.range(keys, range).map(|series| {
+            series
+                .into_iter()
+                .map(|(ts, agg)| (ts, A::lower(agg)))
+                .collect()
+        })
+    }
+
+    /// Executes a tumbling or sliding window over the given date range across a range of keys
+    ///
+    /// Windows are combined from groups (see [Self::group_by]) whose interval evenly divides both the length
+    /// and the slide of the window. Returns the start timestamp and aggregate of every window that fits within
+    /// the range. Returns `None` for session windows, invalid ranges or if none of the wheels can answer the range.
+    pub fn window<Q, R>(
+        &self,
+        keys: R,
+        range: WheelRange,
+        window: Window,
+    ) -> Option<Vec<(u64, A::Aggregate)>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        let (length, slide) = match window {
+            Window::Tumbling { range } => (range, range),
+            Window::Sliding { range, slide } => (range, slide),
+            Window::Session { .. } => return None,
+        };
+        let length_ms = length.whole_milliseconds() as u64;
+        let slide_ms = slide.whole_milliseconds() as u64;
+        if length_ms == 0 || slide_ms == 0 {
+            return None;
+        }
+
+        let pane_ms = gcd(length_ms, slide_ms);
+        let pane = Duration::milliseconds(pane_ms as i64);
+        let panes =
+            self.merge_by_time(keys, |wheel| wheel.as_ref().group_by_partial(range, pane))?;
+
+        let end = range.end_ms();
+        let windows = panes
+            .windows((length_ms / pane_ms) as usize)
+            .step_by((slide_ms / pane_ms) as usize)
+            // only keep windows that fit within the range
+            .filter(|panes| panes[0].0 + length_ms <= end)
+            .map(|panes| {
+                let agg = panes.iter().fold(A::IDENTITY, |acc, (_, partial)| {
+                    A::combine(acc, partial.clone())
+                });
+                (panes[0].0, A::lower(agg))
+            })
+            .collect();
+        Some(windows)
+    }
+
+    // Combines timestamped partial aggregates of a range of keys ordered by time
+    //
+    // Wheels that cannot answer the query are skipped. Returns `None` if none of the wheels answered it.
+    fn merge_by_time<Q, R>(
+        &self,
+        keys: R,
+        query: impl Fn(&ReaderWheel<A>) -> Option<Vec<(u64, A::PartialAggregate)>>,
+    ) -> Option<Vec<(u64, A::PartialAggregate)>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        let mut merged: Option<BTreeMap<u64, A::PartialAggregate>> = None;
+        for wheel in self.wheels(keys) {
+            if let Some(series) = query(&wheel) {
+                let merged = merged.get_or_insert_with(BTreeMap::new);
+                for (ts, partial) in series {
+                    match merged.entry(ts) {
+                        Entry::Occupied(mut acc) => {
+                            let acc = acc.get_mut();
+                            *acc = A::combine(mem::take(acc), partial);
+                        }
+                        Entry::Vacant(slot) => {
+                            slot.insert(partial);
+                        }
+                    }
+                }
+            }
+        }
+        merged.map(|merged| merged.into_iter().collect())
+    }
+
+    // Returns the wheels of a range of keys where an unbounded range is answered by the star wheel
+    fn wheels<Q, R>(&self, keys: R) -> Vec<ReaderWheel<A>>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + PartialEq,
+    {
+        match (keys.start_bound(), keys.end_bound()) {
+            (Bound::Unbounded, Bound::Unbounded) => vec![self.star.clone()],
+            _ => self.inner.range(keys).map(|(_, wheel)| wheel).collect(),
+        }
+    }
+
+    #[inline]
+    fn combine_wheels(wheels: &[ReaderWheel<A>], range: WheelRange) -> Option<A::PartialAggregate> {
+        wheels.iter().fold(None, |mut acc, wheel| {
+            if let Some(agg) = wheel.combine_range(range) {
+                combine_or_insert::<A>(&mut acc, agg);
+            }
+            acc
+        })
+    }
+
     /// Returns the amount of memory used
     pub fn memory_usage_bytes(&self) -> usize {
         let keys = self.inner.len();
@@ -279,10 +544,19 @@ where
     }
 }
 
+// Returns the greatest common divisor of two numbers
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use time::macros::datetime;
-    use uwheel::{aggregator::sum::U64SumAggregator, NumericalDuration};
+    use uwheel::{aggregator::sum::U64SumAggregator, HawConf, NumericalDuration};
 
     use super::*;
 
@@ -330,4 +604,115 @@ mod tests {
             Some(25 + 21)
         );
     }
+
+    #[test]
+    fn wheel_tree_temporal_test() {
+        let mut wheel_tree: WheelTree<usize, U64SumAggregator> = WheelTree::default();
+        let watermark = 1699488000000;
+
+        wheel_tree.merge_delta(
+            1,
+            DeltaState::new(watermark, vec![Some(10), None, Some(15), None]),
+        );
+        wheel_tree.merge_delta(
+            2,
+            DeltaState::new(watermark, vec![Some(20), None, Some(1), None]),
+        );
+        wheel_tree.insert_star(ReaderWheel::from_delta_state(DeltaState::new(
+            watermark,
+            vec![Some(30), None, Some(16), None],
+        )));
+
+        let range = WheelRange::new_unchecked(watermark, watermark + 4000);
+        let series = vec![
+            (watermark, 30),
+            (watermark + 1000, 0),
+            (watermark + 2000, 16),
+            (watermark + 3000, 0),
+        ];
+        assert_eq!(wheel_tree.range(0..3, range), Some(series.clone()));
+        assert_eq!(wheel_tree.range(.., range), Some(series));
+        assert_eq!(wheel_tree.range(3.., range), None);
+
+        assert_eq!(wheel_tree.combine_range_and_lower(1..=1, range), Some(25));
+        assert_eq!(
+            wheel_tree.group_by(0..3, range, 2.seconds()),
+            Some(vec![(watermark, 30), (watermark + 2000, 16)])
+        );
+        assert_eq!(wheel_tree.group_by(3.., range, 2.seconds()), None);
+
+        assert_eq!(
+            wheel_tree.window(0..3, range, Window::sliding(2.seconds(), 1.seconds())),
+            Some(vec![
+                (watermark, 30),
+                (watermark + 1000, 16),
+                (watermark + 2000, 16)
+            ])
+        );
+        assert_eq!(
+            wheel_tree.window(.., range, Window::tumbling(3.seconds())),
+            Some(vec![(watermark, 46)])
+        );
+        assert_eq!(
+            wheel_tree.window(0..3, range, Window::session(1.seconds())),
+            None
+        );
+
+        let range = WheelRange::new_unchecked(watermark, watermark + 2000);
+        match wheel_tree.explain_combine_range(0..3, range) {
+            Some(TreePlan::Keys(plans)) => {
+                assert_eq!(plans.len(), 2);
+                assert_eq!(plans[0].0, 1);
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+        assert!(matches!(
+            wheel_tree.explain_combine_range(.., range),
+            Some(TreePlan::Star(_))
+        ));
+        assert!(wheel_tree.explain_combine_range(3.., range).is_none());
+    }
+
+    #[test]
+    fn wheel_tree_group_by_test() {
+        let wheel_tree: WheelTree<usize, U64SumAggregator> = WheelTree::default();
+        let watermark = 1699488000000;
+        // UTC+01:00 without a milliseconds wheel
+        let conf = HawConf::default()
+            .with_watermark(watermark)
+            .with_utc_offset(3600);
+        for key in 1..=2 {
+            let wheel: ReaderWheel<U64SumAggregator> = ReaderWheel::with_conf(conf);
+            wheel.delta_advance((1..=6).map(|i| Some(i * key as u64)));
+            wheel_tree.insert(key, wheel);
+        }
+
+        // groups follow the wheels, which ignore the sub-second start of the range
+        let range = WheelRange::new_unchecked(watermark + 500, watermark + 6000);
+        let expected = wheel_tree.get(&1).unwrap().group_by(range, 2.seconds());
+        assert_eq!(
+            expected,
+            Some(vec![
+                (watermark, 3),
+                (watermark + 2000, 7),
+                (watermark + 4000, 11)
+            ])
+        );
+        assert_eq!(wheel_tree.group_by(1..=1, range, 2.seconds()), expected);
+        assert_eq!(
+            wheel_tree.group_by(1..=2, range, 2.seconds()),
+            Some(vec![
+                (watermark, 9),
+                (watermark + 2000, 21),
+                (watermark + 4000, 33)
+            ])
+        );
+
+        // intervals below the tick size of the wheels cannot be answered
+        assert_eq!(wheel_tree.group_by(1..=2, range, 500.milliseconds()), None);
+        assert_eq!(
+            wheel_tree.window(1..=2, range, Window::sliding(4.seconds(), 2.seconds())),
+            Some(vec![(watermark, 30), (watermark + 2000, 54)])
+        );
+    }
 }
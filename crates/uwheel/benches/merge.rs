@@ -44,20 +44,20 @@ fn merge_large_to_fresh_wheel(bencher: &mut Bencher) {
 fn merge_small_to_fresh_wheel(bencher: &mut Bencher) {
     let wheel = small_wheel();
     bencher.iter(|| {
-        let fresh_wheel = RwWheel::new(0);
+        let mut fresh_wheel = RwWheel::new(0);
         fresh_wheel.merge_read_wheel(wheel.read());
         fresh_wheel
     });
 }
 
 fn merge_same_size_small(bencher: &mut Bencher) {
-    let wheel = small_wheel();
+    let mut wheel = small_wheel();
     let other_wheel = small_wheel();
     bencher.iter(|| wheel.merge_read_wheel(other_wheel.read()));
 }
 
 fn merge_same_size_large(bencher: &mut Bencher) {
-    let wheel = large_wheel();
+    let mut wheel = large_wheel();
     let other_wheel = large_wheel();
     bencher.iter(|| wheel.merge_read_wheel(other_wheel.read()));
 }
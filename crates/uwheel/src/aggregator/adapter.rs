@@ -0,0 +1,391 @@
+use core::{any::type_name, fmt::Debug, marker::PhantomData};
+
+use super::{Aggregator, CombineSimdFn, Compression, InputBounds, InverseFn};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+/// A predicate on the input of an aggregator used by [FilterAggregator]
+///
+/// # Example
+///
+/// ```
+/// use uwheel::aggregator::adapter::Predicate;
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct Even;
+///
+/// impl Predicate<u32> for Even {
+///     fn test(input: &u32) -> bool {
+///         input % 2 == 0
+///     }
+/// }
+/// ```
+pub trait Predicate<T>: Default + Debug + Clone + Copy + Send + 'static {
+    /// Returns `true` if the input should be aggregated
+    fn test(input: &T) -> bool;
+}
+
+/// A conversion from a richer input type into the input of an aggregator used by [MapAggregator]
+///
+/// # Example
+///
+/// ```
+/// use uwheel::aggregator::adapter::Mapper;
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct Bytes;
+///
+/// // maps (status, bytes) to bytes
+/// impl Mapper for Bytes {
+///     type Input = (u16, u64);
+///     type Output = u64;
+///
+///     fn map((_status, bytes): Self::Input) -> Self::Output {
+///         bytes
+///     }
+/// }
+/// ```
+pub trait Mapper: Default + Debug + Clone + Copy + Send + 'static {
+    /// The input type of the adapted aggregator
+    type Input: InputBounds;
+    /// The input type of the inner aggregator
+    type Output;
+
+    /// Converts an input into the input of the inner aggregator
+    fn map(input: Self::Input) -> Self::Output;
+}
+
+/// Aggregator adapter that only aggregates inputs matching a [Predicate]
+///
+/// Inputs for which the predicate returns `false` are ignored by the inner aggregator `A`.
+/// Partial aggregates, aggregates and optional capabilities such as `combine_inverse` are those
+/// of `A`.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{
+///     aggregator::{
+///         adapter::{FilterAggregator, Predicate},
+///         sum::U32SumAggregator,
+///     },
+///     Entry,
+///     NumericalDuration,
+///     RwWheel,
+/// };
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct Large;
+///
+/// impl Predicate<u32> for Large {
+///     fn test(input: &u32) -> bool {
+///         *input >= 100
+///     }
+/// }
+///
+/// let mut wheel: RwWheel<FilterAggregator<U32SumAggregator, Large>> = RwWheel::new(0);
+/// wheel.insert(Entry::new(5, 1000));
+/// wheel.insert(Entry::new(150, 2000));
+/// wheel.advance(5.seconds());
+///
+/// assert_eq!(wheel.read().interval_and_lower(5.seconds()), Some(150));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FilterAggregator<A, P>(PhantomData<(A, P)>);
+
+impl<A, P> Default for FilterAggregator<A, P> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A, P> Aggregator for FilterAggregator<A, P>
+where
+    A: Aggregator,
+    P: Predicate<A::Input>,
+{
+    const IDENTITY: Self::PartialAggregate = A::IDENTITY;
+
+    type Input = A::Input;
+    // `None` until an input has passed the predicate
+    type MutablePartialAggregate = Option<A::MutablePartialAggregate>;
+    type PartialAggregate = A::PartialAggregate;
+    type Aggregate = A::Aggregate;
+
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::adapter::FilterAggregator<{}, {}>",
+            A::name(),
+            type_name::<P>()
+        )
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        P::test(&input).then(|| A::lift(input))
+    }
+
+    #[inline]
+    fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        if !P::test(&input) {
+            return;
+        }
+        match mutable {
+            Some(mutable) => A::combine_mutable(mutable, input),
+            None => *mutable = Some(A::lift(input)),
+        }
+    }
+
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        mutable.map(A::freeze).unwrap_or(A::IDENTITY)
+    }
+
+    #[inline]
+    fn combine(a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        A::combine(a, b)
+    }
+
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        A::lower(a)
+    }
+
+    fn combine_inverse() -> Option<InverseFn<Self::PartialAggregate>> {
+        A::combine_inverse()
+    }
+
+    fn combine_simd() -> Option<CombineSimdFn<Self::PartialAggregate>> {
+        A::combine_simd()
+    }
+
+    fn compression() -> Option<Compression<Self::PartialAggregate>> {
+        A::compression()
+    }
+}
+
+/// Aggregator adapter that converts its input using a [Mapper] before aggregating it
+///
+/// This allows a single event type to feed aggregators that operate on one of its fields.
+/// Partial aggregates, aggregates and optional capabilities such as `combine_inverse` are those
+/// of the inner aggregator `A`.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{
+///     aggregator::{
+///         adapter::{FilterAggregator, MapAggregator, Mapper, Predicate},
+///         sum::U64SumAggregator,
+///     },
+///     Entry,
+///     NumericalDuration,
+///     RwWheel,
+/// };
+///
+/// // (status, bytes)
+/// type Request = (u16, u64);
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct Bytes;
+///
+/// impl Mapper for Bytes {
+///     type Input = Request;
+///     type Output = u64;
+///
+///     fn map((_status, bytes): Request) -> u64 {
+///         bytes
+///     }
+/// }
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct ServerError;
+///
+/// impl Predicate<Request> for ServerError {
+///     fn test((status, _bytes): &Request) -> bool {
+///         *status >= 500
+///     }
+/// }
+///
+/// // sum of bytes where status >= 500
+/// type ErrorBytes = FilterAggregator<MapAggregator<U64SumAggregator, Bytes>, ServerError>;
+///
+/// let mut wheel: RwWheel<ErrorBytes> = RwWheel::new(0);
+/// wheel.insert(Entry::new((200, 100), 1000));
+/// wheel.insert(Entry::new((503, 20), 2000));
+/// wheel.advance(5.seconds());
+///
+/// assert_eq!(wheel.read().interval_and_lower(5.seconds()), Some(20));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MapAggregator<A, M>(PhantomData<(A, M)>);
+
+impl<A, M> Default for MapAggregator<A, M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A, M> Aggregator for MapAggregator<A, M>
+where
+    A: Aggregator,
+    M: Mapper<Output = A::Input>,
+{
+    const IDENTITY: Self::PartialAggregate = A::IDENTITY;
+
+    type Input = M::Input;
+    type MutablePartialAggregate = A::MutablePartialAggregate;
+    type PartialAggregate = A::PartialAggregate;
+    type Aggregate = A::Aggregate;
+
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::adapter::MapAggregator<{}, {}>",
+            A::name(),
+            type_name::<M>()
+        )
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        A::lift(M::map(input))
+    }
+
+    #[inline]
+    fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        A::combine_mutable(mutable, M::map(input))
+    }
+
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        A::freeze(mutable)
+    }
+
+    #[inline]
+    fn combine(a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        A::combine(a, b)
+    }
+
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        A::lower(a)
+    }
+
+    fn combine_inverse() -> Option<InverseFn<Self::PartialAggregate>> {
+        A::combine_inverse()
+    }
+
+    fn combine_simd() -> Option<CombineSimdFn<Self::PartialAggregate>> {
+        A::combine_simd()
+    }
+
+    fn compression() -> Option<Compression<Self::PartialAggregate>> {
+        A::compression()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aggregator::{max::U16MaxAggregator, sum::U64SumAggregator},
+        duration::NumericalDuration,
+        Entry,
+        RwWheel,
+        WheelRange,
+    };
+
+    #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+    #[derive(Debug, Clone, Copy)]
+    struct Request {
+        bytes: u64,
+        status: u16,
+    }
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct Bytes;
+
+    impl Mapper for Bytes {
+        type Input = Request;
+        type Output = u64;
+
+        fn map(request: Request) -> u64 {
+            request.bytes
+        }
+    }
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct Status;
+
+    impl Mapper for Status {
+        type Input = Request;
+        type Output = u16;
+
+        fn map(request: Request) -> u16 {
+            request.status
+        }
+    }
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct ServerError;
+
+    impl Predicate<Request> for ServerError {
+        fn test(request: &Request) -> bool {
+            request.status >= 500
+        }
+    }
+
+    type TotalBytes = MapAggregator<U64SumAggregator, Bytes>;
+    type ErrorBytes = FilterAggregator<TotalBytes, ServerError>;
+
+    #[test]
+    fn filter_map_test() {
+        let mut wheel: RwWheel<(
+            TotalBytes,
+            ErrorBytes,
+            MapAggregator<U16MaxAggregator, Status>,
+        )> = RwWheel::new(0);
+        for second in 0..10u64 {
+            let request = Request {
+                bytes: 10,
+                status: if second % 3 == 0 { 500 } else { 200 },
+            };
+            wheel.insert(Entry::new((request, request, request), second * 1000));
+        }
+        wheel.advance(10.seconds());
+
+        // seconds 0, 3, 6 and 9 failed
+        assert_eq!(
+            wheel.read().interval_and_lower(10.seconds()),
+            Some((100, 40, 500))
+        );
+        assert_eq!(
+            wheel
+                .read()
+                .combine_range_and_lower(WheelRange::new_unchecked(1000, 3000)),
+            Some((20, 0, 200))
+        );
+    }
+
+    #[test]
+    fn filter_map_capabilities_test() {
+        assert_eq!(ErrorBytes::invertible(), U64SumAggregator::invertible());
+        assert_eq!(ErrorBytes::simd_support(), U64SumAggregator::simd_support());
+
+        let request = Request {
+            bytes: 5,
+            status: 404,
+        };
+        assert_eq!(ErrorBytes::lift(request), None);
+        assert_eq!(ErrorBytes::freeze(None), U64SumAggregator::IDENTITY);
+
+        let mut mutable = ErrorBytes::lift(request);
+        ErrorBytes::combine_mutable(
+            &mut mutable,
+            Request {
+                bytes: 7,
+                status: 502,
+            },
+        );
+        assert_eq!(mutable, Some(7));
+    }
+}
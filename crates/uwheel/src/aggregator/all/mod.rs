@@ -91,6 +91,7 @@ impl PartialEq for AggState {
 pub struct AllAggregator;
 
 impl Aggregator for AllAggregator {
+    const NAME: Option<&'static str> = Some("uwheel::aggregator::all::AllAggregator");
     const IDENTITY: Self::PartialAggregate = AggState::identity();
     type Input = f64;
     type Aggregate = AggState;
@@ -7,6 +7,7 @@ macro_rules! avg_impl {
         pub struct $struct;
 
         impl Aggregator for $struct {
+            const NAME: Option<&'static str> = Some(concat!(module_path!(), "::", stringify!($struct)));
             const IDENTITY: Self::PartialAggregate = (0 as $type, 0 as $type);
             type Input = $type;
             type MutablePartialAggregate = $pa;
@@ -0,0 +1,228 @@
+use core::{any::type_name, hash::Hash, marker::PhantomData};
+
+use crate::{
+    aggregator::{hash::KeyHasher, Aggregator, InputBounds, PartialAggregateType},
+    wheels::read::ReaderWheel,
+    WheelRange,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+#[cfg(feature = "serde")]
+use serde_big_array::BigArray;
+
+/// A Bloom filter with `W * 64` bits and `K` hash functions
+///
+/// A lookup never returns a false negative, while the false positive rate grows with the number of
+/// inserted keys. With `n` keys the rate is roughly `(1 - e^(-K * n / (W * 64)))^K`, which for the
+/// default of 16384 bits and 4 hash functions stays below 1% up to about 1500 keys.
+/// Filters are merged by OR-ing their bits.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BloomFilter<const W: usize, const K: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "BigArray"))]
+    words: [u64; W],
+}
+
+impl<const W: usize, const K: usize> Default for BloomFilter<W, K> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<const W: usize, const K: usize> PartialAggregateType for BloomFilter<W, K> {}
+
+impl<const W: usize, const K: usize> BloomFilter<W, K> {
+    const BITS: u64 = W as u64 * 64;
+
+    /// Returns an empty filter
+    pub const fn identity() -> Self {
+        Self { words: [0; W] }
+    }
+
+    /// Inserts a key into the filter
+    #[inline]
+    pub fn insert<T: Hash + ?Sized>(&mut self, key: &T) {
+        for bit in Self::bits(key) {
+            self.words[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns `true` if the key may have been inserted and `false` if it definitely was not
+    #[inline]
+    pub fn contains<T: Hash + ?Sized>(&self, key: &T) -> bool {
+        Self::bits(key).all(|bit| self.words[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Merges another filter into this one
+    #[inline]
+    pub fn merge(&mut self, other: &Self) {
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= other;
+        }
+    }
+
+    /// Returns `true` if no keys have been inserted into the filter
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    // Derives the K bit positions of a key using double hashing
+    #[inline]
+    fn bits<T: Hash + ?Sized>(key: &T) -> impl Iterator<Item = u64> {
+        let hash = KeyHasher::hash(key);
+        let (h1, h2) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        (0..K as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % Self::BITS)
+    }
+}
+
+/// Set membership aggregation using a Bloom filter
+///
+/// Keys of any hashable type `T` are inserted into a [BloomFilter] with `W * 64` bits and `K` hash
+/// functions. Filters are OR-combined across the wheel hierarchy, which makes it possible to ask
+/// whether a key appeared within a time range (see [ReaderWheel::contains]).
+///
+/// Note that a filter is kept per wheel slot and that filters of coarser levels hold every key of
+/// their time span, so `W` should be sized for the number of distinct keys per day or week.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::bloom::BloomAggregator, Entry, NumericalDuration, RwWheel, WheelRange};
+///
+/// let mut wheel: RwWheel<BloomAggregator<u64>> = RwWheel::new(0);
+/// wheel.insert(Entry::new(42, 1000));
+/// wheel.insert(Entry::new(7, 3000));
+/// wheel.advance(5.seconds());
+///
+/// assert_eq!(wheel.read().contains(&42, WheelRange::new_unchecked(0, 2000)), Some(true));
+/// assert_eq!(wheel.read().contains(&7, WheelRange::new_unchecked(0, 2000)), Some(false));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BloomAggregator<T, const W: usize = 256, const K: usize = 4>(PhantomData<T>);
+
+impl<T, const W: usize, const K: usize> Default for BloomAggregator<T, W, K> {
+    // have to implement manually as T does not implement Default
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, const W: usize, const K: usize> Aggregator for BloomAggregator<T, W, K>
+where
+    T: InputBounds + Hash + 'static,
+{
+    const IDENTITY: Self::PartialAggregate = BloomFilter::identity();
+    const NAME: Option<&'static str> = Some("uwheel::aggregator::bloom::BloomAggregator");
+
+    type Input = T;
+    type MutablePartialAggregate = BloomFilter<W, K>;
+    type PartialAggregate = BloomFilter<W, K>;
+    type Aggregate = BloomFilter<W, K>;
+
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::bloom::BloomAggregator<{}, {W}, {K}>",
+            type_name::<T>()
+        )
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        let mut filter = BloomFilter::identity();
+        filter.insert(&input);
+        filter
+    }
+
+    #[inline]
+    fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        mutable.insert(&input);
+    }
+
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        mutable
+    }
+
+    #[inline]
+    fn combine(mut a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        a.merge(&b);
+        a
+    }
+
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        a
+    }
+}
+
+impl<T, const W: usize, const K: usize> ReaderWheel<BloomAggregator<T, W, K>>
+where
+    T: InputBounds + Hash + 'static,
+{
+    /// Checks whether a key may have been inserted within the given date range [start, end)
+    ///
+    /// The filters are combined using the same execution plan as [ReaderWheel::combine_range],
+    /// which touches the minimal set of wheel slots covering the range.
+    ///
+    /// Returns `Some(false)` if the key definitely did not appear in the range, `Some(true)` if it
+    /// may have appeared, and `None` if the range cannot be answered by the wheel.
+    pub fn contains(&self, key: &T, range: impl Into<WheelRange>) -> Option<bool> {
+        self.combine_range(range).map(|filter| filter.contains(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{duration::NumericalDuration, Entry, RwWheel};
+
+    #[test]
+    fn bloom_filter_test() {
+        let mut filter = BloomFilter::<256, 4>::identity();
+        assert!(filter.is_empty());
+        for key in 0..1000u32 {
+            filter.insert(&key);
+        }
+        assert!(filter.count_ones() <= 4000);
+        assert!((0..1000u32).all(|key| filter.contains(&key)));
+
+        let false_positives = (1000..11000u32).filter(|key| filter.contains(key)).count();
+        assert!(false_positives < 100, "{false_positives} false positives");
+
+        let mut other = BloomFilter::<256, 4>::identity();
+        other.insert("hello");
+        assert!(!filter.contains("hello"));
+        filter.merge(&other);
+        assert!(filter.contains("hello"));
+    }
+
+    #[test]
+    fn bloom_wheel_test() {
+        let mut wheel: RwWheel<BloomAggregator<u32>> = RwWheel::new(0);
+        // key i is inserted at minute i
+        for minute in 0..90u64 {
+            wheel.insert(Entry::new(minute as u32, minute * 60_000));
+        }
+        wheel.advance(2.hours());
+        let read = wheel.read();
+
+        let hour = WheelRange::new_unchecked(0, 3_600_000);
+        assert_eq!(read.contains(&10, hour), Some(true));
+        assert_eq!(read.contains(&75, hour), Some(false));
+        assert_eq!(
+            read.contains(&75, WheelRange::new_unchecked(3_600_000, 7_200_000)),
+            Some(true)
+        );
+        // a single minute slot
+        let minute = WheelRange::new_unchecked(3_660_000, 3_720_000);
+        assert_eq!(read.contains(&61, minute), Some(true));
+        assert_eq!(read.contains(&75, minute), Some(false));
+        assert_eq!(read.contains(&500, hour), Some(false));
+    }
+}
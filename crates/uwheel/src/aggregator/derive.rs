@@ -0,0 +1,53 @@
+//! Support code for `#[derive(Aggregator)]`, not meant to be used directly
+
+#[cfg(feature = "serde")]
+pub use serde;
+
+/// Numeric types supported by fields of derived aggregators
+pub trait Numeric: Copy {
+    /// The additive identity
+    const ZERO: Self;
+    /// The smallest value of the type
+    const MIN: Self;
+    /// The largest value of the type
+    const MAX: Self;
+}
+
+macro_rules! numeric_impl {
+    ($($type:ty),*) => {
+        $(
+            impl Numeric for $type {
+                const ZERO: Self = 0 as $type;
+                const MIN: Self = <$type>::MIN;
+                const MAX: Self = <$type>::MAX;
+            }
+        )*
+    };
+}
+
+numeric_impl!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+/// Defines the partial aggregate of a derived aggregator with serde support if enabled
+#[cfg(feature = "serde")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __derive_partial {
+    ($($item:tt)*) => {
+        #[derive(
+            ::uwheel::aggregator::derive::serde::Serialize,
+            ::uwheel::aggregator::derive::serde::Deserialize,
+        )]
+        #[serde(crate = "::uwheel::aggregator::derive::serde")]
+        $($item)*
+    };
+}
+
+/// Defines the partial aggregate of a derived aggregator with serde support if enabled
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __derive_partial {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
@@ -0,0 +1,384 @@
+use core::{any::type_name, fmt::Debug, marker::PhantomData};
+
+use crate::aggregator::{Aggregator, PartialAggregateType};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+#[cfg(feature = "simd")]
+use core::simd::u64x8;
+
+#[cfg(feature = "serde")]
+use serde_big_array::BigArray;
+
+/// Upper bounds of the buckets of a [HistogramAggregator]
+///
+/// # Example
+///
+/// ```
+/// use uwheel::aggregator::histogram::BucketBounds;
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct LatencyBuckets;
+///
+/// impl BucketBounds<4> for LatencyBuckets {
+///     const BOUNDS: [f64; 4] = [5.0, 10.0, 50.0, 100.0];
+/// }
+/// ```
+pub trait BucketBounds<const N: usize>: Default + Debug + Clone + Copy + Send + 'static {
+    /// Inclusive upper bounds of the buckets in ascending order
+    const BOUNDS: [f64; N];
+}
+
+/// Per-bucket counts of a fixed-bucket histogram
+///
+/// Bucket `i` counts the values in `(BOUNDS[i - 1], BOUNDS[i]]` while values above the last bound
+/// are counted separately (the `+Inf` bucket). The sum of all values is tracked as well.
+/// `NaN` values are ignored.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Histogram<const N: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "BigArray"))]
+    counts: [u64; N],
+    overflow: u64,
+    sum: f64,
+}
+
+impl<const N: usize> Default for Histogram<N> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<const N: usize> PartialAggregateType for Histogram<N> {}
+
+impl<const N: usize> Histogram<N> {
+    /// Returns an empty histogram
+    pub const fn identity() -> Self {
+        Self {
+            counts: [0; N],
+            overflow: 0,
+            sum: 0.0,
+        }
+    }
+
+    /// Adds a value to the bucket given by `bounds`
+    #[inline]
+    pub fn insert(&mut self, bounds: &[f64; N], value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        // first bucket whose upper bound is greater than or equal to the value
+        let bucket = bounds.partition_point(|bound| *bound < value);
+        match self.counts.get_mut(bucket) {
+            Some(count) => *count += 1,
+            None => self.overflow += 1,
+        }
+        self.sum += value;
+    }
+
+    /// Returns the counts of each bucket
+    pub fn counts(&self) -> &[u64; N] {
+        &self.counts
+    }
+
+    /// Returns the number of values above the last bound
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    /// Returns the number of values
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum::<u64>() + self.overflow
+    }
+
+    /// Returns the sum of the values
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    #[inline]
+    fn merge(mut a: Self, b: Self) -> Self {
+        for (count, other) in a.counts.iter_mut().zip(b.counts.iter()) {
+            *count += other;
+        }
+        a.overflow += b.overflow;
+        a.sum += b.sum;
+        a
+    }
+
+    #[inline]
+    fn inverse(mut a: Self, b: Self) -> Self {
+        for (count, other) in a.counts.iter_mut().zip(b.counts.iter()) {
+            *count = count.saturating_sub(*other);
+        }
+        a.overflow = a.overflow.saturating_sub(b.overflow);
+        a.sum -= b.sum;
+        a
+    }
+
+    #[cfg(feature = "simd")]
+    #[inline]
+    fn simd_merge(slice: &[Self]) -> Self {
+        let mut acc = Self::identity();
+        for histogram in slice {
+            let (dst_chunks, dst_tail) = acc.counts.as_chunks_mut::<{ u64x8::LEN }>();
+            let (src_chunks, src_tail) = histogram.counts.as_chunks::<{ u64x8::LEN }>();
+            for (d, s) in dst_chunks.iter_mut().zip(src_chunks.iter()) {
+                *d = (u64x8::from_array(*d) + u64x8::from_array(*s)).to_array();
+            }
+            for (d, s) in dst_tail.iter_mut().zip(src_tail.iter()) {
+                *d += s;
+            }
+            acc.overflow += histogram.overflow;
+            acc.sum += histogram.sum;
+        }
+        acc
+    }
+}
+
+/// Prometheus-style cumulative histogram produced by lowering a [Histogram]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CumulativeHistogram<const N: usize> {
+    /// Inclusive upper bounds of the buckets
+    pub bounds: [f64; N],
+    /// Number of values less than or equal to each bound
+    pub cumulative: [u64; N],
+    /// Number of values (the `+Inf` bucket)
+    pub count: u64,
+    /// Sum of the values
+    pub sum: f64,
+}
+
+impl<const N: usize> CumulativeHistogram<N> {
+    /// Estimates the value at the given quantile (e.g., 0.99 for p99)
+    ///
+    /// Values are assumed to be uniformly distributed within a bucket and the lower bound of the
+    /// first bucket is taken to be zero. If the quantile falls above the last bound, the last
+    /// bound is returned.
+    ///
+    /// Returns `None` if the histogram is empty or if the quantile is not within `[0, 1]`.
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        if self.count == 0 || N == 0 || !(0.0..=1.0).contains(&quantile) {
+            return None;
+        }
+        let rank = quantile * self.count as f64;
+        let Some(bucket) = self.cumulative.iter().position(|c| *c as f64 >= rank) else {
+            return Some(self.bounds[N - 1]);
+        };
+        let (lower, below) = if bucket == 0 {
+            (0.0f64.min(self.bounds[0]), 0)
+        } else {
+            (self.bounds[bucket - 1], self.cumulative[bucket - 1])
+        };
+        let in_bucket = self.cumulative[bucket] - below;
+        if in_bucket == 0 {
+            return Some(self.bounds[bucket]);
+        }
+        let fraction = (rank - below as f64) / in_bucket as f64;
+        Some(lower + (self.bounds[bucket] - lower) * fraction)
+    }
+}
+
+/// Fixed-bucket histogram aggregation
+///
+/// Aggregates `f64` values into `N` buckets whose upper bounds are defined by [BucketBounds] and
+/// lowers into a [CumulativeHistogram]. The aggregator supports prefix-sum range queries through
+/// `combine_inverse` and SIMD combining of slices with the `simd` feature.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{
+///     aggregator::histogram::{BucketBounds, HistogramAggregator},
+///     Entry,
+///     NumericalDuration,
+///     RwWheel,
+/// };
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct LatencyBuckets;
+///
+/// impl BucketBounds<4> for LatencyBuckets {
+///     const BOUNDS: [f64; 4] = [5.0, 10.0, 50.0, 100.0];
+/// }
+///
+/// let mut wheel: RwWheel<HistogramAggregator<LatencyBuckets, 4>> = RwWheel::new(0);
+/// for latency in [1.0, 7.0, 8.0, 20.0, 500.0] {
+///     wheel.insert(Entry::new(latency, 1000));
+/// }
+/// wheel.advance(5.seconds());
+///
+/// let histogram = wheel.read().interval_and_lower(5.seconds()).unwrap();
+/// assert_eq!(histogram.cumulative, [1, 3, 4, 4]);
+/// assert_eq!(histogram.count, 5);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct HistogramAggregator<B, const N: usize>(PhantomData<B>);
+
+impl<B, const N: usize> Default for HistogramAggregator<B, N> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<B, const N: usize> Aggregator for HistogramAggregator<B, N>
+where
+    B: BucketBounds<N>,
+{
+    const IDENTITY: Self::PartialAggregate = Histogram::identity();
+    const NAME: Option<&'static str> = Some("uwheel::aggregator::histogram::HistogramAggregator");
+
+    type Input = f64;
+    type MutablePartialAggregate = Histogram<N>;
+    type PartialAggregate = Histogram<N>;
+    type Aggregate = CumulativeHistogram<N>;
+
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::histogram::HistogramAggregator<{}, {N}>",
+            type_name::<B>()
+        )
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        let mut histogram = Histogram::identity();
+        histogram.insert(&B::BOUNDS, input);
+        histogram
+    }
+
+    #[inline]
+    fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        mutable.insert(&B::BOUNDS, input);
+    }
+
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        mutable
+    }
+
+    #[inline]
+    fn combine(a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        Histogram::merge(a, b)
+    }
+
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        let mut cumulative = a.counts;
+        for i in 1..N {
+            cumulative[i] += cumulative[i - 1];
+        }
+        CumulativeHistogram {
+            bounds: B::BOUNDS,
+            cumulative,
+            count: a.count(),
+            sum: a.sum,
+        }
+    }
+
+    #[inline]
+    fn combine_inverse(
+    ) -> Option<fn(Self::PartialAggregate, Self::PartialAggregate) -> Self::PartialAggregate> {
+        Some(Histogram::inverse)
+    }
+
+    #[cfg(feature = "simd")]
+    #[inline]
+    fn combine_simd() -> Option<fn(&[Self::PartialAggregate]) -> Self::PartialAggregate> {
+        Some(Histogram::simd_merge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{duration::NumericalDuration, Entry, RwWheel, WheelRange};
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct Buckets;
+
+    impl BucketBounds<10> for Buckets {
+        const BOUNDS: [f64; 10] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0];
+    }
+
+    type Aggregator10 = HistogramAggregator<Buckets, 10>;
+
+    #[test]
+    fn histogram_lower_test() {
+        let mut histogram = Histogram::identity();
+        for value in [0.5, 1.0, 1.5, 3.0, 10.0, 15.0, 99.0, 2000.0] {
+            histogram.insert(&Buckets::BOUNDS, value);
+        }
+        assert_eq!(histogram.counts(), &[2, 1, 1, 1, 1, 0, 1, 0, 0, 0]);
+        assert_eq!(histogram.overflow(), 1);
+
+        let lowered = Aggregator10::lower(histogram);
+        assert_eq!(lowered.cumulative, [2, 3, 4, 5, 6, 6, 7, 7, 7, 7]);
+        assert_eq!(lowered.count, 8);
+        assert_eq!(lowered.sum, 2130.0);
+
+        // rank 4 is the last value of the (2, 5] bucket
+        assert_eq!(lowered.quantile(0.5), Some(5.0));
+        // rank 1 is halfway into the first bucket
+        assert_eq!(lowered.quantile(0.125), Some(0.5));
+        // falls into the +Inf bucket
+        assert_eq!(lowered.quantile(0.99), Some(1000.0));
+        assert_eq!(lowered.quantile(2.0), None);
+        assert_eq!(
+            Aggregator10::lower(Histogram::identity()).quantile(0.5),
+            None
+        );
+    }
+
+    #[test]
+    fn histogram_nan_test() {
+        let mut histogram = Aggregator10::lift(f64::NAN);
+        assert_eq!(histogram, Histogram::identity());
+
+        Aggregator10::combine_mutable(&mut histogram, 3.0);
+        Aggregator10::combine_mutable(&mut histogram, f64::NAN);
+        assert_eq!(histogram.count(), 1);
+        assert_eq!(histogram.counts()[0], 0);
+        assert_eq!(histogram.sum(), 3.0);
+    }
+
+    #[test]
+    fn histogram_combine_test() {
+        let histograms: Vec<Histogram<10>> = (0..100)
+            .map(|i| Aggregator10::lift((i % 30) as f64 * 40.0))
+            .collect();
+
+        let scalar = histograms
+            .iter()
+            .copied()
+            .fold(Aggregator10::IDENTITY, Aggregator10::combine);
+        assert_eq!(Aggregator10::combine_slice(&histograms), Some(scalar));
+        assert_eq!(scalar.count(), 100);
+
+        let inverse = Aggregator10::combine_inverse().unwrap();
+        let head = Aggregator10::combine_slice(&histograms[..40]).unwrap();
+        let tail = Aggregator10::combine_slice(&histograms[40..]).unwrap();
+        assert_eq!(inverse(scalar, head), tail);
+    }
+
+    #[test]
+    fn histogram_prefix_wheel_test() {
+        let mut wheel: RwWheel<Aggregator10> = RwWheel::new(0);
+        for second in 0..60u64 {
+            wheel.insert(Entry::new(second as f64 * 10.0, second * 1000));
+        }
+        wheel.advance(60.seconds());
+
+        let range = WheelRange::new_unchecked(10_000, 40_000);
+        let expected = wheel.read().combine_range(range);
+        wheel.read().to_prefix_wheels();
+        assert_eq!(wheel.read().combine_range(range), expected);
+
+        let lowered = wheel.read().combine_range_and_lower(range).unwrap();
+        assert_eq!(lowered.count, 30);
+        // values 100..=390 with the median around 250
+        assert_eq!(lowered.cumulative[6], 1);
+        assert_eq!(lowered.cumulative[7], 11);
+        assert_eq!(lowered.quantile(0.5), Some(200.0 + 300.0 * (4.0 / 19.0)));
+    }
+}
@@ -0,0 +1,385 @@
+use core::{any::type_name, hash::Hash, marker::PhantomData};
+
+use crate::aggregator::{
+    hash::KeyHasher,
+    Aggregator,
+    Compression,
+    InputBounds,
+    PartialAggregateType,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde_big_array::BigArray;
+
+/// A HyperLogLog sketch with `M` registers
+///
+/// `M` sets the precision of the sketch and must be a power of two between 16 and 65536.
+/// The standard error of the estimate is roughly `1.04 / sqrt(M)`, which is about 1.6% for the
+/// default of 4096 registers. Sketches are merged by taking the maximum of each register.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HyperLogLog<const M: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "BigArray"))]
+    registers: [u8; M],
+}
+
+impl<const M: usize> Default for HyperLogLog<M> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<const M: usize> PartialAggregateType for HyperLogLog<M> {}
+
+impl<const M: usize> HyperLogLog<M> {
+    const VALID: () = assert!(
+        M.is_power_of_two() && M >= 16 && M <= 65536,
+        "the number of registers must be a power of two between 16 and 65536"
+    );
+    const PRECISION: u32 = M.trailing_zeros();
+
+    /// Returns an empty sketch
+    pub const fn identity() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::VALID;
+        Self { registers: [0; M] }
+    }
+
+    /// Inserts a key into the sketch
+    #[inline]
+    pub fn insert<K: Hash>(&mut self, key: &K) {
+        self.insert_hash(KeyHasher::hash(key));
+    }
+
+    /// Inserts an already hashed key into the sketch
+    ///
+    /// The hash must be uniformly distributed over all 64 bits.
+    #[inline]
+    pub fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - Self::PRECISION)) as usize;
+        // position of the first set bit among the remaining bits
+        let rank = ((hash << Self::PRECISION).leading_zeros() + 1).min(65 - Self::PRECISION) as u8;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Merges another sketch into this one
+    #[inline]
+    pub fn merge(&mut self, other: &Self) {
+        for (register, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Returns `true` if no keys have been inserted into the sketch
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|r| *r == 0)
+    }
+
+    /// Returns the estimated number of distinct keys in the sketch
+    pub fn estimate(&self) -> u64 {
+        let m = M as f64;
+        let alpha = match M {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let (sum, zeros) = self
+            .registers
+            .iter()
+            .fold((0.0, 0usize), |(sum, zeros), r| {
+                (sum + pow2_neg(*r), zeros + (*r == 0) as usize)
+            });
+        let estimate = alpha * m * m / sum;
+
+        // use linear counting for small cardinalities
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * ln(m / zeros as f64) + 0.5) as u64
+        } else {
+            (estimate + 0.5) as u64
+        }
+    }
+
+    // Run-length encodes the registers of a slice of sketches.
+    //
+    // Registers never exceed 64, so a byte with the high bit set marks a run of
+    // `(byte & 0x7f) + 2` equal registers whose value follows in the next byte.
+    fn compress(sketches: &[Self]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut registers = sketches.iter().flat_map(|s| s.registers.iter().copied());
+        let mut current = registers.next();
+        while let Some(value) = current {
+            let mut run = 1;
+            current = registers.next();
+            while current == Some(value) && run < 129 {
+                run += 1;
+                current = registers.next();
+            }
+            if run == 1 {
+                out.push(value);
+            } else {
+                out.push(0x80 | (run - 2) as u8);
+                out.push(value);
+            }
+        }
+        out
+    }
+
+    // Decodes sketches encoded by `compress`
+    fn decompress(bytes: &[u8]) -> Vec<Self> {
+        let mut sketches = Vec::new();
+        let mut sketch = Self::identity();
+        let mut index = 0;
+        let mut bytes = bytes.iter();
+        while let Some(byte) = bytes.next() {
+            let (run, value) = if byte & 0x80 != 0 {
+                let value = *bytes.next().expect("truncated HyperLogLog run");
+                ((byte & 0x7f) as usize + 2, value)
+            } else {
+                (1, *byte)
+            };
+            for _ in 0..run {
+                sketch.registers[index] = value;
+                index += 1;
+                if index == M {
+                    sketches.push(sketch);
+                    index = 0;
+                }
+            }
+        }
+        sketches
+    }
+}
+
+/// Distinct count aggregation using HyperLogLog
+///
+/// Keys of any hashable type `K` are inserted into a [HyperLogLog] sketch with `M` registers.
+/// Sketches are merged across the wheel hierarchy so that distinct counts can be queried over any
+/// range, and are lowered into the estimated number of distinct keys.
+///
+/// The aggregator supports compression which run-length encodes the registers of
+/// sparse sketches. Enable it through [DataLayout::Compressed](crate::wheels::read::aggregation::conf::DataLayout::Compressed).
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::hyperloglog::HyperLogLogAggregator, Entry, NumericalDuration, RwWheel};
+///
+/// let mut wheel: RwWheel<HyperLogLogAggregator<u64>> = RwWheel::new(0);
+/// for user in 0..1000u64 {
+///     // every user is seen twice
+///     wheel.insert(Entry::new(user, 1000));
+///     wheel.insert(Entry::new(user, 2000));
+/// }
+/// wheel.advance(5.seconds());
+///
+/// let distinct = wheel.read().interval_and_lower(5.seconds()).unwrap();
+/// assert!(distinct.abs_diff(1000) < 50);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct HyperLogLogAggregator<K, const M: usize = 4096>(PhantomData<K>);
+
+impl<K, const M: usize> Default for HyperLogLogAggregator<K, M> {
+    // have to implement manually as K does not implement Default
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<K, const M: usize> Aggregator for HyperLogLogAggregator<K, M>
+where
+    K: InputBounds + Hash + 'static,
+{
+    const IDENTITY: Self::PartialAggregate = HyperLogLog::identity();
+    const NAME: Option<&'static str> =
+        Some("uwheel::aggregator::hyperloglog::HyperLogLogAggregator");
+
+    type Input = K;
+    type MutablePartialAggregate = HyperLogLog<M>;
+    type PartialAggregate = HyperLogLog<M>;
+    type Aggregate = u64;
+
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::hyperloglog::HyperLogLogAggregator<{}, {M}>",
+            type_name::<K>()
+        )
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        let mut sketch = HyperLogLog::identity();
+        sketch.insert(&input);
+        sketch
+    }
+
+    #[inline]
+    fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        mutable.insert(&input);
+    }
+
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        mutable
+    }
+
+    #[inline]
+    fn combine(mut a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        a.merge(&b);
+        a
+    }
+
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        a.estimate()
+    }
+
+    fn compression() -> Option<Compression<Self::PartialAggregate>> {
+        Some(Compression::new(
+            HyperLogLog::compress,
+            HyperLogLog::decompress,
+        ))
+    }
+}
+
+// Returns 2^-exp
+#[inline]
+fn pow2_neg(exp: u8) -> f64 {
+    f64::from_bits(((1023 - exp as i64) as u64) << 52)
+}
+
+// Natural logarithm for positive values that also works without std
+fn ln(x: f64) -> f64 {
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    // mantissa in [1, 2)
+    let mantissa = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) <= 1/3
+    let z = (mantissa - 1.0) / (mantissa + 1.0);
+    let z2 = z * z;
+    let mut term = z;
+    let mut sum = 0.0;
+    for k in 0..20 {
+        sum += term / (2 * k + 1) as f64;
+        term *= z2;
+    }
+    2.0 * sum + exponent as f64 * core::f64::consts::LN_2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        duration::NumericalDuration,
+        wheels::read::aggregation::{
+            conf::{DataLayout, RetentionPolicy, WheelConf},
+            Wheel,
+            WheelSlot,
+        },
+        Entry,
+        RwWheel,
+        WheelRange,
+    };
+
+    fn assert_close(estimate: u64, exact: u64) {
+        let error = estimate.abs_diff(exact) as f64 / exact as f64;
+        assert!(error < 0.05, "{estimate} is not close to {exact}");
+    }
+
+    #[test]
+    fn hyperloglog_estimate_test() {
+        let mut sketch = HyperLogLog::<4096>::identity();
+        assert!(sketch.is_empty());
+        assert_eq!(sketch.estimate(), 0);
+
+        for key in 0..10u64 {
+            sketch.insert(&key);
+            sketch.insert(&key);
+        }
+        assert_eq!(sketch.estimate(), 10);
+
+        for key in 0..100_000u64 {
+            sketch.insert(&key);
+        }
+        assert_close(sketch.estimate(), 100_000);
+    }
+
+    #[test]
+    fn hyperloglog_merge_test() {
+        let mut a = HyperLogLog::<1024>::identity();
+        let mut b = HyperLogLog::<1024>::identity();
+        for key in 0..20_000u32 {
+            a.insert(&key);
+        }
+        for key in 10_000..30_000u32 {
+            b.insert(&key);
+        }
+        a.merge(&b);
+        assert_close(a.estimate(), 30_000);
+    }
+
+    #[test]
+    fn hyperloglog_compression_test() {
+        let mut sketches = Vec::new();
+        let mut sketch = HyperLogLog::<256>::identity();
+        for key in 0..300u32 {
+            sketch.insert(&key);
+            if key % 100 == 0 {
+                sketches.push(sketch);
+            }
+        }
+        sketches.push(HyperLogLog::identity());
+
+        let compressed = HyperLogLog::compress(&sketches);
+        assert!(compressed.len() < sketches.len() * 256);
+        assert_eq!(HyperLogLog::decompress(&compressed), sketches);
+    }
+
+    #[test]
+    fn hyperloglog_wheel_test() {
+        let mut wheel: RwWheel<HyperLogLogAggregator<u32>> = RwWheel::new(0);
+        // users 0..600 in the first minute and 300..900 in the second
+        for second in 0..120u64 {
+            let offset = (second / 60) as u32 * 300;
+            for user in 0..10u32 {
+                let user = offset + (second % 60) as u32 * 10 + user;
+                wheel.insert(Entry::new(user, second * 1000));
+            }
+        }
+        wheel.advance(2.minutes());
+
+        let read = wheel.read();
+        assert_close(read.interval_and_lower(2.minutes()).unwrap(), 900);
+        let groups = read
+            .group_by(WheelRange::new_unchecked(0, 120_000), 1.minutes())
+            .unwrap();
+        assert_close(groups[0].1, 600);
+        assert_close(groups[1].1, 600);
+    }
+
+    #[test]
+    fn hyperloglog_compressed_layout_test() {
+        let conf = WheelConf::new(1000, 60)
+            .with_retention_policy(RetentionPolicy::Keep)
+            .with_data_layout(DataLayout::Compressed(8));
+        let mut compressed = Wheel::<HyperLogLogAggregator<u32, 256>>::new(conf);
+        let conf = WheelConf::new(1000, 60).with_retention_policy(RetentionPolicy::Keep);
+        let mut wheel = Wheel::<HyperLogLogAggregator<u32, 256>>::new(conf);
+
+        for slot in 0..30u32 {
+            let mut sketch = HyperLogLog::identity();
+            for key in 0..slot * 10 {
+                sketch.insert(&key);
+            }
+            compressed.insert_slot(WheelSlot::new(Some(sketch)));
+            wheel.insert_slot(WheelSlot::new(Some(sketch)));
+            compressed.tick();
+            wheel.tick();
+        }
+
+        assert_eq!(compressed.combine_range(0..30), wheel.combine_range(0..30));
+        assert_eq!(compressed.range(3..12), wheel.range(3..12));
+    }
+}
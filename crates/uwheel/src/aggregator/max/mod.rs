@@ -18,6 +18,8 @@ macro_rules! max_impl {
         pub struct $struct;
 
         impl Aggregator for $struct {
+            const NAME: Option<&'static str> =
+                Some(concat!(module_path!(), "::", stringify!($struct)));
             const IDENTITY: Self::PartialAggregate = <$type>::MIN;
             type Input = $type;
             type MutablePartialAggregate = $pa;
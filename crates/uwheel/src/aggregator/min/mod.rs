@@ -18,6 +18,8 @@ macro_rules! min_impl {
         pub struct $struct;
 
         impl Aggregator for $struct {
+            const NAME: Option<&'static str> =
+                Some(concat!(module_path!(), "::", stringify!($struct)));
             const IDENTITY: Self::PartialAggregate = <$type>::MAX;
 
             type Input = $type;
@@ -65,6 +65,8 @@ macro_rules! min_max_impl {
         pub struct $struct;
 
         impl Aggregator for $struct {
+            const NAME: Option<&'static str> =
+                Some(concat!(module_path!(), "::", stringify!($struct)));
             const IDENTITY: Self::PartialAggregate = MinMaxState {
                 min: <$type>::MAX,
                 max: <$type>::MIN,
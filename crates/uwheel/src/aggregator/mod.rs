@@ -1,14 +1,28 @@
-use core::fmt::Debug;
+use core::{any::type_name, fmt::Debug, mem};
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 
+/// Adapters that filter or map the input of an aggregator
+pub mod adapter;
 /// An All Aggregator enabling the following functions (MAX, MIN, SUM, COUNT, AVG).
 #[cfg(feature = "all")]
 pub mod all;
 /// Incremental AVG aggregation
 #[cfg(feature = "avg")]
 pub mod avg;
+/// Set membership using a Bloom filter
+#[cfg(feature = "bloom")]
+pub mod bloom;
+/// Fixed-bucket histogram aggregation
+#[cfg(feature = "histogram")]
+pub mod histogram;
+/// Distinct count aggregation using HyperLogLog
+#[cfg(feature = "hyperloglog")]
+pub mod hyperloglog;
 /// Incremental MAX aggregation
 #[cfg(feature = "max")]
 pub mod max;
@@ -18,14 +32,29 @@ pub mod min;
 /// Incremental MinMax aggregation for temporal pruning
 #[cfg(feature = "min_max")]
 pub mod min_max;
+/// Approximate quantile aggregation using a mergeable sketch
+#[cfg(feature = "quantile")]
+pub mod quantile;
 /// Incremental SUM aggregation
 #[cfg(feature = "sum")]
 pub mod sum;
+/// Variance and standard deviation aggregation
+#[cfg(feature = "variance")]
+pub mod variance;
 
 #[cfg(feature = "top_n")]
 /// Top-N Aggregation using a nested Aggregator which has a PartialAggregate that implements `Ord`
 pub mod top_n;
 
+#[cfg(any(feature = "hyperloglog", feature = "bloom"))]
+mod hash;
+mod tuple;
+
+/// Support code for `#[derive(Aggregator)]`
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive;
+
 /// Type alias for a Combine Simd function
 pub type CombineSimdFn<P> = fn(&[P]) -> P;
 /// Type alias for an Inverse Combine function.
@@ -40,6 +69,10 @@ pub type InverseFn<P> = fn(P, P) -> P;
 /// - [avg]
 /// - [all]
 ///
+/// Tuples of up to six aggregators are aggregators themselves, which makes it possible to
+/// maintain several aggregates over a single stream using one wheel. The input, partial aggregate
+/// and aggregate of a tuple are the tuples of the inner types.
+///
 /// # Example
 ///
 /// Here is a simple example showing how to create a SUM aggregator using u32.
@@ -83,6 +116,13 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
     /// For example, for SUM types the identity value should be set to 0.
     const IDENTITY: Self::PartialAggregate;
 
+    /// Stable name that identifies the aggregator
+    ///
+    /// Snapshots record the name (see [Self::name]) and refuse to load using an aggregator with a different name.
+    /// The name must therefore stay the same across releases. Is set to `None` by default in which case the
+    /// type name of the aggregator is used, which may change across compiler versions.
+    const NAME: Option<&'static str> = None;
+
     /// Aggregator Input type that can be converted or applied to a [Self::MutablePartialAggregate].
     type Input: InputBounds;
 
@@ -90,6 +130,9 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
     type MutablePartialAggregate: MutablePartialAggregateType;
 
     /// Immutable Partial Aggregate type that defines aggregates below µWheel's low watermark.
+    ///
+    /// Partial aggregates are typically small `Copy` types, but heap-allocated types are supported
+    /// as well since wheels clone partial aggregates when answering queries.
     type PartialAggregate: PartialAggregateType;
 
     /// Final Aggregate type that can be lowered from a [Self::PartialAggregate].
@@ -123,7 +166,7 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
     fn combine_slice(slice: &[Self::PartialAggregate]) -> Option<Self::PartialAggregate> {
         match Self::combine_simd() {
             Some(combine_simd) => Some(combine_simd(slice)),
-            None => Some(slice.iter().copied().fold(Self::IDENTITY, Self::combine)),
+            None => Some(slice.iter().cloned().fold(Self::IDENTITY, Self::combine)),
         }
     }
 
@@ -136,7 +179,7 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
     fn merge(s1: &mut [Self::PartialAggregate], s2: &[Self::PartialAggregate]) {
         // NOTE: merges at most s2.len() aggregates
         for (self_slot, other_slot) in s1.iter_mut().zip(s2.iter()).take(s2.len()) {
-            *self_slot = Self::combine(*self_slot, *other_slot);
+            *self_slot = Self::combine(mem::take(self_slot), other_slot.clone());
         }
     }
 
@@ -148,9 +191,9 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
     fn build_prefix(slice: &[Self::PartialAggregate]) -> Vec<Self::PartialAggregate> {
         slice
             .iter()
-            .scan(Self::IDENTITY, |pa, &i| {
-                *pa = Self::combine(*pa, i);
-                Some(*pa)
+            .scan(Self::IDENTITY, |pa, i| {
+                *pa = Self::combine(mem::take(pa), i.clone());
+                Some(pa.clone())
             })
             .collect::<Vec<_>>()
     }
@@ -167,9 +210,9 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
     ) -> Option<Self::PartialAggregate> {
         Self::combine_inverse().map(|inverse| {
             if start == 0 {
-                slice[end]
+                slice[end].clone()
             } else {
-                inverse(slice[end], slice[start - 1])
+                inverse(slice[end].clone(), slice[start - 1].clone())
             }
         })
     }
@@ -195,6 +238,14 @@ pub trait Aggregator: Default + Debug + Clone + 'static {
         None
     }
 
+    /// Returns the name that identifies the aggregator in snapshots
+    ///
+    /// Defaults to [Self::NAME] or the type name of the aggregator if it has no name. Generic aggregators
+    /// extend their name with their type parameters, where inner aggregators are identified by their own name.
+    fn name() -> String {
+        Self::NAME.map_or_else(|| type_name::<Self>().to_string(), String::from)
+    }
+
     /// Returns ``true`` if the Aggregator supports invertibility
     #[doc(hidden)]
     fn invertible() -> bool {
@@ -273,21 +324,21 @@ impl<T> MutablePartialAggregateType for T where
 
 /// Trait bounds for a partial aggregate type
 #[cfg(not(feature = "serde"))]
-pub trait PartialAggregateBounds: Default + Debug + Clone + Copy + Send {}
+pub trait PartialAggregateBounds: Default + Debug + Clone + Send {}
 
 /// Trait bounds for a partial aggregate type
 #[cfg(feature = "serde")]
 pub trait PartialAggregateBounds:
-    Default + Debug + Clone + Copy + Send + serde::Serialize + for<'a> serde::Deserialize<'a>
+    Default + Debug + Clone + Send + serde::Serialize + for<'a> serde::Deserialize<'a>
 {
 }
 
 #[cfg(not(feature = "serde"))]
-impl<T> PartialAggregateBounds for T where T: Default + Debug + Clone + Copy + Send {}
+impl<T> PartialAggregateBounds for T where T: Default + Debug + Clone + Send {}
 
 #[cfg(feature = "serde")]
 impl<T> PartialAggregateBounds for T where
-    T: Default + Debug + Clone + Copy + Send + serde::Serialize + for<'a> serde::Deserialize<'a>
+    T: Default + Debug + Clone + Send + serde::Serialize + for<'a> serde::Deserialize<'a>
 {
 }
 
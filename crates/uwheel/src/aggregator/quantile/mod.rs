@@ -0,0 +1,284 @@
+use crate::aggregator::{Aggregator, PartialAggregateType};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+#[cfg(feature = "serde")]
+use serde_big_array::BigArray;
+
+/// Number of mantissa bits used to split every power of two into buckets
+const SUB_BUCKET_BITS: u32 = 4;
+/// Number of buckets per power of two
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Exponent of the smallest value that gets its own bucket (2^-10 ≈ 0.001)
+const MIN_EXPONENT: i64 = -10;
+
+/// A mergeable quantile sketch with a fixed number of buckets
+///
+/// Values are mapped into log-linear buckets: every power of two is split into 16 equally sized
+/// buckets, which bounds the relative error of a quantile estimate to about 3%. Bucket `0` starts at
+/// 2^-10 and each group of 16 buckets covers the next power of two, so `N` buckets cover values up to
+/// 2^(N/16 - 10). Smaller values are counted in the first bucket and larger ones in the last, while
+/// the exact minimum and maximum are tracked separately.
+///
+/// The sketch is meant for non-negative values such as latencies. `NaN` values are ignored.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy)]
+pub struct QuantileSketch<const N: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "BigArray"))]
+    buckets: [u64; N],
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl<const N: usize> Default for QuantileSketch<N> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<const N: usize> PartialAggregateType for QuantileSketch<N> {}
+
+impl<const N: usize> QuantileSketch<N> {
+    /// Returns an empty sketch
+    pub const fn identity() -> Self {
+        Self {
+            buckets: [0; N],
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Inserts a value into the sketch
+    #[inline]
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.buckets[Self::bucket(value)] += 1;
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Merges another sketch into this one
+    #[inline]
+    pub fn merge(&mut self, other: &Self) {
+        for (bucket, other) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *bucket += other;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Returns the estimated value at the given quantile (e.g., 0.99 for p99)
+    ///
+    /// Returns `None` if the sketch is empty or if the quantile is not within `[0, 1]`.
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        if self.count == 0 || !(0.0..=1.0).contains(&quantile) {
+            return None;
+        }
+        if quantile == 0.0 {
+            return Some(self.min);
+        }
+        if quantile == 1.0 {
+            return Some(self.max);
+        }
+
+        // 0-based rank of the requested value
+        let rank = (quantile * (self.count - 1) as f64) as u64;
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen > rank {
+                return Some(Self::estimate(index).clamp(self.min, self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Returns the number of values in the sketch
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns `true` if the sketch contains no values
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the smallest value in the sketch
+    pub fn min(&self) -> Option<f64> {
+        (!self.is_empty()).then_some(self.min)
+    }
+
+    /// Returns the largest value in the sketch
+    pub fn max(&self) -> Option<f64> {
+        (!self.is_empty()).then_some(self.max)
+    }
+
+    // Maps a value to its bucket using the exponent and the upper mantissa bits
+    #[inline]
+    fn bucket(value: f64) -> usize {
+        let bits = value.to_bits();
+        // negative values, zero and subnormals
+        if value <= 0.0 || (bits >> 52) & 0x7ff == 0 {
+            return 0;
+        }
+        let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+        let sub_bucket = ((bits >> (52 - SUB_BUCKET_BITS)) as usize) & (SUB_BUCKETS - 1);
+        let index = (exponent - MIN_EXPONENT) * SUB_BUCKETS as i64 + sub_bucket as i64;
+        index.clamp(0, N as i64 - 1) as usize
+    }
+
+    // Returns the midpoint of a bucket
+    #[inline]
+    fn estimate(index: usize) -> f64 {
+        let exponent = (index / SUB_BUCKETS) as i64 + MIN_EXPONENT;
+        let sub_bucket = (index % SUB_BUCKETS) as f64;
+        let base = f64::from_bits(((exponent + 1023) as u64) << 52);
+        base * (1.0 + (sub_bucket + 0.5) / SUB_BUCKETS as f64)
+    }
+}
+
+/// Approximate quantile aggregation (e.g., p50, p95, p99)
+///
+/// Aggregates `f64` values into a [QuantileSketch] with `N` buckets. Sketches are merged by adding
+/// up bucket counts, so quantiles can be answered for any range or group of the wheel.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::quantile::QuantileAggregator, Entry, NumericalDuration, RwWheel};
+///
+/// let mut wheel: RwWheel<QuantileAggregator> = RwWheel::new(0);
+/// for latency in 1..=100 {
+///     wheel.insert(Entry::new(latency as f64, 1000));
+/// }
+/// wheel.advance(5.seconds());
+///
+/// let sketch = wheel.read().interval_and_lower(5.seconds()).unwrap();
+/// let p99 = sketch.quantile(0.99).unwrap();
+/// assert!((p99 - 99.0).abs() / 99.0 < 0.05);
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+pub struct QuantileAggregator<const N: usize = 1024>;
+
+impl<const N: usize> Aggregator for QuantileAggregator<N> {
+    const IDENTITY: Self::PartialAggregate = QuantileSketch::identity();
+    const NAME: Option<&'static str> = Some("uwheel::aggregator::quantile::QuantileAggregator");
+
+    type Input = f64;
+    type MutablePartialAggregate = QuantileSketch<N>;
+    type PartialAggregate = QuantileSketch<N>;
+    type Aggregate = QuantileSketch<N>;
+
+    fn name() -> String {
+        format!("uwheel::aggregator::quantile::QuantileAggregator<{N}>")
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        let mut sketch = QuantileSketch::identity();
+        sketch.insert(input);
+        sketch
+    }
+
+    #[inline]
+    fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        mutable.insert(input);
+    }
+
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        mutable
+    }
+
+    #[inline]
+    fn combine(mut a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        a.merge(&b);
+        a
+    }
+
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{duration::NumericalDuration, Entry, RwWheel, WheelRange};
+
+    fn assert_close(estimate: f64, exact: f64) {
+        assert!(
+            (estimate - exact).abs() / exact <= 0.035,
+            "{estimate} is not close to {exact}"
+        );
+    }
+
+    #[test]
+    fn quantile_sketch_test() {
+        let mut sketch = QuantileSketch::<1024>::identity();
+        assert_eq!(sketch.quantile(0.5), None);
+
+        for value in 1..=1000 {
+            sketch.insert(value as f64);
+        }
+        sketch.insert(f64::NAN);
+
+        assert_eq!(sketch.count(), 1000);
+        assert_eq!(sketch.quantile(0.0), Some(1.0));
+        assert_eq!(sketch.quantile(1.0), Some(1000.0));
+        assert_eq!(sketch.quantile(1.5), None);
+        assert_close(sketch.quantile(0.5).unwrap(), 500.0);
+        assert_close(sketch.quantile(0.95).unwrap(), 950.0);
+        assert_close(sketch.quantile(0.99).unwrap(), 990.0);
+    }
+
+    #[test]
+    fn quantile_sketch_bounds_test() {
+        let mut sketch = QuantileSketch::<64>::identity();
+        // 64 buckets cover values up to 2^-6
+        sketch.insert(0.0);
+        sketch.insert(-5.0);
+        sketch.insert(1e9);
+
+        assert_eq!(sketch.min(), Some(-5.0));
+        assert_eq!(sketch.max(), Some(1e9));
+        assert_eq!(sketch.buckets[0], 2);
+        assert_eq!(sketch.buckets[63], 1);
+    }
+
+    #[test]
+    fn quantile_wheel_test() {
+        let mut wheel: RwWheel<QuantileAggregator> = RwWheel::new(0);
+        // second i holds the latencies i * 100 + 1 ..= i * 100 + 100
+        for second in 0..10u64 {
+            for latency in 1..=100 {
+                let value = (second * 100 + latency) as f64;
+                wheel.insert(Entry::new(value, second * 1000));
+            }
+        }
+        wheel.advance(10.seconds());
+
+        let sketch = wheel
+            .read()
+            .combine_range_and_lower(WheelRange::new_unchecked(0, 10000))
+            .unwrap();
+        assert_eq!(sketch.count(), 1000);
+        assert_close(sketch.quantile(0.5).unwrap(), 500.0);
+        assert_close(sketch.quantile(0.99).unwrap(), 990.0);
+
+        let groups = wheel
+            .read()
+            .group_by(WheelRange::new_unchecked(0, 10000), 5.seconds())
+            .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_close(groups[0].1.quantile(0.5).unwrap(), 250.0);
+        assert_close(groups[1].1.quantile(0.5).unwrap(), 750.0);
+    }
+}
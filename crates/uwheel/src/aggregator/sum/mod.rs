@@ -18,6 +18,7 @@ macro_rules! sum_impl {
         pub struct $struct;
 
         impl Aggregator for $struct {
+            const NAME: Option<&'static str> = Some(concat!(module_path!(), "::", stringify!($struct)));
             const IDENTITY: Self::PartialAggregate = 0 as $pa;
 
             type Input = $type;
@@ -0,0 +1,199 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// A compact binary encoding of Top-N keys and partial aggregates
+///
+/// Used by [DynamicTopNAggregator](super::DynamicTopNAggregator) to compress its state. Integers
+/// are encoded as LEB128 varints (signed integers using zigzag encoding), which keeps small
+/// counts and identifiers down to a single byte.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::aggregator::top_n::Codec;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// struct UserId(u32);
+///
+/// impl Codec for UserId {
+///     fn encode(&self, buf: &mut Vec<u8>) {
+///         self.0.encode(buf)
+///     }
+///     fn decode(bytes: &mut &[u8]) -> Option<Self> {
+///         u32::decode(bytes).map(UserId)
+///     }
+/// }
+///
+/// let mut buf = Vec::new();
+/// UserId(300).encode(&mut buf);
+/// assert_eq!(buf.len(), 2);
+/// assert_eq!(UserId::decode(&mut buf.as_slice()), Some(UserId(300)));
+/// ```
+pub trait Codec: Sized {
+    /// Appends the encoding of `self` to `buf`
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// Decodes a value from the front of `bytes` and advances past it
+    ///
+    /// Returns `None` if `bytes` does not start with a valid encoding.
+    fn decode(bytes: &mut &[u8]) -> Option<Self>;
+}
+
+#[inline]
+pub(super) fn encode_varint(buf: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[inline]
+pub(super) fn decode_varint(bytes: &mut &[u8]) -> Option<u128> {
+    let mut value = 0u128;
+    for (index, byte) in bytes.iter().enumerate() {
+        let shift = index * 7;
+        if shift >= 128 {
+            return None;
+        }
+        value |= ((byte & 0x7f) as u128) << shift;
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[index + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+macro_rules! unsigned_codec {
+    ($($type:ty),*) => {
+        $(
+            impl Codec for $type {
+                #[inline]
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    encode_varint(buf, *self as u128);
+                }
+                #[inline]
+                fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                    decode_varint(bytes).and_then(|v| <$type>::try_from(v).ok())
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! signed_codec {
+    ($($type:ty),*) => {
+        $(
+            impl Codec for $type {
+                #[inline]
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    // zigzag encoding maps small negative numbers to small unsigned numbers
+                    let value = *self as i128;
+                    encode_varint(buf, ((value << 1) ^ (value >> 127)) as u128);
+                }
+                #[inline]
+                fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                    let value = decode_varint(bytes)?;
+                    let value = (value >> 1) as i128 ^ -((value & 1) as i128);
+                    <$type>::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+unsigned_codec!(u8, u16, u32, u64, u128, usize);
+signed_codec!(i8, i16, i32, i64, i128, isize);
+
+impl Codec for bool {
+    #[inline]
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+    #[inline]
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        match byte {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Codec, const N: usize> Codec for [T; N] {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for value in self {
+            value.encode(buf);
+        }
+    }
+    fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let values: Vec<T> = (0..N).map(|_| T::decode(bytes)).collect::<Option<_>>()?;
+        values.try_into().ok()
+    }
+}
+
+macro_rules! tuple_codec {
+    ( $( $name:ident $index:tt )+ ) => {
+        impl<$($name: Codec),+> Codec for ($($name,)+) {
+            #[inline]
+            fn encode(&self, buf: &mut Vec<u8>) {
+                $(self.$index.encode(buf);)+
+            }
+            #[inline]
+            fn decode(bytes: &mut &[u8]) -> Option<Self> {
+                Some(($($name::decode(bytes)?,)+))
+            }
+        }
+    };
+}
+
+tuple_codec!(A 0 B 1);
+tuple_codec!(A 0 B 1 C 2);
+tuple_codec!(A 0 B 1 C 2 D 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip<T: Codec + PartialEq + core::fmt::Debug>(value: T) -> usize {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        let mut bytes = buf.as_slice();
+        assert_eq!(T::decode(&mut bytes), Some(value));
+        assert!(bytes.is_empty());
+        buf.len()
+    }
+
+    #[test]
+    fn codec_roundtrip_test() {
+        assert_eq!(roundtrip(0u64), 1);
+        assert_eq!(roundtrip(127u32), 1);
+        assert_eq!(roundtrip(128u32), 2);
+        assert_eq!(roundtrip(u64::MAX), 10);
+        assert_eq!(roundtrip(u128::MAX), 19);
+        assert_eq!(roundtrip(-1i32), 1);
+        assert_eq!(roundtrip(63i64), 1);
+        assert_eq!(roundtrip(-64i64), 1);
+        roundtrip(i64::MIN);
+        roundtrip(i128::MAX);
+        roundtrip(true);
+        roundtrip([192u8, 168, 0, 1]);
+        roundtrip((7u16, -3i8, 1_000_000usize));
+    }
+
+    #[test]
+    fn codec_invalid_test() {
+        // out of range for the target type
+        let mut buf = Vec::new();
+        300u32.encode(&mut buf);
+        assert_eq!(u8::decode(&mut buf.as_slice()), None);
+
+        // truncated varint
+        assert_eq!(u64::decode(&mut [0x80u8, 0x80].as_slice()), None);
+        assert_eq!(bool::decode(&mut [2u8].as_slice()), None);
+        assert_eq!(<(u8, u8)>::decode(&mut [1u8].as_slice()), None);
+    }
+}
@@ -0,0 +1,389 @@
+use super::{
+    codec::{decode_varint, encode_varint, Codec},
+    entry::TopNEntry,
+    map::TopNMap,
+    order::{Ascending, Order},
+    KeyBounds,
+};
+use crate::aggregator::{Aggregator, Compression, PartialAggregateType};
+use core::{any::type_name, cmp::Ordering, fmt::Debug, marker::PhantomData, ops::Deref};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+/// The number of entries kept by a [DynamicTopNAggregator]
+///
+/// The limit is read at runtime whenever a state is frozen or combined, so it may come from
+/// configuration rather than being fixed at compile time. Changing the limit only affects states
+/// created or combined afterwards.
+///
+/// # Example
+///
+/// ```
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+/// use uwheel::aggregator::top_n::Limit;
+///
+/// static TOP_N: AtomicUsize = AtomicUsize::new(1000);
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct Configured;
+///
+/// impl Limit for Configured {
+///     fn limit() -> usize {
+///         TOP_N.load(Ordering::Relaxed)
+///     }
+/// }
+/// ```
+pub trait Limit: Default + Debug + Clone + Copy + Send + 'static {
+    /// Returns the maximum number of entries to keep
+    fn limit() -> usize;
+}
+
+/// An immutable partial aggregate for the DynamicTopNAggregator
+///
+/// Holds at most [Limit::limit] entries sorted by the order of the aggregator. Unlike
+/// [TopNState](super::TopNState), the entries are heap-allocated so a state only takes up space
+/// for the keys that were actually seen.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
+#[derive(Debug, Clone)]
+pub struct DynamicTopNState<Key, A>
+where
+    Key: KeyBounds,
+    A: Aggregator,
+    A::PartialAggregate: Ord,
+{
+    entries: Vec<TopNEntry<Key, A::PartialAggregate>>,
+}
+
+impl<Key, A> Default for DynamicTopNState<Key, A>
+where
+    Key: KeyBounds,
+    A: Aggregator,
+    A::PartialAggregate: Ord,
+{
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<Key, A> DynamicTopNState<Key, A>
+where
+    Key: KeyBounds,
+    A: Aggregator,
+    A::PartialAggregate: Ord,
+{
+    /// Returns the identity aggregate of DynamicTopNState
+    pub const fn identity() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    fn merge(&mut self, other: Self, limit: usize, order: Ordering) {
+        let mut map = TopNMap::<Key, A>::default();
+        for entry in self.entries.drain(..).chain(other.entries) {
+            map.insert(entry.key, entry.data);
+        }
+        self.entries = map.top(limit, order);
+    }
+}
+
+impl<Key, A> DynamicTopNState<Key, A>
+where
+    Key: KeyBounds + Codec,
+    A: Aggregator,
+    A::PartialAggregate: Ord + Codec,
+{
+    // Encodes the states as a varint count followed by the entries of each state
+    fn compress(states: &[Self]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        encode_varint(&mut bytes, states.len() as u128);
+        for state in states {
+            encode_varint(&mut bytes, state.entries.len() as u128);
+            for entry in &state.entries {
+                entry.key.encode(&mut bytes);
+                entry.data.encode(&mut bytes);
+            }
+        }
+        bytes
+    }
+
+    // Decodes states encoded by `compress`
+    fn decompress(mut bytes: &[u8]) -> Vec<Self> {
+        let bytes = &mut bytes;
+        let states = decode_varint(bytes).expect("invalid Top-N length") as usize;
+        (0..states)
+            .map(|_| {
+                let entries = decode_varint(bytes).expect("invalid Top-N length") as usize;
+                let entries = (0..entries)
+                    .map(|_| {
+                        let key = Key::decode(bytes).expect("invalid Top-N key");
+                        let data = A::PartialAggregate::decode(bytes).expect("invalid Top-N data");
+                        TopNEntry::new(key, data)
+                    })
+                    .collect();
+                Self { entries }
+            })
+            .collect()
+    }
+}
+
+impl<Key, A> PartialAggregateType for DynamicTopNState<Key, A>
+where
+    Key: KeyBounds,
+    A: Aggregator + Clone,
+    A::PartialAggregate: Ord,
+{
+}
+
+impl<Key, A> Deref for DynamicTopNState<Key, A>
+where
+    Key: KeyBounds,
+    A: Aggregator,
+    A::PartialAggregate: Ord,
+{
+    type Target = [TopNEntry<Key, A::PartialAggregate>];
+
+    fn deref(&self) -> &[TopNEntry<Key, A::PartialAggregate>] {
+        &self.entries
+    }
+}
+
+/// A Top-N Aggregator with a runtime limit and heap-allocated state
+///
+/// [TopNAggregator](super::TopNAggregator) stores `N` slots inline in every wheel slot, which
+/// becomes expensive for large `N` as the state is kept across all wheel levels. This variant keeps
+/// at most [Limit::limit] entries in a heap-allocated [DynamicTopNState], so slots with few keys
+/// stay small.
+///
+/// States are compressed using [Codec] when the wheel is configured with
+/// a compressed data layout, which keeps long retention of Top-N states affordable.
+///
+/// Orders data in ascending order by default
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{
+///     aggregator::{
+///         sum::U64SumAggregator,
+///         top_n::{Descending, DynamicTopNAggregator, Limit},
+///     },
+///     Entry,
+///     NumericalDuration,
+///     RwWheel,
+/// };
+///
+/// #[derive(Default, Debug, Clone, Copy)]
+/// struct Top2;
+///
+/// impl Limit for Top2 {
+///     fn limit() -> usize {
+///         2
+///     }
+/// }
+///
+/// type TopPages = DynamicTopNAggregator<u32, U64SumAggregator, Top2, Descending>;
+///
+/// let mut wheel: RwWheel<TopPages> = RwWheel::new(0);
+/// wheel.insert(Entry::new((1, 10), 1000));
+/// wheel.insert(Entry::new((2, 50), 1000));
+/// wheel.insert(Entry::new((3, 30), 2000));
+/// wheel.advance(5.seconds());
+///
+/// let top = wheel.read().interval_and_lower(5.seconds()).unwrap();
+/// assert_eq!(top.len(), 2);
+/// assert_eq!((top[0].key, top[0].data), (2, 50));
+/// assert_eq!((top[1].key, top[1].data), (3, 30));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicTopNAggregator<Key: KeyBounds, A: Aggregator, L, OrderBy = Ascending>(
+    PhantomData<(Key, A, L, OrderBy)>,
+);
+
+impl<Key, A, L, OrderBy> Default for DynamicTopNAggregator<Key, A, L, OrderBy>
+where
+    Key: KeyBounds,
+    A: Aggregator,
+{
+    // have to implement manually as Key does not implement Default
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Key, A, L, OrderBy> Aggregator for DynamicTopNAggregator<Key, A, L, OrderBy>
+where
+    Key: KeyBounds + Codec,
+    A: Aggregator + Clone + Copy,
+    A::PartialAggregate: Ord + Codec,
+    L: Limit,
+    OrderBy: Order,
+{
+    const IDENTITY: Self::PartialAggregate = DynamicTopNState::identity();
+    const NAME: Option<&'static str> = Some("uwheel::aggregator::top_n::DynamicTopNAggregator");
+
+    type Input = (Key, A::Input);
+    type MutablePartialAggregate = TopNMap<Key, A>;
+    type PartialAggregate = DynamicTopNState<Key, A>;
+    type Aggregate = DynamicTopNState<Key, A>;
+
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::top_n::DynamicTopNAggregator<{}, {}, {}, {}>",
+            type_name::<Key>(),
+            A::name(),
+            type_name::<L>(),
+            type_name::<OrderBy>()
+        )
+    }
+
+    #[inline]
+    fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+        let mut map = TopNMap::default();
+        Self::combine_mutable(&mut map, input);
+        map
+    }
+    #[inline]
+    fn combine_mutable(map: &mut Self::MutablePartialAggregate, input: Self::Input) {
+        let inner_mutable = A::lift(input.1);
+        map.insert(input.0, A::freeze(inner_mutable));
+    }
+    fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+        DynamicTopNState {
+            entries: mutable.top(L::limit(), OrderBy::ordering()),
+        }
+    }
+
+    #[inline]
+    fn combine(mut a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+        a.merge(b, L::limit(), OrderBy::ordering());
+        a
+    }
+    #[inline]
+    fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+        a
+    }
+
+    fn compression() -> Option<Compression<Self::PartialAggregate>> {
+        Some(Compression::new(
+            DynamicTopNState::compress,
+            DynamicTopNState::decompress,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aggregator::{
+            sum::U64SumAggregator,
+            top_n::{Descending, TopNAggregator},
+        },
+        duration::NumericalDuration,
+        wheels::read::aggregation::{
+            conf::{DataLayout, RetentionPolicy, WheelConf},
+            Wheel,
+            WheelSlot,
+        },
+        Entry,
+        RwWheel,
+    };
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct Top3;
+
+    impl Limit for Top3 {
+        fn limit() -> usize {
+            3
+        }
+    }
+
+    type Ascending3 = DynamicTopNAggregator<u32, U64SumAggregator, Top3>;
+    type Descending3 = DynamicTopNAggregator<u32, U64SumAggregator, Top3, Descending>;
+
+    fn entries(state: &DynamicTopNState<u32, U64SumAggregator>) -> Vec<(u32, u64)> {
+        state.iter().map(|e| (e.key, e.data)).collect()
+    }
+
+    #[test]
+    fn dynamic_top_n_test() {
+        let mut wheel: RwWheel<(Ascending3, Descending3)> = RwWheel::new(0);
+        for (key, value) in [(1, 10), (2, 50), (3, 30), (4, 5), (5, 70)] {
+            wheel.insert(Entry::new(((key, value), (key, value)), 1000));
+        }
+        wheel.advance_to(2000);
+
+        // same keys combine across slots
+        wheel.insert(Entry::new(((4, 100), (4, 100)), 2000));
+        wheel.insert(Entry::new(((6, 1), (6, 1)), 2000));
+        wheel.advance_to(3000);
+
+        let (lowest, highest) = wheel.read().interval(1.seconds()).unwrap();
+        assert_eq!(entries(&lowest), [(6, 1), (4, 100)]);
+        assert_eq!(entries(&highest), [(4, 100), (6, 1)]);
+
+        let (lowest, highest) = wheel.read().interval(2.seconds()).unwrap();
+        assert_eq!(entries(&lowest), [(6, 1), (1, 10), (3, 30)]);
+        // key 4 did not make the top 3 of the first slot and is therefore missing its first 5
+        assert_eq!(entries(&highest), [(4, 100), (5, 70), (2, 50)]);
+    }
+
+    #[test]
+    fn dynamic_top_n_matches_fixed_test() {
+        let mut fixed: RwWheel<TopNAggregator<u32, 3, U64SumAggregator>> = RwWheel::new(0);
+        let mut dynamic: RwWheel<Ascending3> = RwWheel::new(0);
+        for second in 0..20u64 {
+            for key in 0..10u32 {
+                let value = (second * 7 + key as u64 * 13) % 17 * 100 + key as u64;
+                fixed.insert(Entry::new((key, value), second * 1000));
+                dynamic.insert(Entry::new((key, value), second * 1000));
+            }
+        }
+        fixed.advance(20.seconds());
+        dynamic.advance(20.seconds());
+
+        for seconds in [1, 5, 20] {
+            let fixed = fixed.read().interval(seconds.seconds()).unwrap();
+            let dynamic = dynamic.read().interval(seconds.seconds()).unwrap();
+            let fixed: Vec<u64> = fixed.iter().flatten().map(|e| e.data).collect();
+            let dynamic: Vec<u64> = dynamic.iter().map(|e| e.data).collect();
+            assert_eq!(fixed, dynamic);
+        }
+    }
+
+    #[test]
+    fn dynamic_top_n_compression_test() {
+        let states: Vec<_> = (0..4u32)
+            .map(|slot| {
+                let mut map = TopNMap::default();
+                for key in 0..slot * 2 {
+                    map.insert(key, (key * 1000 + slot) as u64);
+                }
+                Descending3::freeze(map)
+            })
+            .collect();
+
+        let compressed = DynamicTopNState::compress(&states);
+        // 1 byte for the number of states, 1 byte per state length and 3 bytes per entry at most
+        assert!(compressed.len() <= 1 + 4 + 8 * 3);
+        let decompressed = DynamicTopNState::<u32, U64SumAggregator>::decompress(&compressed);
+        assert_eq!(decompressed.len(), states.len());
+        for (state, decompressed) in states.iter().zip(decompressed.iter()) {
+            assert_eq!(entries(state), entries(decompressed));
+        }
+
+        let conf = WheelConf::new(1000, 8)
+            .with_retention_policy(RetentionPolicy::Keep)
+            .with_data_layout(DataLayout::Compressed(2));
+        let mut wheel = Wheel::<Descending3>::new(conf);
+        for state in states {
+            wheel.insert_slot(WheelSlot::new(Some(state)));
+            wheel.tick();
+        }
+        let combined = wheel.combine_range(..).unwrap();
+        assert_eq!(entries(&combined), [(3, 6005), (5, 5003), (4, 4003)]);
+    }
+}
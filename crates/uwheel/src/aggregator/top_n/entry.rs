@@ -10,7 +10,7 @@ use core::{cmp::Ordering, fmt::Debug};
 pub struct TopNEntry<Key, A>
 where
     Key: KeyBounds,
-    A: Ord + Copy,
+    A: Ord,
 {
     pub key: Key,
     pub data: A,
@@ -19,7 +19,7 @@ where
 impl<Key, A> TopNEntry<Key, A>
 where
     Key: KeyBounds,
-    A: Ord + Copy,
+    A: Ord,
 {
     pub fn new(key: Key, data: A) -> Self {
         Self { key, data }
@@ -28,7 +28,7 @@ where
 impl<Key, A> Ord for TopNEntry<Key, A>
 where
     Key: KeyBounds,
-    A: Ord + Copy,
+    A: Ord,
 {
     fn cmp(&self, other: &Self) -> Ordering {
         self.data.cmp(&other.data)
@@ -38,7 +38,7 @@ where
 impl<Key, A> PartialOrd for TopNEntry<Key, A>
 where
     Key: KeyBounds,
-    A: Ord + Copy,
+    A: Ord,
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -47,7 +47,7 @@ where
 impl<Key, A> PartialEq for TopNEntry<Key, A>
 where
     Key: KeyBounds,
-    A: Ord + Copy,
+    A: Ord,
 {
     fn eq(&self, other: &Self) -> bool {
         self.data == other.data
@@ -1,10 +1,10 @@
 use super::{entry::TopNEntry, state::TopNState, KeyBounds};
 use crate::Aggregator;
-use core::{cmp::Ordering, fmt::Debug};
-use hashbrown::HashMap;
+use core::{cmp::Ordering, fmt::Debug, mem};
+use hashbrown::{hash_map::Entry, HashMap};
 
 #[cfg(not(feature = "std"))]
-use alloc::collections::BinaryHeap;
+use alloc::{collections::BinaryHeap, vec::Vec};
 #[cfg(feature = "std")]
 use std::collections::BinaryHeap;
 
@@ -42,14 +42,20 @@ where
 {
     #[inline]
     pub(super) fn insert(&mut self, key: Key, delta: A::PartialAggregate) {
-        self.table
-            .entry(key)
-            .and_modify(|curr_delta| {
-                *curr_delta = A::combine(*curr_delta, delta);
-            })
-            .or_insert(delta);
+        match self.table.entry(key) {
+            Entry::Occupied(mut curr) => {
+                let curr_delta = curr.get_mut();
+                *curr_delta = A::combine(mem::take(curr_delta), delta);
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(delta);
+            }
+        }
     }
-    pub(super) fn build<const N: usize>(mut self, order: Ordering) -> TopNState<Key, N, A> {
+    pub(super) fn build<const N: usize>(mut self, order: Ordering) -> TopNState<Key, N, A>
+    where
+        A::PartialAggregate: Copy,
+    {
         let mut heap = BinaryHeap::with_capacity(N);
 
         // build Top N state from table
@@ -81,4 +87,25 @@ where
 
         TopNState::from(top_n_vec)
     }
+    /// Returns at most `n` entries sorted by the given order
+    pub(super) fn top(self, n: usize, order: Ordering) -> Vec<TopNEntry<Key, A::PartialAggregate>> {
+        let mut entries: Vec<_> = self
+            .table
+            .into_iter()
+            .map(|(key, agg)| TopNEntry::new(key, agg))
+            .collect();
+        let cmp = |a: &TopNEntry<_, _>, b: &TopNEntry<_, _>| match order {
+            Ordering::Greater => b.cmp(a),
+            _ => a.cmp(b),
+        };
+        // partition the n first entries before sorting them
+        if entries.len() > n {
+            if n > 0 {
+                entries.select_nth_unstable_by(n - 1, cmp);
+            }
+            entries.truncate(n);
+        }
+        entries.sort_unstable_by(cmp);
+        entries
+    }
 }
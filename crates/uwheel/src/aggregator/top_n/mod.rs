@@ -1,12 +1,19 @@
 use crate::aggregator::Aggregator;
-use core::{fmt::Debug, marker::PhantomData};
+use core::{any::type_name, fmt::Debug, marker::PhantomData};
 
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+mod codec;
+mod dynamic;
 mod entry;
 mod key;
 mod map;
 mod order;
 mod state;
 
+pub use codec::Codec;
+pub use dynamic::{DynamicTopNAggregator, DynamicTopNState, Limit};
 pub use key::KeyBounds;
 pub use map::TopNMap;
 pub use order::{Ascending, Descending, Order};
@@ -39,12 +46,22 @@ where
     A::PartialAggregate: Ord + Copy,
 {
     const IDENTITY: Self::PartialAggregate = TopNState::identity();
+    const NAME: Option<&'static str> = Some("uwheel::aggregator::top_n::TopNAggregator");
 
     type Input = (Key, A::Input);
     type MutablePartialAggregate = TopNMap<Key, A>;
     type PartialAggregate = TopNState<Key, N, A>;
     type Aggregate = TopNState<Key, N, A>;
 
+    fn name() -> String {
+        format!(
+            "uwheel::aggregator::top_n::TopNAggregator<{}, {N}, {}, {}>",
+            type_name::<Key>(),
+            A::name(),
+            type_name::<OrderBy>()
+        )
+    }
+
     #[inline]
     fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
         let mut map = TopNMap::default();
@@ -0,0 +1,260 @@
+use super::{Aggregator, Compression, InverseFn};
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+
+// Appends a compressed column prefixed by its length
+fn write_column(bytes: &mut Vec<u8>, column: &[u8]) {
+    bytes.extend_from_slice(&(column.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(column);
+}
+
+// Reads a column written by `write_column` and advances past it
+fn read_column<'a>(bytes: &mut &'a [u8]) -> &'a [u8] {
+    let (len, rest) = bytes
+        .split_first_chunk::<4>()
+        .expect("invalid tuple column length");
+    let (column, rest) = rest
+        .split_at_checked(u32::from_le_bytes(*len) as usize)
+        .expect("truncated tuple column");
+    *bytes = rest;
+    column
+}
+
+// Runs several aggregators over the same stream using the tuple of their inputs and partials.
+//
+// `combine_inverse` and `compression` are only provided if every inner aggregator supports them.
+// Partials are compressed column by column so that each inner aggregator compresses its own
+// values. `combine_simd` is never provided as the partials of a single aggregator are not
+// contiguous in a slice of tuples.
+macro_rules! tuple_aggregator {
+    ( $( $name:ident $index:tt )+ ) => {
+        impl<$($name: Aggregator),+> Aggregator for ($($name,)+) {
+            const IDENTITY: Self::PartialAggregate = ($($name::IDENTITY,)+);
+
+            type Input = ($($name::Input,)+);
+            type MutablePartialAggregate = ($($name::MutablePartialAggregate,)+);
+            type PartialAggregate = ($($name::PartialAggregate,)+);
+            type Aggregate = ($($name::Aggregate,)+);
+
+            #[inline]
+            fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+                ($($name::lift(input.$index),)+)
+            }
+
+            #[inline]
+            fn combine_mutable(a: &mut Self::MutablePartialAggregate, input: Self::Input) {
+                $($name::combine_mutable(&mut a.$index, input.$index);)+
+            }
+
+            fn freeze(a: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+                ($($name::freeze(a.$index),)+)
+            }
+
+            #[inline]
+            fn combine(a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+                ($($name::combine(a.$index, b.$index),)+)
+            }
+
+            #[inline]
+            fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+                ($($name::lower(a.$index),)+)
+            }
+
+            fn name() -> String {
+                format!("({})", [$($name::name()),+].join(", "))
+            }
+
+            #[inline]
+            fn combine_inverse() -> Option<InverseFn<Self::PartialAggregate>> {
+                if $($name::invertible())&&+ {
+                    // every inner aggregator is invertible so unwrapping cannot fail
+                    Some(|a, b| ($($name::combine_inverse().unwrap()(a.$index, b.$index),)+))
+                } else {
+                    None
+                }
+            }
+
+            fn compression() -> Option<Compression<Self::PartialAggregate>> {
+                if $($name::compression_support())&&+ {
+                    // every inner aggregator supports compression so unwrapping cannot fail
+                    let compressor = |slice: &[Self::PartialAggregate]| {
+                        let mut bytes = Vec::new();
+                        $(
+                            let column: Vec<_> = slice.iter().map(|p| p.$index.clone()).collect();
+                            let compressor = $name::compression().unwrap().compressor;
+                            write_column(&mut bytes, &compressor(&column));
+                        )+
+                        bytes
+                    };
+                    let decompressor = |mut bytes: &[u8]| {
+                        let bytes = &mut bytes;
+                        let mut columns = ($({
+                            let decompressor = $name::compression().unwrap().decompressor;
+                            decompressor(read_column(bytes)).into_iter()
+                        },)+);
+                        core::iter::from_fn(|| Some(($(columns.$index.next()?,)+))).collect()
+                    };
+                    Some(Compression::new(compressor, decompressor))
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+tuple_aggregator!(A 0 B 1);
+tuple_aggregator!(A 0 B 1 C 2);
+tuple_aggregator!(A 0 B 1 C 2 D 3);
+tuple_aggregator!(A 0 B 1 C 2 D 3 E 4);
+tuple_aggregator!(A 0 B 1 C 2 D 3 E 4 F 5);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        aggregator::{
+            max::U64MaxAggregator,
+            min::U64MinAggregator,
+            sum::{U32SumAggregator, U64SumAggregator},
+            Aggregator,
+            Compression,
+        },
+        duration::NumericalDuration,
+        wheels::{
+            read::aggregation::conf::{DataLayout, RetentionPolicy},
+            Conf,
+        },
+        Entry,
+        HawConf,
+        RwWheel,
+        WheelRange,
+    };
+
+    // Sums u32 values and stores them as little-endian bytes when compressed
+    #[derive(Clone, Debug, Default)]
+    struct LeSumAggregator;
+
+    impl Aggregator for LeSumAggregator {
+        const IDENTITY: Self::PartialAggregate = 0;
+
+        type Input = u32;
+        type PartialAggregate = u32;
+        type MutablePartialAggregate = u32;
+        type Aggregate = u32;
+
+        fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+            input
+        }
+        fn combine_mutable(mutable: &mut Self::MutablePartialAggregate, input: Self::Input) {
+            *mutable += input
+        }
+        fn freeze(a: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+            a
+        }
+        fn combine(a: Self::PartialAggregate, b: Self::PartialAggregate) -> Self::PartialAggregate {
+            a + b
+        }
+        fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+            a
+        }
+        fn compression() -> Option<Compression<Self::PartialAggregate>> {
+            let compressor = |slice: &[u32]| slice.iter().flat_map(|v| v.to_le_bytes()).collect();
+            let decompressor = |bytes: &[u8]| {
+                let (chunks, _) = bytes.as_chunks::<4>();
+                chunks
+                    .iter()
+                    .map(|chunk| u32::from_le_bytes(*chunk))
+                    .collect()
+            };
+            Some(Compression::new(compressor, decompressor))
+        }
+    }
+
+    #[test]
+    fn tuple_aggregator_test() {
+        type Agg = (U64SumAggregator, U64MaxAggregator, U32SumAggregator);
+
+        let mut wheel: RwWheel<Agg> = RwWheel::new(0);
+        for second in 0..10u64 {
+            wheel.insert(Entry::new((second, second * 2, 1), second * 1000));
+        }
+        wheel.advance(10.seconds());
+
+        assert_eq!(
+            wheel.read().interval_and_lower(10.seconds()),
+            Some((45, 18, 10))
+        );
+        assert_eq!(
+            wheel
+                .read()
+                .combine_range(WheelRange::new_unchecked(2000, 5000)),
+            Some((9, 8, 3))
+        );
+    }
+
+    #[test]
+    fn tuple_aggregator_capabilities_test() {
+        type Invertible = (U64SumAggregator, U32SumAggregator);
+        type NotInvertible = (U64SumAggregator, U64MinAggregator);
+
+        let inverse = Invertible::combine_inverse().unwrap();
+        assert_eq!(inverse((10, 5), (4, 2)), (6, 3));
+        assert!(NotInvertible::combine_inverse().is_none());
+
+        assert!(!Invertible::simd_support());
+        let partials = [(1, 2), (3, 4), (5, 6)];
+        assert_eq!(Invertible::combine_slice(&partials), Some((9, 12)));
+        assert_eq!(
+            NotInvertible::combine_slice(&[(1, 2), (3, 1)]),
+            Some((4, 1))
+        );
+    }
+
+    #[test]
+    fn tuple_aggregator_prefix_test() {
+        let mut wheel: RwWheel<(U64SumAggregator, U32SumAggregator)> = RwWheel::new(0);
+        for second in 0..10u64 {
+            wheel.insert(Entry::new((second, 1), second * 1000));
+        }
+        wheel.advance(10.seconds());
+        wheel.read().to_prefix_wheels();
+
+        assert_eq!(
+            wheel
+                .read()
+                .combine_range(WheelRange::new_unchecked(3000, 7000)),
+            Some((18, 4))
+        );
+    }
+
+    #[test]
+    fn tuple_aggregator_compression_test() {
+        type Agg = (LeSumAggregator, LeSumAggregator);
+        assert!(<(LeSumAggregator, U32SumAggregator)>::compression().is_none());
+
+        let Compression {
+            compressor,
+            decompressor,
+        } = Agg::compression().unwrap();
+        let partials = [(1, 10), (2, 20), (3, 30)];
+        assert_eq!(decompressor(&compressor(&partials)), partials);
+        assert!(decompressor(&compressor(&[])).is_empty());
+
+        let mut haw_conf = HawConf::default();
+        haw_conf.seconds.set_retention_policy(RetentionPolicy::Keep);
+        haw_conf.seconds.set_data_layout(DataLayout::Compressed(8));
+        let mut wheel: RwWheel<Agg> = RwWheel::with_conf(Conf::default().with_haw_conf(haw_conf));
+        for second in 0..20u64 {
+            wheel.insert(Entry::new((1, second as u32), second * 1000));
+        }
+        wheel.advance(20.seconds());
+
+        assert_eq!(
+            wheel
+                .read()
+                .combine_range(WheelRange::new_unchecked(2000, 12000)),
+            Some((10, 65))
+        );
+    }
+}
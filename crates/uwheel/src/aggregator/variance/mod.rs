@@ -0,0 +1,309 @@
+use super::super::Aggregator;
+use crate::aggregator::PartialAggregateType;
+
+/// Running count, mean and sum of squared differences from the mean
+///
+/// Values are added using Welford's online algorithm and states are merged using Chan's
+/// parallel algorithm, which avoids the catastrophic cancellation of a naive sum of squares.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct VarianceState {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl PartialAggregateType for VarianceState {}
+
+impl VarianceState {
+    /// Returns an empty state
+    pub const fn identity() -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// Adds a value to the state
+    #[inline]
+    pub fn insert(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Merges two states
+    #[inline]
+    pub fn merge(a: Self, b: Self) -> Self {
+        if a.count == 0 {
+            return b;
+        }
+        if b.count == 0 {
+            return a;
+        }
+        let count = a.count + b.count;
+        let (na, nb, n) = (a.count as f64, b.count as f64, count as f64);
+        let delta = b.mean - a.mean;
+        Self {
+            count,
+            mean: a.mean + delta * nb / n,
+            m2: a.m2 + b.m2 + delta * delta * na * nb / n,
+        }
+    }
+
+    /// Removes the values of `b` from `a`, where `b` must have been merged into `a`
+    ///
+    /// This reverses [Self::merge] but may lose precision when `b` holds most of the values of `a`.
+    #[inline]
+    pub fn inverse(a: Self, b: Self) -> Self {
+        if b.count == 0 {
+            return a;
+        }
+        if a.count <= b.count {
+            return Self::identity();
+        }
+        let count = a.count - b.count;
+        let (na, nb, n) = (a.count as f64, b.count as f64, count as f64);
+        let mean = (a.mean * na - b.mean * nb) / n;
+        let delta = b.mean - mean;
+        let m2 = a.m2 - b.m2 - delta * delta * n * nb / na;
+        Self {
+            count,
+            mean,
+            // rounding errors may leave a tiny negative remainder
+            m2: m2.max(0.0),
+        }
+    }
+
+    /// Returns the number of values
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the mean of the values
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the population variance of the values
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// Returns the sample variance of the values
+    pub fn sample_variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+}
+
+/// Lowered result of a variance aggregation
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Variance {
+    /// Number of values
+    pub count: u64,
+    /// Mean of the values
+    pub mean: f64,
+    /// Population variance of the values
+    pub variance: f64,
+    /// Population standard deviation of the values
+    pub stddev: f64,
+}
+
+impl From<VarianceState> for Variance {
+    fn from(state: VarianceState) -> Self {
+        let variance = state.variance();
+        Self {
+            count: state.count,
+            mean: state.mean,
+            variance,
+            stddev: sqrt(variance),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+// Newton's method as f64::sqrt requires std
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    // halving the exponent gives a close initial guess
+    let mut guess = f64::from_bits((x.to_bits() >> 1) + (1023 << 51));
+    for _ in 0..6 {
+        guess = 0.5 * (guess + x / guess);
+    }
+    guess
+}
+
+macro_rules! variance_impl {
+    ($struct:tt, $type:ty) => {
+        #[derive(Default, Debug, Clone, Copy)]
+        #[allow(missing_docs)]
+        pub struct $struct;
+
+        impl Aggregator for $struct {
+            const NAME: Option<&'static str> = Some(concat!(module_path!(), "::", stringify!($struct)));
+            const IDENTITY: Self::PartialAggregate = VarianceState::identity();
+            type Input = $type;
+            type MutablePartialAggregate = VarianceState;
+            type Aggregate = Variance;
+            type PartialAggregate = VarianceState;
+
+            fn lift(input: Self::Input) -> Self::MutablePartialAggregate {
+                let mut state = VarianceState::identity();
+                state.insert(input as f64);
+                state
+            }
+            #[inline]
+            fn combine_mutable(a: &mut Self::MutablePartialAggregate, input: Self::Input) {
+                a.insert(input as f64);
+            }
+
+            fn freeze(mutable: Self::MutablePartialAggregate) -> Self::PartialAggregate {
+                mutable
+            }
+
+            #[inline]
+            fn combine(
+                a: Self::PartialAggregate,
+                b: Self::PartialAggregate,
+            ) -> Self::PartialAggregate {
+                VarianceState::merge(a, b)
+            }
+
+            #[inline]
+            fn lower(a: Self::PartialAggregate) -> Self::Aggregate {
+                a.into()
+            }
+            #[inline]
+            fn combine_inverse() -> Option<fn(Self::PartialAggregate, Self::PartialAggregate) -> Self::PartialAggregate> {
+                Some(VarianceState::inverse)
+            }
+        }
+    };
+}
+
+variance_impl!(U32VarianceAggregator, u32);
+variance_impl!(U64VarianceAggregator, u64);
+variance_impl!(I32VarianceAggregator, i32);
+variance_impl!(I64VarianceAggregator, i64);
+variance_impl!(F32VarianceAggregator, f32);
+variance_impl!(F64VarianceAggregator, f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        duration::NumericalDuration,
+        wheels::read::aggregation::{
+            conf::{DataLayout, RetentionPolicy, WheelConf},
+            Wheel,
+            WheelSlot,
+        },
+        RwWheel,
+    };
+
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    fn state(values: &[f64]) -> VarianceState {
+        values.iter().fold(VarianceState::identity(), |mut s, v| {
+            s.insert(*v);
+            s
+        })
+    }
+
+    #[test]
+    fn variance_merge_test() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let total = state(&values);
+        let merged = VarianceState::merge(state(&values[..3]), state(&values[3..]));
+
+        assert_eq!(merged.count(), 8);
+        assert_close(merged.mean(), 5.0);
+        assert_close(merged.variance(), 4.0);
+        assert_close(merged.sample_variance(), 32.0 / 7.0);
+        assert_close(merged.m2, total.m2);
+        assert_close(Variance::from(merged).stddev, 2.0);
+
+        let inverse = VarianceState::inverse(merged, state(&values[..3]));
+        assert_eq!(inverse.count(), 5);
+        assert_close(inverse.mean(), state(&values[3..]).mean());
+        assert_close(inverse.m2, state(&values[3..]).m2);
+        assert_eq!(
+            VarianceState::inverse(merged, merged),
+            VarianceState::identity()
+        );
+    }
+
+    #[test]
+    fn variance_large_offset_test() {
+        // a naive sum of squares loses all precision for values with a large offset
+        let values: Vec<f64> = (0..1000).map(|i| 1e9 + (i % 10) as f64).collect();
+        let merged = values
+            .chunks(7)
+            .map(state)
+            .fold(VarianceState::identity(), VarianceState::merge);
+        assert_close(merged.mean(), 1e9 + 4.5);
+        assert_close(merged.variance(), 8.25);
+    }
+
+    #[test]
+    fn variance_prefix_test() {
+        let prefix_conf = WheelConf::new(1000, 64)
+            .with_retention_policy(RetentionPolicy::Keep)
+            .with_data_layout(DataLayout::Prefix);
+        let mut prefix_wheel = Wheel::<F64VarianceAggregator>::new(prefix_conf);
+        let conf = WheelConf::new(1000, 64).with_retention_policy(RetentionPolicy::Keep);
+        let mut wheel = Wheel::<F64VarianceAggregator>::new(conf);
+
+        for i in 0..40 {
+            let slot = state(&[i as f64, (i * i) as f64, 3.0]);
+            prefix_wheel.insert_slot(WheelSlot::new(Some(slot)));
+            wheel.insert_slot(WheelSlot::new(Some(slot)));
+            prefix_wheel.tick();
+            wheel.tick();
+        }
+
+        for (start, end) in [(0, 40), (5, 9), (20, 35), (39, 40)] {
+            let prefix = prefix_wheel.combine_range(start..end).unwrap();
+            let scan = wheel.combine_range(start..end).unwrap();
+            assert_eq!(prefix.count(), scan.count());
+            assert!((prefix.mean() - scan.mean()).abs() < 1e-9 * scan.mean().abs().max(1.0));
+            assert!((prefix.m2 - scan.m2).abs() < 1e-9 * scan.m2.max(1.0));
+        }
+    }
+
+    #[test]
+    fn variance_wheel_test() {
+        let mut wheel = RwWheel::<U64VarianceAggregator>::new(0);
+        for second in 0..10u64 {
+            wheel.insert(crate::Entry::new(second % 2 * 10, second * 1000));
+        }
+        wheel.advance(10.seconds());
+
+        let result = wheel.read().interval_and_lower(10.seconds()).unwrap();
+        assert_eq!(result.count, 10);
+        assert_close(result.mean, 5.0);
+        assert_close(result.variance, 25.0);
+        assert_close(result.stddev, 5.0);
+    }
+}
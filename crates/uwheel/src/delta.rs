@@ -1,3 +1,5 @@
+use core::fmt::{self, Display};
+
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
@@ -37,6 +39,43 @@ impl<T> DeltaState<T> {
     }
 }
 
+/// Errors that may occur when applying incremental checkpoints onto a wheel
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CheckpointError {
+    /// The checkpoint starts after the watermark of the wheel meaning deltas are missing
+    Gap {
+        /// Current watermark of the wheel
+        watermark: u64,
+        /// Checkpoint id (oldest timestamp) of the incremental checkpoint
+        checkpoint: u64,
+    },
+    /// The checkpoint is not aligned with the tick size of the wheel
+    Misaligned {
+        /// Checkpoint id (oldest timestamp) of the incremental checkpoint
+        checkpoint: u64,
+    },
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Gap {
+                watermark,
+                checkpoint,
+            } => write!(
+                f,
+                "checkpoint {checkpoint} starts after the wheel watermark {watermark}"
+            ),
+            CheckpointError::Misaligned { checkpoint } => {
+                write!(
+                    f,
+                    "checkpoint {checkpoint} is not aligned with the wheel tick size"
+                )
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
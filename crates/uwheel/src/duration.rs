@@ -44,29 +44,24 @@ const fn expect_failed(message: &str) -> ! {
 /// By explicitly inserting this enum where padding is expected, the compiler is able to better
 /// perform niche value optimization.
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub(crate) enum Padding {
     #[allow(clippy::missing_docs_in_private_items)]
+    #[default]
     Optimize,
 }
 
-impl Default for Padding {
-    fn default() -> Self {
-        Self::Optimize
-    }
-}
-
 /// A span of time with nanosecond precision.
 #[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Duration {
     /// Number of whole seconds.
-    seconds: i64,
+    pub(crate) seconds: i64,
     /// Number of nanoseconds within the second. The sign always matches the `seconds` field.
-    nanoseconds: i32, // always -10^9 < nanoseconds < 10^9
+    pub(crate) nanoseconds: i32, // always -10^9 < nanoseconds < 10^9
     #[allow(clippy::missing_docs_in_private_items)]
-    padding: Padding,
+    pub(crate) padding: Padding,
 }
 
 impl fmt::Debug for Duration {
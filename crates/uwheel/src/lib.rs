@@ -17,6 +17,16 @@
 //!    - Enables min-max aggregation
 //! - `all` (_enabled by default_)
 //!    - Enables all aggregation
+//! - `quantile`
+//!    - Enables approximate quantile aggregation
+//! - `hyperloglog`
+//!    - Enables distinct count aggregation using HyperLogLog
+//! - `variance`
+//!    - Enables variance and standard deviation aggregation
+//! - `bloom`
+//!    - Enables set membership aggregation using a Bloom filter
+//! - `histogram`
+//!    - Enables fixed-bucket histogram aggregation
 //! - `top_n`
 //!    - Enables Top-N aggregation
 //! - `simd` (_requires `nightly`_)
@@ -27,8 +37,16 @@
 //!    - Enables recording of latencies for various operations
 //! - `serde`
 //!    - Enables serde support
+//! - `snapshot` (_implicitly enables `serde`_)
+//!    - Enables versioned binary snapshots of wheels
+//! - `wal` (_implicitly enables `snapshot` and `std`_)
+//!    - Enables a write-ahead log for crash recovery
+//! - `replication` (_implicitly enables `snapshot` and `std`_)
+//!    - Enables shipping deltas of wheels to read-only replicas
 //! - `timer`
 //!    - Enables scheduling user-defined functions
+//! - `derive`
+//!    - Enables `#[derive(Aggregator)]` for structs of input data
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![cfg_attr(feature = "simd", feature(portable_simd))]
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -55,7 +73,7 @@ pub mod duration;
 /// Various wheels used by µWheel
 pub mod wheels;
 
-pub use delta::DeltaState;
+pub use delta::{CheckpointError, DeltaState};
 pub use duration::{Duration, NumericalDuration};
 
 #[macro_use]
@@ -63,16 +81,35 @@ pub use duration::{Duration, NumericalDuration};
 mod macros;
 
 pub use aggregator::Aggregator;
+#[cfg(feature = "derive")]
+pub use uwheel_derive::Aggregator;
 
+#[cfg(feature = "replication")]
+pub use wheels::replication;
+#[cfg(feature = "snapshot")]
+pub use wheels::snapshot;
+#[cfg(feature = "wal")]
+pub use wheels::wal;
 pub use wheels::{
     read::{
         aggregation::conf::{CompressionPolicy, RetentionPolicy, WheelConf},
+        calendar::{CalendarConf, CalendarUnit},
         hierarchical::{Haw, HawConf, WheelRange},
+        HawLevel,
+        MergeReport,
+        MergeSpan,
+        QueryError,
+        QueryResult,
     },
     Conf,
+    InsertOutcome,
+    KeyedRwWheel,
+    LatePolicy,
     RwWheel,
+    ShardResult,
+    ShardSet,
 };
-pub use window::{Window, WindowAggregate};
+pub use window::{Window, WindowAggregate, WindowAggregateKind, WindowId};
 
 #[doc(hidden)]
 pub use time::OffsetDateTime;
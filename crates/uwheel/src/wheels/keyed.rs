@@ -0,0 +1,328 @@
+use core::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+};
+
+use super::{
+    read::{hierarchical::HawConf, ReaderWheel},
+    Conf,
+    InsertOutcome,
+    RwWheel,
+};
+use crate::{
+    aggregator::Aggregator,
+    duration::Duration,
+    wheels::read::aggregation::combine_or_insert,
+    Entry,
+    WheelRange,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// A keyed Reader-Writer Wheel that maintains a [RwWheel] per key
+///
+/// All keyed wheels are advanced under a single low watermark and wheels for new keys are created lazily
+/// using the configuration of the keyed wheel. In addition, a star wheel aggregating entries across
+/// all keys is maintained automatically and is used for queries spanning every key.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::sum::U32SumAggregator, Entry, KeyedRwWheel, NumericalDuration};
+///
+/// let mut wheel: KeyedRwWheel<&str, U32SumAggregator> = KeyedRwWheel::new(0);
+/// wheel.insert("a", Entry::new(1, 1000));
+/// wheel.insert("b", Entry::new(2, 1000));
+/// wheel.advance(5.seconds());
+///
+/// // per-key query
+/// assert_eq!(wheel.get("a").unwrap().interval(5.seconds()), Some(1));
+/// // query across a range of keys
+/// assert_eq!(wheel.interval("a".."c", 5.seconds()), Some(3));
+/// // all-key query answered by the star wheel
+/// assert_eq!(wheel.star().interval(5.seconds()), Some(3));
+/// ```
+pub struct KeyedRwWheel<K, A>
+where
+    K: Ord + Clone,
+    A: Aggregator,
+{
+    /// Configuration used to create wheels for new keys
+    conf: Conf,
+    /// Current low watermark of all wheels
+    watermark: u64,
+    /// Wheels ordered by key
+    wheels: BTreeMap<K, RwWheel<A>>,
+    /// A wheel aggregating entries across all keys
+    star: RwWheel<A>,
+}
+
+impl<K, A> KeyedRwWheel<K, A>
+where
+    K: Ord + Clone,
+    A: Aggregator,
+{
+    /// Creates a new keyed wheel starting from the given time with default configuration
+    ///
+    /// Time is represented as milliseconds
+    pub fn new(time: u64) -> Self {
+        Self::with_conf(Conf::default().with_haw_conf(HawConf::default().with_watermark(time)))
+    }
+
+    /// Creates a new keyed wheel from the given configuration
+    ///
+    /// The configuration is used for the star wheel and for every wheel created for a new key.
+    pub fn with_conf(conf: Conf) -> Self {
+        Self {
+            conf,
+            watermark: conf.reader_conf.haw_conf.watermark,
+            wheels: BTreeMap::new(),
+            star: RwWheel::with_conf(conf),
+        }
+    }
+
+    /// Inserts an entry into the wheel of the given key and into the star wheel
+    ///
+    /// A wheel is created for the key if it does not exist. Returns the outcome of inserting into the keyed wheel.
+    #[inline]
+    pub fn insert(&mut self, key: K, e: impl Into<Entry<A::Input>>) -> InsertOutcome {
+        let entry = e.into();
+        let _ = self.star.insert(entry);
+
+        let conf = self.conf.with_watermark(self.watermark);
+        self.wheels
+            .entry(key)
+            .or_insert_with(|| RwWheel::with_conf(conf))
+            .insert(entry)
+    }
+
+    /// Drains all late entries buffered in the side outputs of the keyed wheels
+    ///
+    /// Entries are only buffered if the wheel is configured with [crate::LatePolicy::SideOutput].
+    pub fn drain_late(&mut self) -> Vec<(K, Entry<A::Input>)> {
+        // the star wheel buffers the same entries
+        let _ = self.star.drain_late();
+        self.wheels
+            .iter_mut()
+            .flat_map(|(key, wheel)| {
+                wheel
+                    .drain_late()
+                    .into_iter()
+                    .map(move |entry| (key.clone(), entry))
+            })
+            .collect()
+    }
+
+    /// Returns the current watermark of the wheel
+    #[inline]
+    pub fn watermark(&self) -> u64 {
+        self.watermark
+    }
+
+    /// Advances the watermark of all wheels by the given [Duration]
+    #[inline]
+    pub fn advance(&mut self, duration: Duration) {
+        let to = self.watermark + duration.whole_milliseconds() as u64;
+        self.advance_to(to);
+    }
+
+    /// Advances the watermark of all wheels to the given watermark
+    #[inline]
+    pub fn advance_to(&mut self, watermark: u64) {
+        let _ = self.star.advance_to(watermark);
+        for wheel in self.wheels.values_mut() {
+            let _ = wheel.advance_to(watermark);
+        }
+        self.watermark = self.star.watermark();
+    }
+
+    /// Returns the reader wheel of the given key
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&ReaderWheel<A>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.wheels.get(key).map(RwWheel::read)
+    }
+
+    /// Returns the star wheel that aggregates entries across all keys
+    #[inline]
+    pub fn star(&self) -> &ReaderWheel<A> {
+        self.star.read()
+    }
+
+    /// Returns an iterator over the keys and their reader wheels ordered by key
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &ReaderWheel<A>)> {
+        self.wheels.iter().map(|(key, wheel)| (key, wheel.read()))
+    }
+
+    /// Returns the number of keys
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.wheels.len()
+    }
+
+    /// Returns `true` if no keys have been inserted
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.wheels.is_empty()
+    }
+
+    /// Combines partial aggregates within the given date range across a range of keys
+    ///
+    /// An unbounded key range is answered directly by the star wheel.
+    #[inline]
+    pub fn combine_range<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<A::PartialAggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let range = range.into();
+        self.fold_keys(keys, |wheel| wheel.combine_range(range))
+    }
+
+    /// Combines partial aggregates within the given date range across a range of keys and lowers the result
+    #[inline]
+    pub fn combine_range_and_lower<Q, R>(
+        &self,
+        keys: R,
+        range: impl Into<WheelRange>,
+    ) -> Option<A::Aggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.combine_range(keys, range).map(A::lower)
+    }
+
+    /// Returns the partial aggregate in the given time interval across a range of keys
+    #[inline]
+    pub fn interval<Q, R>(&self, keys: R, dur: Duration) -> Option<A::PartialAggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.fold_keys(keys, |wheel| wheel.interval(dur))
+    }
+
+    /// Returns the landmark window across a range of keys
+    #[inline]
+    pub fn landmark<Q, R>(&self, keys: R) -> Option<A::PartialAggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.fold_keys(keys, |wheel| wheel.landmark())
+    }
+
+    // Combines the results of a query across a range of keys
+    #[inline]
+    fn fold_keys<Q, R>(
+        &self,
+        keys: R,
+        query: impl Fn(&ReaderWheel<A>) -> Option<A::PartialAggregate>,
+    ) -> Option<A::PartialAggregate>
+    where
+        R: RangeBounds<Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        match (keys.start_bound(), keys.end_bound()) {
+            (Bound::Unbounded, Bound::Unbounded) => query(self.star.read()),
+            _ => self
+                .wheels
+                .range::<Q, R>(keys)
+                .fold(None, |mut acc, (_, wheel)| {
+                    if let Some(agg) = query(wheel.read()) {
+                        combine_or_insert::<A>(&mut acc, agg);
+                    }
+                    acc
+                }),
+        }
+    }
+
+    /// Returns an estimation of bytes used by all wheels
+    pub fn size_bytes(&self) -> usize {
+        let keys = self.wheels.len() * core::mem::size_of::<K>();
+        let wheels = self
+            .wheels
+            .values()
+            .map(|wheel| wheel.size_bytes())
+            .sum::<usize>();
+        keys + wheels + self.star.size_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aggregator::sum::U64SumAggregator, *};
+
+    #[test]
+    fn keyed_wheel_test() {
+        let watermark = 1699488000000;
+        let mut wheel: KeyedRwWheel<u32, U64SumAggregator> = KeyedRwWheel::new(watermark);
+
+        wheel.insert(1, Entry::new(10, watermark));
+        wheel.insert(2, Entry::new(20, watermark + 1000));
+        wheel.insert(3, Entry::new(5, watermark + 2000));
+        assert_eq!(wheel.len(), 3);
+
+        wheel.advance(3.seconds());
+        assert_eq!(wheel.watermark(), watermark + 3000);
+
+        assert_eq!(wheel.get(&1).unwrap().landmark(), Some(10));
+        assert_eq!(wheel.landmark(..), Some(35));
+        assert_eq!(wheel.landmark(1..3), Some(30));
+        assert_eq!(wheel.landmark(2..), Some(25));
+        assert_eq!(wheel.interval(..=2, 2.seconds()), Some(20));
+        assert_eq!(wheel.interval(.., 1.seconds()), Some(5));
+
+        let range = WheelRange::new_unchecked(watermark, watermark + 2000);
+        assert_eq!(wheel.combine_range(.., range), Some(30));
+        assert_eq!(wheel.combine_range(2..=3, range), Some(20));
+        assert_eq!(wheel.combine_range(4.., range), None);
+
+        // wheels for new keys start from the current watermark
+        wheel.insert(4, Entry::new(1, watermark + 3000));
+        wheel.advance(1.seconds());
+        let new_wheel = wheel.get(&4).unwrap();
+        assert_eq!(new_wheel.watermark(), watermark + 4000);
+        assert_eq!(new_wheel.landmark(), Some(1));
+        assert_eq!(wheel.landmark(..), Some(36));
+        assert_eq!(
+            wheel.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn keyed_wheel_late_test() {
+        let conf = Conf::default()
+            .with_haw_conf(HawConf::default().with_watermark(10000))
+            .with_late_policy(LatePolicy::SideOutput);
+        let mut wheel: KeyedRwWheel<&str, U64SumAggregator> = KeyedRwWheel::with_conf(conf);
+
+        assert_eq!(
+            wheel.insert("a", Entry::new(1, 5000)),
+            InsertOutcome::Late(LatePolicy::SideOutput)
+        );
+        let late = wheel.drain_late();
+        assert_eq!(late.len(), 1);
+        assert_eq!(late[0].0, "a");
+        assert!(wheel.drain_late().is_empty());
+        assert!(wheel.star().landmark().is_none());
+    }
+}
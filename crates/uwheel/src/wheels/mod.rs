@@ -1,7 +1,20 @@
+/// Keyed Reader-Writer Wheel
+pub mod keyed;
 /// Reader Wheel
 ///
 /// Single reader or multi-reader with the ``sync`` feature enabled.
 pub mod read;
+/// Replication of wheels across processes
+#[cfg(feature = "replication")]
+pub mod replication;
+/// Queries across sharded wheels
+pub mod shard;
+/// Versioned binary snapshots of wheels
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+/// Write-ahead logging and crash recovery
+#[cfg(feature = "wal")]
+pub mod wal;
 /// Extension trait for implementing a custom wheel
 pub mod wheel_ext;
 /// Writer Wheel
@@ -20,13 +33,15 @@ use crate::{aggregator::Aggregator, duration::Duration, window::WindowAggregate,
 use core::fmt::Debug;
 use write::DEFAULT_WRITE_AHEAD_SLOTS;
 
+pub use keyed::KeyedRwWheel;
 pub use read::{DAYS, HOURS, MINUTES, SECONDS, WEEKS, YEARS};
+pub use shard::{ShardResult, ShardSet};
 pub use wheel_ext::WheelExt;
-pub use write::WriterWheel;
+pub use write::{InsertOutcome, LatePolicy, WriterWheel};
 
-use self::read::{hierarchical::HawConf, ReaderWheel};
+use self::read::{hierarchical::HawConf, MergeReport, ReaderWheel};
 
-use crate::window::Window;
+use crate::window::{Window, WindowId};
 
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
@@ -85,7 +100,7 @@ where
     /// A multiple-reader wheel designed for efficient querying of aggregate across arbitrary time ranges
     reader: ReaderWheel<A>,
     #[cfg(feature = "profiler")]
-    stats: stats::Stats,
+    pub(crate) stats: stats::Stats,
 }
 
 impl<A: Aggregator> Default for RwWheel<A> {
@@ -116,20 +131,36 @@ where
     /// let conf = Conf::default().with_haw_conf(HawConf::default().with_watermark(10000));
     /// let wheel: RwWheel<U32SumAggregator> = RwWheel::with_conf(conf);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the reader wheel generates deltas (see [HawConf::with_deltas]) and late entries are
+    /// corrected, either through [LatePolicy::Correct] or an allowed lateness.
     pub fn with_conf(conf: Conf) -> Self {
+        assert!(
+            !conf.reader_conf.haw_conf.generate_deltas
+                || conf.writer_conf.late_policy != LatePolicy::Correct,
+            "LatePolicy::Correct cannot be combined with generated deltas"
+        );
         Self {
             writer: WriterWheel::with_capacity_and_watermark(
                 conf.writer_conf.write_ahead_capacity,
                 conf.reader_conf.haw_conf.watermark,
-            ),
+            )
+            .with_slot_size(Duration::milliseconds(
+                conf.reader_conf.haw_conf.tick_size_ms() as i64,
+            ))
+            .with_late_policy(conf.writer_conf.late_policy)
+            .with_allowed_lateness(conf.reader_conf.haw_conf.allowed_lateness),
             reader: ReaderWheel::with_conf(conf.reader_conf.haw_conf),
             #[cfg(feature = "profiler")]
             stats: stats::Stats::default(),
         }
     }
-    /// Installs a periodic window aggregation query
+    /// Installs a periodic window aggregation query and returns a handle to it
     ///
-    /// Results of the window are returned when advancing the wheel with [Self::advance_to] or [Self::advance].
+    /// Results of the window are returned when advancing the wheel with [Self::advance_to] or [Self::advance]
+    /// and are tagged with the returned [WindowId]. Multiple windows may be installed at the same time.
     ///
     /// # Example
     ///
@@ -140,34 +171,71 @@ where
     /// let window = Window::sliding(10.seconds(), 3.seconds());
     /// // Initialize a Reader-Writer Wheel and install the window
     /// let mut wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
-    /// wheel.window(window);
+    /// let id = wheel.window(window);
     /// ```
-    pub fn window(&mut self, window: impl Into<Window>) {
-        self.reader.window(window.into());
+    pub fn window(&mut self, window: impl Into<Window>) -> WindowId {
+        self.reader.window(window.into())
+    }
+
+    /// Removes a previously installed window
+    ///
+    /// Returns `true` if the window was installed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{Window, aggregator::sum::U32SumAggregator, RwWheel, NumericalDuration};
+    ///
+    /// let mut wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
+    /// let id = wheel.window(Window::tumbling(10.seconds()));
+    /// assert!(wheel.remove_window(id));
+    /// assert!(!wheel.remove_window(id));
+    /// ```
+    pub fn remove_window(&mut self, id: WindowId) -> bool {
+        self.reader.remove_window(id)
     }
 
     /// Inserts an entry into the wheel
     ///
-    /// # Safety
+    /// # Late entries
     ///
-    /// Entries with timestamps below the current low watermark ([Self::watermark]) are dropped.
+    /// Entries with timestamps below the current low watermark ([Self::watermark]) are handled
+    /// by the configured [LatePolicy] (see [Conf::with_late_policy]). By default they are dropped.
+    ///
+    /// Entries within the allowed lateness (see [HawConf::with_allowed_lateness]) are always corrected
+    /// and update installed windows that have already been emitted.
     ///
     /// # Example
     ///
     /// ```
-    /// use uwheel::{aggregator::sum::U32SumAggregator, RwWheel, Entry};
+    /// use uwheel::{aggregator::sum::U32SumAggregator, RwWheel, Entry, InsertOutcome};
     ///
     /// let mut wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
     /// let data = 10;
     /// let timestamp = 1000;
-    /// wheel.insert(Entry::new(data, timestamp));
+    /// assert_eq!(wheel.insert(Entry::new(data, timestamp)), InsertOutcome::WriteAhead);
     /// ```
     #[inline]
-    pub fn insert(&mut self, e: impl Into<Entry<A::Input>>) {
+    pub fn insert(&mut self, e: impl Into<Entry<A::Input>>) -> InsertOutcome {
         #[cfg(feature = "profiler")]
         profile_scope!(&self.stats.insert);
 
-        self.writer.insert(e);
+        let entry = e.into();
+        let outcome = self.writer.insert(entry);
+
+        if outcome == InsertOutcome::Late(LatePolicy::Correct) {
+            let partial = A::freeze(A::lift(entry.data));
+            self.reader.apply_late(entry.timestamp, partial);
+        }
+
+        outcome
+    }
+
+    /// Drains all late entries buffered in the side output
+    ///
+    /// Entries are only buffered if the wheel is configured with [LatePolicy::SideOutput].
+    pub fn drain_late(&mut self) -> Vec<Entry<A::Input>> {
+        self.writer.drain_late()
     }
 
     /// Returns a reference to the writer wheel
@@ -178,9 +246,19 @@ where
     pub fn read(&self) -> &ReaderWheel<A> {
         &self.reader
     }
-    /// Merges another read wheel with same size into this one
-    pub fn merge_read_wheel(&self, other: &ReaderWheel<A>) {
-        self.read().merge(other);
+    /// Merges another read wheel into this one
+    ///
+    /// If `other` is ahead in time, this wheel is first advanced to its watermark through
+    /// [RwWheel::advance_to] so that pending writes are not skipped. Window aggregates produced by
+    /// that advancement are not returned, so advance the wheel beforehand if they are needed.
+    ///
+    /// Returns a [MergeReport] describing which parts of `other` were merged and which were not.
+    pub fn merge_read_wheel(&mut self, other: &ReaderWheel<A>) -> MergeReport {
+        let other_watermark = other.watermark();
+        if other_watermark > self.watermark() {
+            self.advance_to(other_watermark);
+        }
+        self.read().merge(other)
     }
     /// Returns the current watermark of this wheel
     pub fn watermark(&self) -> u64 {
@@ -237,6 +315,24 @@ where
         read + write
     }
 
+    /// Encodes the wheel into a versioned binary snapshot
+    ///
+    /// Unlike [Haw::to_snapshot] the snapshot also contains the writer wheel with aggregates above the watermark.
+    ///
+    /// [Haw::to_snapshot]: read::Haw::to_snapshot
+    #[cfg(feature = "snapshot")]
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        use snapshot::Snapshot;
+        let conf = *self.reader.as_ref().conf();
+        snapshot::encode::<A, Self>(snapshot::SnapshotKind::RwWheel, &conf, &self.to_v1())
+    }
+
+    /// Decodes a wheel from a snapshot created by [Self::to_snapshot]
+    #[cfg(feature = "snapshot")]
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, snapshot::SnapshotError> {
+        snapshot::decode::<A, _>(snapshot::SnapshotKind::RwWheel, bytes)
+    }
+
     #[cfg(feature = "profiler")]
     /// Prints the stats of the [RwWheel]
     pub fn print_stats(&self) {
@@ -317,11 +413,14 @@ where
 pub struct WriterConf {
     /// Defines the capacity of write-ahead slots
     write_ahead_capacity: usize,
+    /// Defines how late entries are handled
+    late_policy: LatePolicy,
 }
 impl Default for WriterConf {
     fn default() -> Self {
         Self {
             write_ahead_capacity: DEFAULT_WRITE_AHEAD_SLOTS,
+            late_policy: LatePolicy::default(),
         }
     }
 }
@@ -345,7 +444,8 @@ pub struct Conf {
 impl Conf {
     /// Configure the number of write-ahead slots
     ///
-    /// The default value is [DEFAULT_WRITE_AHEAD_SLOTS]
+    /// The default value is [DEFAULT_WRITE_AHEAD_SLOTS]. Each slot covers a single tick of the
+    /// reader wheel which is a second unless a milliseconds wheel is configured (see [HawConf::with_milliseconds]).
     ///
     /// # Example
     ///
@@ -359,6 +459,23 @@ impl Conf {
         self.writer_conf.write_ahead_capacity = capacity;
         self
     }
+    /// Configures how entries below the low watermark are handled
+    ///
+    /// The default value is [LatePolicy::Drop]. Note that [LatePolicy::Correct] cannot be combined
+    /// with a reader wheel that generates deltas (see [HawConf::with_deltas]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{Conf, LatePolicy};
+    ///
+    /// // Correct the frozen slots of the reader wheel with late entries
+    /// let rw_conf = Conf::default().with_late_policy(LatePolicy::Correct);
+    /// ```
+    pub fn with_late_policy(mut self, policy: LatePolicy) -> Self {
+        self.writer_conf.late_policy = policy;
+        self
+    }
     /// Configures the reader wheel to use the given [HawConf]
     ///
     /// # Example
@@ -374,14 +491,17 @@ impl Conf {
         self.reader_conf.haw_conf = conf;
         self
     }
+    /// Configures the initial watermark of the wheel
+    pub(crate) fn with_watermark(mut self, watermark: u64) -> Self {
+        self.reader_conf.haw_conf = self.reader_conf.haw_conf.with_watermark(watermark);
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "timer")]
-    use core::cell::RefCell;
-    #[cfg(feature = "timer")]
-    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
 
     use super::*;
     use crate::{aggregator::sum::U32SumAggregator, duration::*, *};
@@ -416,6 +536,139 @@ mod tests {
         assert_eq!(read.interval(4.seconds()), Some(1000));
     }
 
+    #[test]
+    fn late_policy_test() {
+        // default policy drops late entries
+        let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
+        rw_wheel.advance_to(2000);
+        assert_eq!(
+            rw_wheel.insert(Entry::new(10, 500)),
+            InsertOutcome::Late(LatePolicy::Drop)
+        );
+        assert_eq!(rw_wheel.read().interval(2.seconds()), Some(0));
+
+        // side output keeps late entries around for the user
+        let conf = Conf::default().with_late_policy(LatePolicy::SideOutput);
+        let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::with_conf(conf);
+        rw_wheel.advance_to(2000);
+        rw_wheel.insert(Entry::new(10, 500));
+        assert_eq!(rw_wheel.read().interval(2.seconds()), Some(0));
+        assert_eq!(rw_wheel.drain_late().len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn late_correct_with_deltas_test() {
+        let conf = Conf::default()
+            .with_haw_conf(HawConf::default().with_deltas())
+            .with_late_policy(LatePolicy::Correct);
+        let _wheel: RwWheel<U32SumAggregator> = RwWheel::with_conf(conf);
+    }
+
+    #[test]
+    #[should_panic]
+    fn allowed_lateness_with_deltas_test() {
+        let haw_conf = HawConf::default()
+            .with_deltas()
+            .with_allowed_lateness(5.seconds());
+        let _wheel: RwWheel<U32SumAggregator> =
+            RwWheel::with_conf(Conf::default().with_haw_conf(haw_conf));
+    }
+
+    #[test]
+    fn late_entries_with_deltas_test() {
+        // late entries are never corrected into a wheel generating deltas, keeping replicas in sync
+        let haw_conf = HawConf::default().with_watermark(1000).with_deltas();
+        let mut primary: RwWheel<U32SumAggregator> =
+            RwWheel::with_conf(Conf::default().with_haw_conf(haw_conf));
+        primary.insert(Entry::new(1, 1000));
+        primary.advance_to(3000);
+        let base = primary.read().delta_checkpoint(1000).unwrap();
+
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(1000);
+        replica.apply_checkpoints([base]).unwrap();
+
+        assert_eq!(
+            primary.insert(Entry::new(100, 1500)),
+            InsertOutcome::Late(LatePolicy::Drop)
+        );
+        primary.advance_to(4000);
+        let delta = primary.read().delta_checkpoint(3000).unwrap();
+        replica.apply_checkpoints([delta]).unwrap();
+
+        assert_eq!(primary.read().landmark(), Some(1));
+        assert_eq!(replica.landmark(), primary.read().landmark());
+    }
+
+    #[test]
+    fn sub_second_ticks_test() {
+        let haw_conf = HawConf::default().with_milliseconds(100.milliseconds());
+        let conf = Conf::default().with_haw_conf(haw_conf);
+        let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::with_conf(conf);
+        let id = rw_wheel.window(Window::tumbling(500.milliseconds()));
+
+        rw_wheel.insert(Entry::new(1, 50));
+        rw_wheel.insert(Entry::new(2, 150));
+        rw_wheel.insert(Entry::new(3, 650));
+        rw_wheel.insert(Entry::new(4, 1250));
+
+        let windows = rw_wheel.advance_to(1000);
+        assert_eq!(rw_wheel.watermark(), 1000);
+        assert_eq!(
+            windows
+                .iter()
+                .map(|w| (w.window_id, w.window_start_ms, w.window_end_ms, w.aggregate))
+                .collect::<Vec<_>>(),
+            vec![(id, 0, 500, 3), (id, 500, 1000, 3)]
+        );
+
+        assert_eq!(rw_wheel.read().interval(100.milliseconds()), Some(0));
+        assert_eq!(rw_wheel.read().interval(500.milliseconds()), Some(3));
+        assert_eq!(
+            rw_wheel
+                .read()
+                .combine_range(WheelRange::new_unchecked(100, 700)),
+            Some(5)
+        );
+
+        rw_wheel.advance_to(1300);
+        assert_eq!(rw_wheel.read().interval(100.milliseconds()), Some(4));
+        assert_eq!(rw_wheel.read().interval(200.milliseconds()), Some(4));
+        assert_eq!(rw_wheel.read().interval(300.milliseconds()), Some(4));
+        assert_eq!(rw_wheel.read().landmark(), Some(10));
+    }
+
+    #[test]
+    fn late_correction_test() {
+        let conf = Conf::default().with_late_policy(LatePolicy::Correct);
+        let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::with_conf(conf);
+        rw_wheel.insert(Entry::new(10, 1000));
+        rw_wheel.insert(Entry::new(10, 5000));
+
+        // move past the seconds wheel so the late entry is rolled up into minutes
+        rw_wheel.advance(2.minutes());
+        assert_eq!(rw_wheel.read().interval(2.minutes()), Some(20));
+
+        assert_eq!(
+            rw_wheel.insert(Entry::new(5, 1500)),
+            InsertOutcome::Late(LatePolicy::Correct)
+        );
+        assert_eq!(rw_wheel.read().interval(2.minutes()), Some(25));
+        assert_eq!(
+            rw_wheel
+                .read()
+                .combine_range(WheelRange::new_unchecked(0, 60000)),
+            Some(25)
+        );
+
+        // entries within the current seconds rotation are corrected as well
+        rw_wheel.insert(Entry::new(1, 119000));
+        rw_wheel.advance(1.seconds());
+        rw_wheel.insert(Entry::new(2, 119500));
+        assert_eq!(rw_wheel.read().interval(1.seconds()), Some(0));
+        assert_eq!(rw_wheel.read().interval(2.seconds()), Some(3));
+    }
+
     #[test]
     fn insert_test() {
         let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::default();
@@ -462,12 +715,12 @@ mod tests {
     #[test]
     fn timer_once_test() {
         let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::default();
-        let gate = Rc::new(RefCell::new(false));
+        let gate = Arc::new(Mutex::new(false));
         let inner_gate = gate.clone();
 
         let _ = rw_wheel.read().schedule_once(5000, move |read| {
             if let Some(last_five) = read.interval(5.seconds()) {
-                *inner_gate.borrow_mut() = true;
+                *inner_gate.lock().unwrap() = true;
                 assert_eq!(last_five, 1000);
             }
         });
@@ -479,14 +732,14 @@ mod tests {
         rw_wheel.advance_to(5000);
 
         // assert that the timer action was triggered
-        assert!(*gate.borrow());
+        assert!(*gate.lock().unwrap());
     }
 
     #[cfg(feature = "timer")]
     #[test]
     fn timer_repeat_test() {
         let mut rw_wheel: RwWheel<U32SumAggregator> = RwWheel::default();
-        let sum = Rc::new(RefCell::new(0));
+        let sum = Arc::new(Mutex::new(0));
         let inner_sum = sum.clone();
 
         // schedule a repeat action
@@ -494,7 +747,7 @@ mod tests {
             .read()
             .schedule_repeat(5000, 5.seconds(), move |read| {
                 if let Some(last_five) = read.interval(5.seconds()) {
-                    *inner_sum.borrow_mut() += last_five;
+                    *inner_sum.lock().unwrap() += last_five;
                 }
             });
 
@@ -505,7 +758,7 @@ mod tests {
 
         // trigger first timer to add sum of last 5 seconds
         rw_wheel.advance_to(5000);
-        assert_eq!(*sum.borrow(), 1000);
+        assert_eq!(*sum.lock().unwrap(), 1000);
 
         rw_wheel.insert(Entry::new(250, 5000));
         rw_wheel.insert(Entry::new(250, 6000));
@@ -513,7 +766,7 @@ mod tests {
 
         // trigger second timer to add sum of last 5 seconds
         rw_wheel.advance_to(10000);
-        assert_eq!(*sum.borrow(), 1750);
+        assert_eq!(*sum.lock().unwrap(), 1750);
     }
 
     #[cfg(feature = "sync")]
@@ -710,4 +963,104 @@ mod tests {
         // assert_eq!(wheel.read().interval(2.seconds()), Some(5));
         assert_eq!(wheel.read().interval(10.seconds()), Some(6));
     }
+
+    #[test]
+    fn merge_unaligned_levels_test() {
+        let mut wheel = RwWheel::<U32SumAggregator>::new(0);
+        wheel.insert(Entry::new(1u32, 5000));
+        wheel.advance(2.minutes());
+
+        // started 30 seconds later so its minute slots do not line up
+        let mut other = RwWheel::<U32SumAggregator>::new(30000);
+        other.insert(Entry::new(2u32, 35000));
+        other.insert(Entry::new(3u32, 100000));
+        other.advance_to(120000);
+
+        let report = wheel.merge_read_wheel(other.read());
+
+        assert_eq!(report.watermark, 120000);
+        assert!(!report.is_complete());
+        assert!(report
+            .merged
+            .iter()
+            .all(|span| span.level == HawLevel::Seconds));
+        assert_eq!(
+            report.unmerged,
+            vec![MergeSpan {
+                level: HawLevel::Minutes,
+                start_ms: 30000,
+                end_ms: 90000,
+                slots: 1,
+            }]
+        );
+        assert_eq!(wheel.read().interval(30.seconds()), Some(3));
+        // the minute slots of the other wheel were not merged
+        assert_eq!(wheel.read().interval(2.minutes()), Some(1));
+    }
+
+    #[test]
+    fn merge_retention_test() {
+        let mut wheel = RwWheel::<U32SumAggregator>::new(0);
+        wheel.advance(2.hours());
+
+        let haw_conf = HawConf::default().with_retention_policy(RetentionPolicy::Keep);
+        let conf = Conf::default().with_haw_conf(haw_conf);
+        let mut other = RwWheel::<U32SumAggregator>::with_conf(conf);
+        other.insert(Entry::new(1u32, 1000));
+        other.advance(2.hours());
+
+        let report = wheel.merge_read_wheel(other.read());
+
+        // older seconds and minutes are only retained by the other wheel
+        let levels: Vec<_> = report.unmerged.iter().map(|span| span.level).collect();
+        assert_eq!(levels, vec![HawLevel::Seconds, HawLevel::Minutes]);
+
+        let other_haw = other.read().as_ref();
+        let other_slots = other_haw.seconds_unchecked().len()
+            + other_haw.minutes_unchecked().len()
+            + other_haw.hours_unchecked().len();
+        assert_eq!(report.merged_slots() + report.unmerged_slots(), other_slots);
+
+        // the hours level still holds the aggregate
+        assert_eq!(wheel.read().interval(2.hours()), Some(1));
+    }
+
+    #[test]
+    fn merge_data_layout_test() {
+        let haw_conf = HawConf::default().with_prefix_sum();
+        let conf = Conf::default().with_haw_conf(haw_conf);
+        let mut prefix_wheel = RwWheel::<U32SumAggregator>::with_conf(conf);
+        prefix_wheel.insert(Entry::new(1u32, 1000));
+        prefix_wheel.advance(10.seconds());
+
+        let mut wheel = RwWheel::<U32SumAggregator>::new(0);
+        wheel.insert(Entry::new(2u32, 2000));
+        wheel.advance(10.seconds());
+
+        let report = prefix_wheel.merge_read_wheel(wheel.read());
+        assert!(report.is_complete());
+        assert_eq!(prefix_wheel.read().interval(10.seconds()), Some(3));
+        assert_eq!(prefix_wheel.read().interval(8.seconds()), Some(2));
+
+        let report = wheel.merge_read_wheel(prefix_wheel.read());
+        assert!(report.is_complete());
+        assert_eq!(wheel.read().interval(10.seconds()), Some(5));
+    }
+
+    #[test]
+    fn merge_read_wheel_advances_writer_test() {
+        let mut wheel = RwWheel::<U32SumAggregator>::new(0);
+        // still pending in the writer wheel
+        wheel.insert(Entry::new(4u32, 5000));
+
+        let mut other = RwWheel::<U32SumAggregator>::new(0);
+        other.insert(Entry::new(1u32, 2000));
+        other.advance(10.seconds());
+
+        let report = wheel.merge_read_wheel(other.read());
+
+        assert!(report.is_complete());
+        assert_eq!(wheel.watermark(), 10000);
+        assert_eq!(wheel.read().interval(10.seconds()), Some(5));
+    }
 }
@@ -80,10 +80,36 @@ impl<A: Aggregator> Data<A> {
         }
     }
 
-    pub fn merge(&mut self, other: &Self) {
-        match (self, other) {
-            (Data::Deque(arr), Data::Deque(arr_other)) => arr.merge(arr_other),
-            _ => unimplemented!("Only Deque Merging supported as of now"),
+    /// Combines `partials` ordered from newest to oldest into the slots starting at `offset`
+    ///
+    /// Returns the number of partials that were combined into existing slots.
+    pub fn merge_at(&mut self, offset: usize, partials: &[A::PartialAggregate]) -> usize {
+        let merged = partials.len().min(self.len().saturating_sub(offset));
+        let partials = &partials[..merged];
+        match self {
+            Data::Deque(arr) => {
+                for (index, partial) in partials.iter().enumerate() {
+                    arr.combine_at(offset + index, partial.clone());
+                }
+            }
+            Data::PrefixDeque(parr) => parr.merge_at(offset, partials),
+            Data::CompressedDeque(arr) => {
+                for (index, partial) in partials.iter().enumerate() {
+                    arr.combine_at(offset + index, partial.clone());
+                }
+            }
+        }
+        merged
+    }
+
+    /// Combines `partial` into the slot at `index`
+    ///
+    /// Returns `false` if the slot does not exist.
+    pub fn combine_at(&mut self, index: usize, partial: A::PartialAggregate) -> bool {
+        match self {
+            Data::Deque(arr) => arr.combine_at(index, partial),
+            Data::PrefixDeque(parr) => parr.combine_at(index, partial),
+            Data::CompressedDeque(arr) => arr.combine_at(index, partial),
         }
     }
 
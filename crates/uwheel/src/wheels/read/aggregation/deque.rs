@@ -1,6 +1,9 @@
 use super::{combine_or_insert, into_range};
-use crate::Aggregator;
-use core::ops::{Bound, Deref, DerefMut, Range, RangeBounds};
+use crate::{aggregator::Compression, Aggregator};
+use core::{
+    mem,
+    ops::{Bound, Deref, DerefMut, Range, RangeBounds},
+};
 
 #[cfg(not(feature = "std"))]
 use alloc::collections::VecDeque;
@@ -16,7 +19,7 @@ use alloc::vec::Vec;
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 #[derive(Default, Clone, Debug)]
 pub struct MutablePartialDeque<A: Aggregator> {
-    inner: VecDeque<A::PartialAggregate>,
+    pub(crate) inner: VecDeque<A::PartialAggregate>,
 }
 
 impl<A: Aggregator> MutablePartialDeque<A> {
@@ -94,7 +97,7 @@ impl<A: Aggregator> MutablePartialDeque<A> {
             .iter()
             .skip(start)
             .take(slots)
-            .copied()
+            .cloned()
             .rev()
             .collect()
     }
@@ -121,7 +124,7 @@ impl<A: Aggregator> MutablePartialDeque<A> {
                     .iter()
                     .skip(start)
                     .take(slots)
-                    .copied()
+                    .cloned()
                     .fold(A::IDENTITY, A::combine),
             )
         }
@@ -147,7 +150,7 @@ impl<A: Aggregator> MutablePartialDeque<A> {
 
         for partial in relevant_range {
             if filter(partial) {
-                combine_or_insert::<A>(&mut accumulator, *partial);
+                combine_or_insert::<A>(&mut accumulator, partial.clone());
             }
         }
 
@@ -166,6 +169,20 @@ impl<A: Aggregator> MutablePartialDeque<A> {
     pub fn get_mut(&mut self, slot: usize) -> Option<&mut A::PartialAggregate> {
         self.inner.get_mut(slot)
     }
+
+    /// Combines a partial aggregate into the given slot
+    ///
+    /// Returns `false` if the slot is out of bounds.
+    #[inline]
+    pub fn combine_at(&mut self, slot: usize, partial: A::PartialAggregate) -> bool {
+        match self.inner.get_mut(slot) {
+            Some(curr) => {
+                *curr = A::combine(mem::take(curr), partial);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 impl<A: Aggregator> Deref for MutablePartialDeque<A> {
@@ -187,8 +204,8 @@ impl<A: Aggregator> DerefMut for MutablePartialDeque<A> {
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 #[derive(Default, Clone, Debug)]
 pub struct PrefixDeque<A: Aggregator> {
-    slots: MutablePartialDeque<A>,
-    prefix: MutablePartialDeque<A>,
+    pub(crate) slots: MutablePartialDeque<A>,
+    pub(crate) prefix: MutablePartialDeque<A>,
 }
 
 impl<A: Aggregator> PrefixDeque<A> {
@@ -221,6 +238,19 @@ impl<A: Aggregator> PrefixDeque<A> {
         self.slots.pop_back();
         self.rebuild_prefix();
     }
+    pub(crate) fn combine_at(&mut self, slot: usize, partial: A::PartialAggregate) -> bool {
+        let updated = self.slots.combine_at(slot, partial);
+        if updated {
+            self.rebuild_prefix();
+        }
+        updated
+    }
+    pub(crate) fn merge_at(&mut self, offset: usize, partials: &[A::PartialAggregate]) {
+        for (index, partial) in partials.iter().enumerate() {
+            self.slots.combine_at(offset + index, partial.clone());
+        }
+        self.rebuild_prefix();
+    }
     pub(crate) fn slots_slice(&self) -> &[A::PartialAggregate] {
         self.slots.as_slice()
     }
@@ -266,8 +296,8 @@ impl<A: Aggregator> PrefixDeque<A> {
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 #[derive(Clone, Debug)]
 pub struct CompressedDeque<A: Aggregator> {
-    buffer: MutablePartialDeque<A>,
-    chunks: VecDeque<Vec<u8>>,
+    pub(crate) buffer: MutablePartialDeque<A>,
+    pub(crate) chunks: VecDeque<Vec<u8>>,
     pub(crate) chunk_size: usize,
 }
 
@@ -326,6 +356,38 @@ impl<A: Aggregator> CompressedDeque<A> {
         self.chunks.pop_back();
     }
 
+    pub(crate) fn combine_at(&mut self, slot: usize, partial: A::PartialAggregate) -> bool {
+        let buffer_size = self.buffer.len();
+        if slot < buffer_size {
+            return self.buffer.combine_at(slot, partial);
+        }
+
+        // locate the compressed chunk containing the slot
+        let chunk_idx = (slot - buffer_size) / self.chunk_size;
+        let offset = (slot - buffer_size) % self.chunk_size;
+
+        // SAFETY: we are sure that compression is implemented since we assert it in the constructor
+        let Compression {
+            compressor,
+            decompressor,
+        } = A::compression().unwrap();
+
+        match self.chunks.get_mut(chunk_idx) {
+            Some(chunk) => {
+                let mut partials = (decompressor)(chunk);
+                match partials.get_mut(offset) {
+                    Some(curr) => {
+                        *curr = A::combine(mem::take(curr), partial);
+                        *chunk = (compressor)(&partials);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            None => false,
+        }
+    }
+
     #[inline]
     pub(crate) fn range<R>(&self, range: R) -> Vec<A::PartialAggregate>
     where
@@ -372,7 +434,7 @@ impl<A: Aggregator> CompressedDeque<A> {
 
         // check whether we need to include the buffer which is not compressed
         if buffer_included {
-            vec.extend_from_slice(&self.buffer.inner.iter().copied().collect::<Vec<_>>());
+            vec.extend(self.buffer.inner.iter().cloned());
         }
 
         let start_after_buffer = start.saturating_sub(buffer_size);
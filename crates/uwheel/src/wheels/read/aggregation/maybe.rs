@@ -8,7 +8,7 @@ use super::{conf::WheelConf, Wheel};
 use crate::{
     aggregator::Aggregator,
     wheels::read::{
-        hierarchical::{Granularity, WheelRange},
+        hierarchical::{to_unix_ms, Granularity, WheelRange},
         plan::{Aggregation, WheelAggregation},
     },
 };
@@ -19,8 +19,8 @@ use crate::{
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 #[derive(Debug, Clone)]
 pub(crate) struct MaybeWheel<A: Aggregator> {
-    conf: WheelConf,
-    inner: Option<Wheel<A>>,
+    pub(crate) conf: WheelConf,
+    pub(crate) inner: Option<Wheel<A>>,
 }
 impl<A: Aggregator> MaybeWheel<A> {
     pub fn new(conf: WheelConf) -> Self {
@@ -31,13 +31,6 @@ impl<A: Aggregator> MaybeWheel<A> {
             wheel.clear();
         }
     }
-    pub fn merge(&mut self, other: &Self) {
-        if let Some(wheel) = self.inner.as_mut() {
-            // TODO: fix better checks
-            wheel.merge(other.as_ref().unwrap());
-        }
-    }
-
     #[inline]
     pub fn range(
         &self,
@@ -48,9 +41,10 @@ impl<A: Aggregator> MaybeWheel<A> {
         self.calculate_slots(start_date, slots, gran)
             .and_then(|(start, end)| {
                 self.inner.as_ref().map(|wheel| {
-                    let start_ts = start_date.unix_timestamp() as u64 * 1000; // to ms
+                    let start_ts = to_unix_ms(start_date);
 
                     let interval = match gran {
+                        Granularity::Millisecond => self.conf.tick_size_ms as i128,
                         Granularity::Second => Duration::SECOND.whole_milliseconds(),
                         Granularity::Minute => Duration::MINUTE.whole_milliseconds(),
                         Granularity::Hour => Duration::HOUR.whole_milliseconds(),
@@ -83,7 +77,7 @@ impl<A: Aggregator> MaybeWheel<A> {
     #[allow(dead_code)]
     #[inline]
     pub fn head(&self) -> Option<A::PartialAggregate> {
-        self.inner.as_ref().and_then(|w| w.at(0)).copied()
+        self.inner.as_ref().and_then(|w| w.at(0)).cloned()
     }
 
     #[inline]
@@ -117,11 +111,14 @@ impl<A: Aggregator> MaybeWheel<A> {
         gran: Granularity,
     ) -> Option<(usize, usize)> {
         let watermark_date =
-            |wm: u64| OffsetDateTime::from_unix_timestamp((wm as i64) / 1000).unwrap();
+            |wm: u64| OffsetDateTime::from_unix_timestamp_nanos(wm as i128 * 1_000_000).unwrap();
         self.inner.as_ref().and_then(|wheel| {
             let watermark = watermark_date(wheel.watermark());
             let distance = watermark - start;
             let slot_distance = match gran {
+                Granularity::Millisecond => {
+                    (distance.whole_milliseconds() / self.conf.tick_size_ms as i128) as i64
+                }
                 Granularity::Second => distance.whole_seconds(),
                 Granularity::Minute => distance.whole_minutes(),
                 Granularity::Hour => distance.whole_hours(),
@@ -145,6 +142,9 @@ impl<A: Aggregator> MaybeWheel<A> {
         } else {
             let diff = range.end - range.start;
             let slots = (match gran {
+                Granularity::Millisecond => {
+                    (diff.whole_milliseconds() / self.conf.tick_size_ms as i128) as i64
+                }
                 Granularity::Second => diff.whole_seconds(),
                 Granularity::Minute => diff.whole_minutes(),
                 Granularity::Hour => diff.whole_hours(),
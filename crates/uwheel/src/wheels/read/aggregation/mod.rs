@@ -28,7 +28,7 @@ pub mod iter;
 /// A maybe initialized [Wheel]
 pub mod maybe;
 
-mod data;
+pub(crate) mod data;
 
 #[cfg(feature = "profiler")]
 use stats::Stats;
@@ -46,8 +46,7 @@ pub fn combine_or_insert<A: Aggregator>(
 ) {
     match dest {
         Some(curr) => {
-            let new_curr = A::combine(*curr, entry);
-            *curr = new_curr;
+            *curr = A::combine(mem::take(curr), entry);
         }
         None => {
             *dest = Some(entry);
@@ -105,27 +104,27 @@ impl<A: Aggregator> WheelSlot<A> {
 #[derive(Clone, Debug)]
 pub struct Wheel<A: Aggregator> {
     /// Number of slots (60 seconds => 60 slots)
-    capacity: usize,
+    pub(crate) capacity: usize,
     /// Total number of wheel slots (must be power of two)
-    num_slots: usize,
+    pub(crate) num_slots: usize,
     /// Partial aggregate for a full rotation
-    total: Option<A::PartialAggregate>,
+    pub(crate) total: Option<A::PartialAggregate>,
     /// The current watermark for this wheel
-    watermark: u64,
+    pub(crate) watermark: u64,
     /// Configured tick size in milliseconds (seconds wheel -> 1000ms)
-    tick_size_ms: u64,
+    pub(crate) tick_size_ms: u64,
     /// Retention policy for the wheel
-    retention: RetentionPolicy,
+    pub(crate) retention: RetentionPolicy,
     /// The configured wheel mode
-    mode: WheelMode,
+    pub(crate) mode: WheelMode,
     /// Wheel slots maintained in a particular data layout
-    data: Data<A>,
+    pub(crate) data: Data<A>,
     /// Keeps track whether we have done a full rotation (rotation_count == num_slots)
-    rotation_count: usize,
+    pub(crate) rotation_count: usize,
     #[cfg(test)]
     pub(crate) total_ticks: usize,
     #[cfg(feature = "profiler")]
-    stats: Stats,
+    pub(crate) stats: Stats,
 }
 
 impl<A: Aggregator> Wheel<A> {
@@ -171,6 +170,10 @@ impl<A: Aggregator> Wheel<A> {
     pub fn watermark(&self) -> u64 {
         self.watermark
     }
+    /// Returns the tick size of the wheel in milliseconds
+    pub fn tick_size_ms(&self) -> u64 {
+        self.tick_size_ms
+    }
     /// Returns the current watermark of the wheel as a Duration
     pub fn now(&self) -> Duration {
         Duration::milliseconds(self.watermark as i64)
@@ -232,7 +235,7 @@ impl<A: Aggregator> Wheel<A> {
     /// - If `0` is specified, it will lower the current head.
     #[inline]
     pub fn lower_at(&self, subtrahend: usize) -> Option<A::Aggregate> {
-        self.at(subtrahend).map(|res| A::lower(*res))
+        self.at(subtrahend).map(|res| A::lower(res.clone()))
     }
 
     /// Returns ``true`` if the underlying data is a PrefixDeque
@@ -380,7 +383,7 @@ impl<A: Aggregator> Wheel<A> {
         #[cfg(feature = "profiler")]
         self.stats.bump_total();
 
-        self.total
+        self.total.clone()
     }
 
     /// Insert PartialAggregate into the head of the wheel
@@ -405,18 +408,67 @@ impl<A: Aggregator> Wheel<A> {
         self.insert_head(slot.total);
     }
 
-    /// Merge two Wheels of similar granularity
+    /// Merges the slots of another wheel of similar granularity into this wheel
+    ///
+    /// Slots are matched by the time they cover rather than by position, which allows the wheels
+    /// to be at different watermarks and to use different data layouts and retention policies.
+    ///
+    /// Returns the range of slots in `other` (counted backwards from its head) that were merged,
+    /// or `None` if the tick sizes or slot boundaries of the two wheels do not line up.
+    #[inline]
+    pub fn merge(&mut self, other: &Self) -> Option<Range<usize>> {
+        if self.tick_size_ms != other.tick_size_ms {
+            return None;
+        }
+        let distance = self.watermark.abs_diff(other.watermark);
+        if !distance.is_multiple_of(self.tick_size_ms) {
+            return None;
+        }
+        let shift = (distance / self.tick_size_ms) as usize;
+
+        // slots of `other` that are newer than this wheel cannot be merged
+        let (skip, offset) = if self.watermark >= other.watermark {
+            (0, shift)
+        } else {
+            (shift, 0)
+        };
+
+        // ordered from newest to oldest
+        let mut partials = other.data.range(..);
+        partials.reverse();
+        let start = skip.min(partials.len());
+        let partials = &partials[start..];
+
+        let merged = self.data.merge_at(offset, partials);
+
+        // slots within the current rotation have not yet been rolled up
+        for (index, partial) in partials[..merged].iter().enumerate() {
+            if offset + index < self.rotation_count {
+                combine_or_insert::<A>(&mut self.total, partial.clone());
+            }
+        }
+
+        Some(start..start + merged)
+    }
+
+    /// Combines a partial aggregate into the slot that covers the given timestamp
     ///
-    /// NOTE: must ensure wheels have been advanced to the same time
-    #[allow(clippy::useless_conversion)]
+    /// If the slot is part of the current rotation, the rotation total is updated as well.
+    ///
+    /// Returns `false` if the timestamp is not covered by a retained slot.
     #[inline]
-    pub fn merge(&mut self, other: &Self) {
-        // merge current total
-        if let Some(other_total) = other.total {
-            combine_or_insert::<A>(&mut self.total, other_total)
+    pub fn combine_at_time(&mut self, timestamp: u64, partial: A::PartialAggregate) -> bool {
+        if timestamp >= self.watermark {
+            return false;
+        }
+        let subtrahend = ((self.watermark - timestamp - 1) / self.tick_size_ms) as usize;
+
+        // slots within the current rotation have not yet been rolled up
+        if subtrahend < self.rotation_count {
+            combine_or_insert::<A>(&mut self.total, partial.clone());
         }
 
-        self.data.merge(&other.data);
+        self.data.combine_at(subtrahend, partial)
     }
 
     /// Tick the wheel by 1 slot
@@ -427,7 +479,7 @@ impl<A: Aggregator> Wheel<A> {
 
         // Possibly update the partial aggregate for the current rotation
         if let Some(curr) = self.data.get(0) {
-            combine_or_insert::<A>(&mut self.total, *curr);
+            combine_or_insert::<A>(&mut self.total, curr.clone());
         }
 
         // If the wheel is full, we clear the oldest entry
@@ -634,6 +686,13 @@ mod tests {
                 compressed_wheel.combine_range(60..85),
                 wheel.combine_range(60..85)
             );
+
+            // correct slots both within the uncompressed buffer and within compressed chunks
+            for ts in [HOUR_TICK_MS * 3, HOUR_TICK_MS * 100, wheel.watermark() - 1] {
+                assert!(wheel.combine_at_time(ts, 7));
+                assert!(compressed_wheel.combine_at_time(ts, 7));
+            }
+            assert_eq!(wheel.range(..), compressed_wheel.range(..));
         }
     }
 
@@ -661,6 +720,33 @@ mod tests {
         assert_eq!(deque.combine_range(0..2), Some(50));
     }
 
+    #[test]
+    fn combine_at_time_test() {
+        let conf = WheelConf::new(HOUR_TICK_MS, 4).with_retention_policy(RetentionPolicy::Keep);
+        let mut wheel = Wheel::<U64SumAggregator>::new(conf);
+
+        for i in 0..6 {
+            wheel.insert_slot(WheelSlot::with_total(Some(i)));
+            wheel.tick();
+        }
+        // 6 slots where the last 2 belong to the current rotation
+        assert_eq!(wheel.rotation_count(), 2);
+        assert_eq!(wheel.total(), Some(9));
+
+        // correct the oldest hour
+        assert!(wheel.combine_at_time(0, 100));
+        assert_eq!(wheel.range(..), vec![100, 1, 2, 3, 4, 5]);
+        assert_eq!(wheel.total(), Some(9));
+
+        // correct the most recent hour which is part of the current rotation
+        assert!(wheel.combine_at_time(HOUR_TICK_MS * 5, 10));
+        assert_eq!(wheel.at(0), Some(&15));
+        assert_eq!(wheel.total(), Some(19));
+
+        // timestamps at or above the watermark cannot be corrected
+        assert!(!wheel.combine_at_time(HOUR_TICK_MS * 6, 10));
+    }
+
     #[test]
     fn retention_drop_test() {
         let conf = WheelConf::new(HOUR_TICK_MS, 24).with_retention_policy(RetentionPolicy::Drop);
@@ -1,25 +1,40 @@
-use core::{cell::Cell, fmt};
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
 use uwheel_stats::Sketch;
 
 /// Stats for [AggregationWheel]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-#[derive(Clone, Default)]
+#[derive(Default)]
 pub struct Stats {
     pub combine_ops: u64,
     pub combine_range: Sketch,
     pub insert: Sketch,
-    pub total_access: Cell<u64>,
-    pub scans: Cell<u64>,
+    pub total_access: AtomicU64,
+    pub scans: AtomicU64,
 }
 impl Stats {
     pub fn add_combine_ops(&mut self, ops: u64) {
         self.combine_ops += ops;
     }
     pub fn add_scans(&self, scans: u64) {
-        self.scans.set(self.scans.get() + scans);
+        self.scans.fetch_add(scans, Ordering::Relaxed);
     }
     pub fn bump_total(&self) {
-        self.total_access.set(self.total_access.get() + 1);
+        self.total_access.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Clone for Stats {
+    fn clone(&self) -> Self {
+        Self {
+            combine_ops: self.combine_ops,
+            combine_range: self.combine_range.clone(),
+            insert: self.insert.clone(),
+            total_access: AtomicU64::new(self.total_access.load(Ordering::Relaxed)),
+            scans: AtomicU64::new(self.scans.load(Ordering::Relaxed)),
+        }
     }
 }
 
@@ -27,9 +42,9 @@ impl core::fmt::Debug for Stats {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("HAW Stats")
             .field("combine ops", &self.combine_ops)
-            .field("scans", &self.scans.get())
+            .field("scans", &self.scans.load(Ordering::Relaxed))
             .field("combine_range", &self.combine_range.percentiles())
-            .field("total access", &self.total_access.get())
+            .field("total access", &self.total_access.load(Ordering::Relaxed))
             .finish()
     }
 }
@@ -0,0 +1,524 @@
+use core::{cmp, ops::Range};
+use time::{Date, Month, OffsetDateTime, UtcOffset};
+
+use super::{hierarchical::to_utc_offset, plan::CalendarAggregation};
+use crate::aggregator::Aggregator;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+/// Default number of retained calendar months
+pub const CALENDAR_MONTHS: usize = 120;
+/// Default number of retained calendar quarters
+pub const CALENDAR_QUARTERS: usize = 40;
+
+/// Configuration for calendar-aligned rollups (months and quarters)
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{CalendarConf, HawConf};
+///
+/// // Retain 24 months and 8 quarters
+/// let haw_conf = HawConf::default().with_calendar(CalendarConf::new(24, 8));
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Copy, Debug)]
+pub struct CalendarConf {
+    /// Number of months to retain
+    pub months: usize,
+    /// Number of quarters to retain
+    pub quarters: usize,
+}
+
+impl Default for CalendarConf {
+    fn default() -> Self {
+        Self::new(CALENDAR_MONTHS, CALENDAR_QUARTERS)
+    }
+}
+
+impl CalendarConf {
+    /// Creates a new calendar configuration with the given month and quarter retention
+    ///
+    /// # Panics
+    ///
+    /// Panics if less than 3 months are retained as quarters are rolled up from months.
+    pub fn new(months: usize, quarters: usize) -> Self {
+        assert!(months >= 3, "Must retain at least 3 months");
+        Self { months, quarters }
+    }
+}
+
+/// Calendar units with variable length
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalendarUnit {
+    /// A calendar month (28-31 days)
+    Month,
+    /// A calendar quarter (Jan-Mar, Apr-Jun, Jul-Sep, Oct-Dec)
+    Quarter,
+}
+
+impl CalendarUnit {
+    #[inline]
+    const fn months(&self) -> i32 {
+        match self {
+            CalendarUnit::Month => 1,
+            CalendarUnit::Quarter => 3,
+        }
+    }
+}
+
+/// Returns the absolute month number (year * 12 + month) of the given unix timestamp in milliseconds
+#[inline]
+pub(crate) fn month_of(ms: u64, offset: UtcOffset) -> i32 {
+    let date = OffsetDateTime::from_unix_timestamp_nanos(ms as i128 * 1_000_000)
+        .unwrap()
+        .to_offset(offset);
+    date.year() * 12 + (date.month() as i32 - 1)
+}
+
+/// Returns the start of the given absolute month number as a unix timestamp in milliseconds
+#[inline]
+pub(crate) fn month_start_ms(month: i32, offset: UtcOffset) -> u64 {
+    let m = Month::try_from((month.rem_euclid(12) + 1) as u8).unwrap();
+    let date = Date::from_calendar_date(month.div_euclid(12), m, 1).unwrap();
+    (date.midnight().assume_offset(offset).unix_timestamp_nanos() / 1_000_000) as u64
+}
+
+/// Returns the absolute month number if the timestamp is aligned to the start of a month
+#[inline]
+pub(crate) fn aligned_month(ms: u64, offset: UtcOffset) -> Option<i32> {
+    let month = month_of(ms, offset);
+    (month_start_ms(month, offset) == ms).then_some(month)
+}
+
+/// Returns the start of the unit following the given aligned timestamp
+///
+/// Returns `None` if the timestamp is not aligned to the given calendar unit.
+#[inline]
+pub(crate) fn next_unit_start(ms: u64, unit: CalendarUnit, offset: UtcOffset) -> Option<u64> {
+    aligned_month(ms, offset)
+        .filter(|month| month.rem_euclid(unit.months()) == 0)
+        .map(|month| month_start_ms(month + unit.months(), offset))
+}
+
+// Returns the indices of the units in `other` that are also retained by `own`
+#[inline]
+fn overlap(own_oldest: i32, own_len: usize, other_oldest: i32, other_len: usize) -> Range<usize> {
+    let start = cmp::max(own_oldest - other_oldest, 0) as usize;
+    let end = (own_oldest + own_len as i32 - other_oldest).clamp(0, other_len as i32) as usize;
+    start.min(end)..end
+}
+
+/// Calendar-aligned rollups of partial aggregates
+///
+/// Partial aggregates are accumulated for the ongoing month which is frozen once the wheel
+/// crosses the next month boundary. Quarters are rolled up from frozen months.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
+pub struct CalendarWheel<A: Aggregator> {
+    pub(crate) conf: CalendarConf,
+    /// Fixed UTC offset in seconds that defines the month boundaries
+    pub(crate) utc_offset: i32,
+    /// Absolute month number of the ongoing month
+    pub(crate) current_month: i32,
+    /// Start of the next month in milliseconds
+    pub(crate) next_month_start: u64,
+    /// Partial aggregate of the ongoing month
+    pub(crate) current: A::PartialAggregate,
+    /// Absolute month number of the oldest retained month
+    pub(crate) oldest_month: i32,
+    /// Frozen months ordered from oldest to newest
+    pub(crate) months: VecDeque<A::PartialAggregate>,
+    /// Absolute quarter number of the oldest retained quarter
+    pub(crate) oldest_quarter: i32,
+    /// Frozen quarters ordered from oldest to newest
+    pub(crate) quarters: VecDeque<A::PartialAggregate>,
+}
+
+impl<A: Aggregator> CalendarWheel<A> {
+    /// Creates a new calendar wheel starting from the given watermark
+    ///
+    /// Month boundaries follow the given UTC offset in seconds.
+    pub fn new(watermark: u64, conf: CalendarConf, utc_offset: i32) -> Self {
+        let offset = to_utc_offset(utc_offset);
+        let current_month = month_of(watermark, offset);
+        Self {
+            conf,
+            utc_offset,
+            current_month,
+            next_month_start: month_start_ms(current_month + 1, offset),
+            current: A::IDENTITY,
+            oldest_month: current_month,
+            months: VecDeque::new(),
+            oldest_quarter: current_month.div_euclid(3),
+            quarters: VecDeque::new(),
+        }
+    }
+
+    /// Combines the partial aggregate of a tick ending at `watermark` into the ongoing month
+    #[inline]
+    pub fn tick(&mut self, partial: A::PartialAggregate, watermark: u64) {
+        self.current = A::combine(core::mem::take(&mut self.current), partial);
+        if watermark >= self.next_month_start {
+            self.freeze_month();
+        }
+    }
+
+    fn freeze_month(&mut self) {
+        if self.months.is_empty() {
+            self.oldest_month = self.current_month;
+        }
+        self.months
+            .push_back(core::mem::replace(&mut self.current, A::IDENTITY));
+
+        // roll up the quarter once its last month is frozen
+        if self.current_month.rem_euclid(3) == 2 {
+            let quarter = self.current_month.div_euclid(3);
+            let quarter_start = cmp::max(quarter * 3, self.oldest_month);
+            let partial = (quarter_start..=self.current_month)
+                .filter_map(|month| self.month(month))
+                .fold(A::IDENTITY, A::combine);
+
+            if self.quarters.is_empty() {
+                self.oldest_quarter = quarter;
+            }
+            self.quarters.push_back(partial);
+            if self.quarters.len() > self.conf.quarters {
+                self.quarters.pop_front();
+                self.oldest_quarter += 1;
+            }
+        }
+
+        if self.months.len() > self.conf.months {
+            self.months.pop_front();
+            self.oldest_month += 1;
+        }
+
+        self.current_month += 1;
+        self.next_month_start = month_start_ms(self.current_month + 1, self.offset());
+    }
+
+    #[inline]
+    fn offset(&self) -> UtcOffset {
+        to_utc_offset(self.utc_offset)
+    }
+
+    #[inline]
+    fn month(&self, month: i32) -> Option<A::PartialAggregate> {
+        let idx = usize::try_from(month - self.oldest_month).ok()?;
+        self.months.get(idx).cloned()
+    }
+
+    #[inline]
+    fn quarter(&self, quarter: i32) -> Option<A::PartialAggregate> {
+        let idx = usize::try_from(quarter - self.oldest_quarter).ok()?;
+        self.quarters.get(idx).cloned()
+    }
+
+    /// Walks the frozen months and quarters covering the aligned range `[start_month, end_month)`
+    ///
+    /// Returns `false` if some part of the range is not available.
+    fn walk(
+        &self,
+        start_month: i32,
+        end_month: i32,
+        mut f: impl FnMut(A::PartialAggregate),
+    ) -> bool {
+        let mut month = start_month;
+        while month < end_month {
+            // prefer whole quarters
+            if month.rem_euclid(3) == 0 && month + 3 <= end_month {
+                if let Some(partial) = self.quarter(month.div_euclid(3)) {
+                    f(partial);
+                    month += 3;
+                    continue;
+                }
+            }
+            match self.month(month) {
+                Some(partial) => f(partial),
+                None => return false,
+            }
+            month += 1;
+        }
+        true
+    }
+
+    /// Returns the number of calendar aggregates required to answer the range `[start, end)`
+    ///
+    /// Returns `None` if the range is not aligned to month boundaries or if it is not covered by frozen months.
+    pub fn plan(&self, start: u64, end: u64) -> Option<CalendarAggregation> {
+        let start_month = aligned_month(start, self.offset())?;
+        let end_month = aligned_month(end, self.offset())?;
+        if start_month >= end_month || end_month > self.current_month {
+            return None;
+        }
+        let mut cost = 0;
+        self.walk(start_month, end_month, |_| cost += 1)
+            .then_some(CalendarAggregation {
+                start_month,
+                end_month,
+                cost,
+            })
+    }
+
+    /// Executes a calendar aggregation plan
+    pub fn aggregate(&self, plan: &CalendarAggregation) -> Option<A::PartialAggregate> {
+        self.combine_range(plan.start_month, plan.end_month)
+    }
+
+    /// Combines frozen months and quarters within the aligned range `[start_month, end_month)`
+    pub fn combine_range(&self, start_month: i32, end_month: i32) -> Option<A::PartialAggregate> {
+        let mut result = A::IDENTITY;
+        self.walk(start_month, end_month, |partial| {
+            result = A::combine(core::mem::take(&mut result), partial)
+        })
+        .then_some(result)
+    }
+
+    /// Applies a late partial aggregate to the month (and quarter) covering the given timestamp
+    ///
+    /// The month and quarter are corrected independently since quarters may be retained longer
+    /// than months. Returns `false` if neither of them is retained.
+    pub fn apply_late(&mut self, timestamp: u64, partial: A::PartialAggregate) -> bool {
+        let month = month_of(timestamp, self.offset());
+        if month == self.current_month {
+            self.current = A::combine(core::mem::take(&mut self.current), partial);
+            return true;
+        }
+
+        let month_idx = usize::try_from(month - self.oldest_month)
+            .ok()
+            .filter(|idx| *idx < self.months.len());
+        let quarter_idx = usize::try_from(month.div_euclid(3) - self.oldest_quarter)
+            .ok()
+            .filter(|idx| *idx < self.quarters.len());
+
+        if let Some(idx) = month_idx {
+            self.months[idx] = A::combine(core::mem::take(&mut self.months[idx]), partial.clone());
+        }
+        if let Some(idx) = quarter_idx {
+            self.quarters[idx] = A::combine(core::mem::take(&mut self.quarters[idx]), partial);
+        }
+        month_idx.is_some() || quarter_idx.is_some()
+    }
+
+    /// Merges another calendar wheel with the same watermark into this wheel
+    ///
+    /// Only months and quarters retained by both wheels are merged. Returns the ranges of frozen
+    /// months and quarters in `other` (ordered from oldest to newest) that were merged, or `None`
+    /// if the wheels use different month boundaries.
+    pub fn merge(&mut self, other: &Self) -> Option<(Range<usize>, Range<usize>)> {
+        if self.utc_offset != other.utc_offset || self.current_month != other.current_month {
+            return None;
+        }
+        self.current = A::combine(core::mem::take(&mut self.current), other.current.clone());
+
+        let months = overlap(
+            self.oldest_month,
+            self.months.len(),
+            other.oldest_month,
+            other.months.len(),
+        );
+        for idx in months.clone() {
+            let own = (other.oldest_month - self.oldest_month) as usize + idx;
+            self.months[own] = A::combine(
+                core::mem::take(&mut self.months[own]),
+                other.months[idx].clone(),
+            );
+        }
+
+        let quarters = overlap(
+            self.oldest_quarter,
+            self.quarters.len(),
+            other.oldest_quarter,
+            other.quarters.len(),
+        );
+        for idx in quarters.clone() {
+            let own = (other.oldest_quarter - self.oldest_quarter) as usize + idx;
+            self.quarters[own] = A::combine(
+                core::mem::take(&mut self.quarters[own]),
+                other.quarters[idx].clone(),
+            );
+        }
+        Some((months, quarters))
+    }
+
+    /// Returns the time span `[start, end)` in milliseconds of the given frozen months
+    pub(crate) fn months_span(&self, months: &Range<usize>) -> (u64, u64) {
+        let offset = self.offset();
+        (
+            month_start_ms(self.oldest_month + months.start as i32, offset),
+            month_start_ms(self.oldest_month + months.end as i32, offset),
+        )
+    }
+
+    /// Returns the time span `[start, end)` in milliseconds of the given frozen quarters
+    pub(crate) fn quarters_span(&self, quarters: &Range<usize>) -> (u64, u64) {
+        let offset = self.offset();
+        (
+            month_start_ms((self.oldest_quarter + quarters.start as i32) * 3, offset),
+            month_start_ms((self.oldest_quarter + quarters.end as i32) * 3, offset),
+        )
+    }
+
+    /// Returns the number of retained quarters
+    pub(crate) fn quarters_len(&self) -> usize {
+        self.quarters.len()
+    }
+
+    /// Clears all frozen months and quarters
+    pub fn clear(&mut self) {
+        self.current = A::IDENTITY;
+        self.months.clear();
+        self.quarters.clear();
+    }
+
+    /// Returns the number of frozen months
+    pub fn len(&self) -> usize {
+        self.months.len()
+    }
+
+    /// Returns `true` if no months have been frozen yet
+    pub fn is_empty(&self) -> bool {
+        self.months.is_empty()
+    }
+
+    /// Returns memory used in bytes
+    pub fn size_bytes(&self) -> usize {
+        (1 + self.months.len() + self.quarters.len()) * core::mem::size_of::<A::PartialAggregate>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::sum::U64SumAggregator;
+    use time::macros::datetime;
+
+    fn ms(date: OffsetDateTime) -> u64 {
+        (date.unix_timestamp_nanos() / 1_000_000) as u64
+    }
+
+    #[test]
+    fn month_boundaries_test() {
+        let feb = month_of(ms(datetime!(2024-02-15 12:00 UTC)), UtcOffset::UTC);
+        assert_eq!(
+            month_start_ms(feb, UtcOffset::UTC),
+            ms(datetime!(2024-02-01 00:00 UTC))
+        );
+        assert_eq!(
+            month_start_ms(feb + 1, UtcOffset::UTC),
+            ms(datetime!(2024-03-01 00:00 UTC))
+        );
+        assert_eq!(
+            month_start_ms(feb + 11, UtcOffset::UTC),
+            ms(datetime!(2025-01-01 00:00 UTC))
+        );
+
+        assert!(aligned_month(ms(datetime!(2024-03-01 00:00 UTC)), UtcOffset::UTC).is_some());
+        assert!(aligned_month(ms(datetime!(2024-03-02 00:00 UTC)), UtcOffset::UTC).is_none());
+
+        let q3 = ms(datetime!(2024-07-01 00:00 UTC));
+        assert_eq!(
+            next_unit_start(q3, CalendarUnit::Quarter, UtcOffset::UTC),
+            Some(ms(datetime!(2024-10-01 00:00 UTC)))
+        );
+        assert_eq!(
+            next_unit_start(
+                ms(datetime!(2024-08-01 00:00 UTC)),
+                CalendarUnit::Quarter,
+                UtcOffset::UTC
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn month_boundaries_offset_test() {
+        let offset = UtcOffset::from_hms(-5, 0, 0).unwrap();
+        // 2024-03-01 02:00 UTC is still February in UTC-05:00
+        let ts = ms(datetime!(2024-03-01 02:00 UTC));
+        let feb = month_of(ts, offset);
+        assert_eq!(
+            feb,
+            month_of(ms(datetime!(2024-02-15 00:00 UTC)), UtcOffset::UTC)
+        );
+        assert_eq!(
+            month_start_ms(feb + 1, offset),
+            ms(datetime!(2024-03-01 00:00 -5))
+        );
+        assert_eq!(
+            aligned_month(ms(datetime!(2024-03-01 00:00 -5)), offset),
+            Some(feb + 1)
+        );
+        assert_eq!(
+            aligned_month(ms(datetime!(2024-03-01 00:00 UTC)), offset),
+            None
+        );
+    }
+
+    #[test]
+    fn calendar_rollup_test() {
+        let start = ms(datetime!(2024-01-01 00:00 UTC));
+        let mut wheel: CalendarWheel<U64SumAggregator> =
+            CalendarWheel::new(start, CalendarConf::new(4, 4), 0);
+
+        // tick one day at a time for the whole year with 1 per day
+        let day = 86_400_000;
+        let mut watermark = start;
+        while watermark < ms(datetime!(2025-01-01 00:00 UTC)) {
+            watermark += day;
+            wheel.tick(1, watermark);
+        }
+
+        // only the last 4 months are retained
+        assert_eq!(wheel.len(), 4);
+        let sep = month_of(ms(datetime!(2024-09-01 00:00 UTC)), UtcOffset::UTC);
+        assert_eq!(wheel.combine_range(sep, sep + 1), Some(30));
+        assert_eq!(wheel.combine_range(sep, sep + 4), Some(30 + 31 + 30 + 31));
+        assert_eq!(wheel.combine_range(sep - 1, sep), None);
+
+        // quarters remain available beyond the retained months
+        let q1 = month_of(start, UtcOffset::UTC);
+        assert_eq!(wheel.combine_range(q1, q1 + 3), Some(31 + 29 + 31));
+        assert_eq!(wheel.combine_range(q1, q1 + 12), Some(366));
+        assert_eq!(wheel.combine_range(q1, q1 + 1), None);
+    }
+
+    #[test]
+    fn calendar_apply_late_test() {
+        let start = ms(datetime!(2024-01-01 00:00 UTC));
+        let mut wheel: CalendarWheel<U64SumAggregator> =
+            CalendarWheel::new(start, CalendarConf::new(3, 4), 0);
+
+        // freeze January to June with 1 per month
+        let mut watermark = start;
+        for _ in 0..6 {
+            watermark = wheel.next_month_start;
+            wheel.tick(1, watermark);
+        }
+        let q1 = month_of(start, UtcOffset::UTC);
+        assert_eq!(wheel.combine_range(q1, q1 + 6), Some(6));
+
+        // January is evicted but its quarter is still retained
+        assert_eq!(wheel.combine_range(q1, q1 + 1), None);
+        assert!(wheel.apply_late(ms(datetime!(2024-01-15 00:00 UTC)), 10));
+        assert_eq!(wheel.combine_range(q1, q1 + 3), Some(13));
+
+        // May is retained both as a month and as part of its quarter
+        assert!(wheel.apply_late(ms(datetime!(2024-05-15 00:00 UTC)), 100));
+        assert_eq!(wheel.combine_range(q1 + 4, q1 + 5), Some(101));
+        assert_eq!(wheel.combine_range(q1 + 3, q1 + 6), Some(103));
+
+        // the ongoing month
+        assert!(wheel.apply_late(watermark, 1000));
+        assert_eq!(wheel.current, 1000);
+
+        // neither the month nor its quarter is retained
+        assert!(!wheel.apply_late(ms(datetime!(2023-12-15 00:00 UTC)), 1));
+        assert_eq!(wheel.combine_range(q1, q1 + 6), Some(116));
+    }
+}
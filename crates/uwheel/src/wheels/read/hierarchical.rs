@@ -1,8 +1,9 @@
 use core::{
     cmp,
     fmt::{self, Display},
+    ops::Range,
 };
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
 
 use super::{
     super::write::WriterWheel,
@@ -11,17 +12,20 @@ use super::{
         maybe::MaybeWheel,
         Wheel,
     },
-    plan::{ExecutionPlan, WheelAggregation, WheelRanges},
+    calendar::{next_unit_start, CalendarConf, CalendarUnit, CalendarWheel},
+    merge::{HawLevel, MergeReport, MergeSpan},
+    plan::{CalendarAggregation, ExecutionPlan, WheelAggregation, WheelRanges},
+    query::{QueryError, QueryResult},
 };
 
 use crate::{
     aggregator::Aggregator,
-    delta::DeltaState,
+    delta::{CheckpointError, DeltaState},
     wheels::read::{
         aggregation::combine_or_insert,
         plan::{CombinedAggregation, WheelAggregations},
     },
-    window::{WindowAggregate, WindowManager},
+    window::{state::SharedPairs, WindowAggregate, WindowAggregateKind, WindowId, WindowManager},
     Duration,
     Window,
 };
@@ -37,10 +41,13 @@ use uwheel_stats::profile_scope;
 crate::cfg_timer! {
     #[cfg(not(feature = "std"))]
     use alloc::{boxed::Box, rc::Rc};
-    use crate::wheels::timer::{RawTimerWheel, TimerWheel, TimerError, TimerAction};
+    use crate::wheels::timer::{RawTimerWheel, TimerWheel, TimerError, TimerAction, TimerFn};
 }
 use super::aggregation::conf::WheelConf;
 
+#[cfg(feature = "snapshot")]
+use crate::wheels::snapshot::{self, Snapshot, SnapshotError, SnapshotKind};
+
 /// Default Second tick represented in milliseconds
 pub const SECOND_TICK_MS: u64 = time::Duration::SECOND.whole_milliseconds() as u64;
 /// Default Minute tick represented in milliseconds
@@ -60,6 +67,8 @@ pub const YEAR_TICK_MS: u64 = WEEK_TICK_MS * 52;
 pub struct HawConf {
     /// Initial watermark of the wheel
     pub watermark: u64,
+    /// Config for the optional milliseconds wheel
+    pub milliseconds: Option<WheelConf>,
     /// Config for the seconds wheel
     pub seconds: WheelConf,
     /// Config for the minutes wheel
@@ -72,24 +81,34 @@ pub struct HawConf {
     pub weeks: WheelConf,
     /// Config for the years wheel
     pub years: WheelConf,
+    /// Config for optional calendar-aligned months and quarters
+    pub calendar: Option<CalendarConf>,
     /// Optimizer configuration
     pub optimizer: Optimizer,
     /// Flag indicating whether to maintain deltas within the wheel
     pub generate_deltas: bool,
+    /// Duration for which late entries may still update emitted windows
+    pub allowed_lateness: Duration,
+    /// Fixed UTC offset in seconds (east of UTC) that defines local day, week and month boundaries
+    pub utc_offset: i32,
 }
 
 impl Default for HawConf {
     fn default() -> Self {
         Self {
             watermark: 0,
+            milliseconds: None,
             seconds: WheelConf::new(SECOND_TICK_MS, SECONDS),
             minutes: WheelConf::new(MINUTE_TICK_MS, MINUTES),
             hours: WheelConf::new(HOUR_TICK_MS, HOURS),
             days: WheelConf::new(DAY_TICK_MS, DAYS),
             weeks: WheelConf::new(WEEK_TICK_MS, WEEKS),
             years: WheelConf::new(YEAR_TICK_MS, YEARS),
+            calendar: None,
             optimizer: Default::default(),
             generate_deltas: false,
+            allowed_lateness: Duration::ZERO,
+            utc_offset: 0,
         }
     }
 }
@@ -97,6 +116,9 @@ impl Default for HawConf {
 impl HawConf {
     /// Configures the initial watermark
     pub fn with_watermark(mut self, watermark: u64) -> Self {
+        if let Some(milliseconds) = self.milliseconds.as_mut() {
+            milliseconds.set_watermark(watermark);
+        }
         self.seconds.set_watermark(watermark);
         self.minutes.set_watermark(watermark);
         self.hours.set_watermark(watermark);
@@ -114,6 +136,9 @@ impl HawConf {
     /// If using ``WheelMode::Index`` in combination with explicit SIMD support,
     /// then make sure to convert wheels to support SIMD (see ``Haw::to_simd_wheels``).
     pub fn with_mode(mut self, mode: WheelMode) -> Self {
+        if let Some(milliseconds) = self.milliseconds.as_mut() {
+            milliseconds.set_mode(mode);
+        }
         self.seconds.set_mode(mode);
         self.minutes.set_mode(mode);
         self.hours.set_mode(mode);
@@ -126,6 +151,9 @@ impl HawConf {
 
     /// Configures all wheels with prefix-sum enabled
     pub fn with_prefix_sum(mut self) -> Self {
+        if let Some(milliseconds) = self.milliseconds.as_mut() {
+            milliseconds.set_data_layout(DataLayout::Prefix);
+        }
         self.seconds.set_data_layout(DataLayout::Prefix);
         self.minutes.set_data_layout(DataLayout::Prefix);
         self.hours.set_data_layout(DataLayout::Prefix);
@@ -138,6 +166,9 @@ impl HawConf {
 
     /// Configures a global retention policy across all granularities
     pub fn with_retention_policy(mut self, policy: RetentionPolicy) -> Self {
+        if let Some(milliseconds) = self.milliseconds.as_mut() {
+            milliseconds.set_retention_policy(policy);
+        }
         self.seconds.set_retention_policy(policy);
         self.minutes.set_retention_policy(policy);
         self.hours.set_retention_policy(policy);
@@ -148,6 +179,94 @@ impl HawConf {
         self
     }
 
+    /// Configures a milliseconds wheel below the seconds wheel using the given tick size
+    ///
+    /// The tick size defines the finest resolution of the wheel (e.g., 10ms or 100ms buckets)
+    /// and must evenly divide a second. Once configured, the wheel advances in ticks of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tick size is zero or does not evenly divide a second.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{HawConf, NumericalDuration};
+    ///
+    /// // Maintain aggregates in 100ms buckets
+    /// let haw_conf = HawConf::default().with_milliseconds(100.milliseconds());
+    /// assert_eq!(haw_conf.tick_size_ms(), 100);
+    /// ```
+    pub fn with_milliseconds(mut self, tick_size: Duration) -> Self {
+        let tick_size_ms = tick_size.whole_milliseconds() as u64;
+        assert!(
+            tick_size_ms > 0
+                && tick_size_ms < SECOND_TICK_MS
+                && SECOND_TICK_MS.is_multiple_of(tick_size_ms),
+            "Milliseconds tick size must evenly divide a second"
+        );
+        let slots = (SECOND_TICK_MS / tick_size_ms) as usize;
+        self.milliseconds =
+            Some(WheelConf::new(tick_size_ms, slots).with_watermark(self.watermark));
+        self
+    }
+
+    /// Configures calendar-aligned rollups of months and quarters
+    ///
+    /// Weeks and years in the wheel hierarchy are not aligned with the calendar. With calendar rollups enabled,
+    /// ranges aligned to month boundaries (e.g., March or Q3) are answered exactly and
+    /// [Haw::group_by_calendar] may step by calendar months or quarters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{CalendarConf, HawConf};
+    ///
+    /// let haw_conf = HawConf::default().with_calendar(CalendarConf::default());
+    /// ```
+    pub fn with_calendar(mut self, conf: CalendarConf) -> Self {
+        self.calendar = Some(conf);
+        self
+    }
+
+    /// Configures a fixed UTC offset in seconds (east of UTC) for the wheel
+    ///
+    /// Query ranges and [Haw::group_by] buckets are interpreted in local time using the offset. For instance,
+    /// a range between two local midnights is answered by the days wheel. Calendar months (see [Self::with_calendar])
+    /// follow the offset as well.
+    ///
+    /// Note that the wheel hierarchy rolls over relative to its initial watermark and thus the watermark should be
+    /// aligned to local time (e.g., local midnight) for day and week boundaries to follow the local time zone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is outside the range of ±25:59:59.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::HawConf;
+    ///
+    /// // UTC+02:00
+    /// let haw_conf = HawConf::default().with_utc_offset(2 * 3600);
+    /// ```
+    pub fn with_utc_offset(mut self, seconds: i32) -> Self {
+        assert!(
+            UtcOffset::from_whole_seconds(seconds).is_ok(),
+            "UTC offset must be within ±25:59:59"
+        );
+        self.utc_offset = seconds;
+        self
+    }
+
+    /// Returns the tick size of the lowest granularity in milliseconds
+    #[inline]
+    pub fn tick_size_ms(&self) -> u64 {
+        self.milliseconds
+            .map(|conf| conf.tick_size_ms)
+            .unwrap_or(SECOND_TICK_MS)
+    }
+
     /// Configures the seconds granularity
     pub fn with_seconds(mut self, seconds: WheelConf) -> Self {
         self.seconds = seconds;
@@ -180,10 +299,33 @@ impl HawConf {
     }
 
     /// Configures the wheel to generate and maintain deltas
+    ///
+    /// Deltas only capture advancements of the wheel. Corrections of already-frozen slots cannot be expressed
+    /// as deltas, so a wheel generating deltas must not be configured with an allowed lateness
+    /// (see [Self::with_allowed_lateness]) or corrected through [Haw::apply_late].
     pub fn with_deltas(mut self) -> Self {
         self.generate_deltas = true;
         self
     }
+
+    /// Configures the allowed lateness of the wheel
+    ///
+    /// Entries that arrive at most `lateness` behind the low watermark are combined into the already-frozen slots
+    /// and cause corrections of installed tumbling and sliding windows to be emitted on the next advance.
+    ///
+    /// Cannot be combined with [Self::with_deltas] as corrections are not reflected in generated deltas.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{HawConf, NumericalDuration};
+    ///
+    /// let haw_conf = HawConf::default().with_allowed_lateness(10.seconds());
+    /// ```
+    pub fn with_allowed_lateness(mut self, lateness: Duration) -> Self {
+        self.allowed_lateness = lateness;
+        self
+    }
 }
 
 /// Default capacity of second slots
@@ -200,7 +342,7 @@ pub const WEEKS: usize = 52;
 pub const YEARS: usize = 10;
 
 /// A type containing error variants that
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RangeError {
     /// Range start is an invalid unix timestamp
     InvalidStart {
@@ -244,11 +386,11 @@ pub struct WheelRange {
 impl WheelRange {
     /// Creates a WheelRange using a start and end timestamp as unix timestamps in milliseconds
     pub fn new(start_ms: u64, end_ms: u64) -> Result<Self, RangeError> {
-        // NOTE: internally we have to convert it to seconds for `OffsetDateTime`
-        let start = OffsetDateTime::from_unix_timestamp(start_ms as i64 / 1000)
+        // NOTE: convert to nanoseconds to keep millisecond precision in `OffsetDateTime`
+        let start = OffsetDateTime::from_unix_timestamp_nanos(start_ms as i128 * 1_000_000)
             .map_err(|_| RangeError::InvalidStart { start_ms })?;
 
-        let end = OffsetDateTime::from_unix_timestamp(end_ms as i64 / 1000)
+        let end = OffsetDateTime::from_unix_timestamp_nanos(end_ms as i128 * 1_000_000)
             .map_err(|_| RangeError::InvalidEnd { end_ms })?;
 
         Ok(Self { start, end })
@@ -265,16 +407,29 @@ impl WheelRange {
     pub fn from(start: OffsetDateTime, end: OffsetDateTime) -> Self {
         Self { start, end }
     }
+    /// Returns the start of the range as a unix timestamp in milliseconds
+    #[inline]
+    pub fn start_ms(&self) -> u64 {
+        to_unix_ms(self.start)
+    }
+    /// Returns the end of the range as a unix timestamp in milliseconds
+    #[inline]
+    pub fn end_ms(&self) -> u64 {
+        to_unix_ms(self.end)
+    }
 }
 
 impl WheelRange {
     /// Returns the lowest granularity of the Wheel range
     pub(crate) fn lowest_granularity(&self) -> Granularity {
+        let is_millis = self.start.millisecond() != 0 || self.end.millisecond() != 0;
         let is_seconds = self.start.second() != 0 || self.end.second() != 0;
         let is_minutes = self.start.minute() != 0 || self.end.minute() != 0;
         let is_hours = self.start.hour() != 0 || self.end.hour() != 0;
         let is_days = self.start.day() != 0 || self.end.day() != 0;
-        if is_seconds {
+        if is_millis {
+            Granularity::Millisecond
+        } else if is_seconds {
             Granularity::Second
         } else if is_minutes {
             Granularity::Minute
@@ -290,6 +445,7 @@ impl WheelRange {
     pub fn scan_estimation(&self) -> i64 {
         let dur = self.duration();
         match self.lowest_granularity() {
+            Granularity::Millisecond => dur.whole_milliseconds() as i64,
             Granularity::Second => dur.whole_seconds(),
             Granularity::Minute => dur.whole_minutes(),
             Granularity::Hour => dur.whole_hours(),
@@ -300,13 +456,67 @@ impl WheelRange {
     /// Returns a Duration object for the range
     #[inline]
     pub fn duration(&self) -> Duration {
-        Duration::seconds((self.end - self.start).whole_seconds())
+        Duration::milliseconds((self.end - self.start).whole_milliseconds() as i64)
+    }
+
+    /// Returns the range converted into the given UTC offset
+    #[inline]
+    pub(crate) fn to_offset(self, offset: UtcOffset) -> Self {
+        Self {
+            start: self.start.to_offset(offset),
+            end: self.end.to_offset(offset),
+        }
+    }
+
+    /// Returns the range with sub-second precision truncated
+    #[inline]
+    pub(crate) fn truncate_millis(self) -> Self {
+        let truncate =
+            |date: OffsetDateTime| date - time::Duration::milliseconds(date.millisecond() as i64);
+        Self {
+            start: truncate(self.start),
+            end: truncate(self.end),
+        }
     }
 }
 
+/// Converts a UTC offset in seconds into a [UtcOffset]
+#[inline]
+pub(crate) fn to_utc_offset(seconds: i32) -> UtcOffset {
+    UtcOffset::from_whole_seconds(seconds).unwrap_or(UtcOffset::UTC)
+}
+
+// Rounds a timestamp up to the next boundary of the granularity in local time
+#[inline]
+fn round_up(ts: u64, granularity: u64, offset_ms: i64) -> u64 {
+    let local = ts as i64 + offset_ms;
+    let rounded = local.div_euclid(granularity as i64) * granularity as i64;
+    let rounded = if rounded < local {
+        rounded + granularity as i64
+    } else {
+        rounded
+    };
+    (rounded - offset_ms).max(0) as u64
+}
+
+// Rounds a timestamp down to the previous boundary of the granularity in local time
+#[inline]
+fn round_down(ts: u64, granularity: u64, offset_ms: i64) -> u64 {
+    let local = ts as i64 + offset_ms;
+    let rounded = local.div_euclid(granularity as i64) * granularity as i64;
+    (rounded - offset_ms).max(0) as u64
+}
+
+/// Converts a [OffsetDateTime] into a unix timestamp in milliseconds
+#[inline]
+pub(crate) fn to_unix_ms(date: OffsetDateTime) -> u64 {
+    (date.unix_timestamp_nanos() / 1_000_000) as u64
+}
+
 #[derive(Debug, Copy, PartialEq, Eq, Clone)]
 #[repr(usize)]
 pub(crate) enum Granularity {
+    Millisecond,
     Second,
     Minute,
     Hour,
@@ -320,7 +530,7 @@ pub const DEFAULT_SIMD_THRESHOLD: usize = 15000;
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone, Copy)]
 pub struct Heuristics {
-    simd_threshold: usize,
+    pub(crate) simd_threshold: usize,
 }
 
 impl Default for Heuristics {
@@ -336,9 +546,9 @@ impl Default for Heuristics {
 #[derive(Default, Debug, Clone, Copy)]
 pub struct Optimizer {
     /// Defines whether the optimizer should use framework provided hints
-    use_hints: bool,
+    pub(crate) use_hints: bool,
     /// A set of heuristics that the optimizer takes into context
-    heuristics: Heuristics,
+    pub(crate) heuristics: Heuristics,
 }
 impl Optimizer {
     /// Sets the use hints flag
@@ -396,25 +606,31 @@ where
     A: Aggregator,
 {
     /// The current low watermark for this wheel
-    watermark: u64,
+    pub(crate) watermark: u64,
+    /// An optional milliseconds wheel which may or may not be initialized
+    pub(crate) milliseconds_wheel: Option<MaybeWheel<A>>,
     /// A seconds wheel which may or may not be initialized
-    seconds_wheel: MaybeWheel<A>,
+    pub(crate) seconds_wheel: MaybeWheel<A>,
     /// A minutes wheel which may or may not be initialized
-    minutes_wheel: MaybeWheel<A>,
+    pub(crate) minutes_wheel: MaybeWheel<A>,
     /// A hours wheel which may or may not be initialized
-    hours_wheel: MaybeWheel<A>,
+    pub(crate) hours_wheel: MaybeWheel<A>,
     /// A days wheel which may or may not be initialized
-    days_wheel: MaybeWheel<A>,
+    pub(crate) days_wheel: MaybeWheel<A>,
     /// A weeks  wheel which may or may not be initialized
-    weeks_wheel: MaybeWheel<A>,
+    pub(crate) weeks_wheel: MaybeWheel<A>,
     /// A years  wheel which may or may not be initialized
-    years_wheel: MaybeWheel<A>,
-    /// An optional window manager that manages windows if configured
-    window_manager: Option<WindowManager<A>>,
+    pub(crate) years_wheel: MaybeWheel<A>,
+    /// Optional calendar-aligned months and quarters
+    pub(crate) calendar_wheel: Option<CalendarWheel<A>>,
+    /// Window managers of the installed windows
+    pub(crate) window_managers: Vec<WindowManager<A>>,
+    /// Identifier assigned to the next installed window
+    pub(crate) next_window_id: u64,
     /// Defines the configuration of the Hierarchical Aggregate Wheel
-    conf: HawConf,
+    pub(crate) conf: HawConf,
     /// Maintains deltas if the wheel has been configured to do so
-    delta: DeltaState<A::PartialAggregate>,
+    pub(crate) delta: DeltaState<A::PartialAggregate>,
     #[cfg(feature = "timer")]
     #[cfg_attr(feature = "serde", serde(skip))]
     /// A hierarchical timing wheel for scheduling user-defined functions
@@ -441,8 +657,6 @@ where
     const YEAR_AS_SECS: u64 = Self::WEEK_AS_SECS * WEEKS as u64;
     const CYCLE_LENGTH_SECS: u64 = Self::CYCLE_LENGTH.whole_seconds() as u64;
 
-    const SECOND_AS_MS: u64 = time::Duration::SECOND.whole_milliseconds() as u64;
-
     const TOTAL_SECS_IN_WHEEL: u64 = Self::YEAR_AS_SECS * YEARS as u64;
     /// Duration of a full wheel cycle
     pub const CYCLE_LENGTH: time::Duration =
@@ -451,18 +665,31 @@ where
     pub const TOTAL_WHEEL_SLOTS: usize = SECONDS + MINUTES + HOURS + DAYS + WEEKS + YEARS;
 
     /// Creates a new Wheel from the given configuration
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configuration generates deltas and has a non-zero allowed lateness.
     pub fn new(conf: HawConf) -> Self {
+        assert!(
+            !conf.generate_deltas || conf.allowed_lateness.is_zero(),
+            "Allowed lateness cannot be combined with generated deltas"
+        );
         Self {
             watermark: conf.watermark,
+            milliseconds_wheel: conf.milliseconds.map(MaybeWheel::new),
             seconds_wheel: MaybeWheel::new(conf.seconds),
             minutes_wheel: MaybeWheel::new(conf.minutes),
             hours_wheel: MaybeWheel::new(conf.hours),
             days_wheel: MaybeWheel::new(conf.days),
             weeks_wheel: MaybeWheel::new(conf.weeks),
             years_wheel: MaybeWheel::new(conf.years),
+            calendar_wheel: conf
+                .calendar
+                .map(|calendar| CalendarWheel::new(conf.watermark, calendar, conf.utc_offset)),
             conf,
             delta: DeltaState::new(conf.watermark, Vec::new()),
-            window_manager: None,
+            window_managers: Vec::new(),
+            next_window_id: 0,
             #[cfg(feature = "timer")]
             timer: TimerWheel::new(RawTimerWheel::default()),
             #[cfg(feature = "profiler")]
@@ -480,9 +707,90 @@ where
         self.delta.clone()
     }
 
+    /// Returns the deltas generated since the given checkpoint and compacts them
+    ///
+    /// A checkpoint is identified by the watermark at which it was taken. The returned [DeltaState] starts at
+    /// `since` and ends at the current watermark, which is the id of the new checkpoint. All deltas up to
+    /// the current watermark are dropped from the wheel afterwards.
+    ///
+    /// Returns `None` if deltas are not generated (see [HawConf::with_deltas]), if the deltas since the
+    /// checkpoint have already been compacted or if the checkpoint is not aligned with the tick size.
+    ///
+    /// Checkpoints only capture advancements of the wheel, which is why late corrections
+    /// (see [Self::apply_late]) are rejected by wheels that generate deltas.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{aggregator::sum::U32SumAggregator, Haw, HawConf};
+    ///
+    /// let mut haw: Haw<U32SumAggregator> = Haw::new(HawConf::default().with_deltas());
+    /// haw.delta_advance(vec![Some(10), None]);
+    ///
+    /// let checkpoint = haw.delta_checkpoint(0).unwrap();
+    /// assert_eq!(checkpoint.deltas, vec![Some(10), None]);
+    ///
+    /// haw.delta_advance(vec![Some(5)]);
+    /// // deltas before the watermark of the last checkpoint have been compacted
+    /// assert!(haw.delta_checkpoint(0).is_none());
+    /// assert_eq!(haw.delta_checkpoint(2000).unwrap().deltas, vec![Some(5)]);
+    /// ```
+    pub fn delta_checkpoint(&mut self, since: u64) -> Option<DeltaState<A::PartialAggregate>> {
+        let oldest_ts = self.delta.oldest_ts;
+        let tick_size_ms = self.tick_size_ms();
+        if !self.conf.generate_deltas
+            || since < oldest_ts
+            || since > self.watermark
+            || !(since - oldest_ts).is_multiple_of(tick_size_ms)
+        {
+            return None;
+        }
+
+        let skip = ((since - oldest_ts) / tick_size_ms) as usize;
+        let compacted =
+            core::mem::replace(&mut self.delta, DeltaState::new(self.watermark, Vec::new()));
+        let deltas = compacted.deltas.into_iter().skip(skip).collect();
+        Some(DeltaState::new(since, deltas))
+    }
+
+    /// Applies a chain of incremental checkpoints (see [Self::delta_checkpoint]) onto the wheel
+    ///
+    /// Deltas of a checkpoint that are already covered by the watermark of the wheel are skipped, meaning the
+    /// same checkpoint may be applied more than once. Returns the number of applied deltas.
+    ///
+    /// The wheel ends up in the same state as the wheel that generated the checkpoints since the latter
+    /// cannot have been corrected by late data (see [HawConf::with_deltas]).
+    ///
+    /// Returns an error if a checkpoint starts after the watermark of the wheel or is not aligned with its tick size.
+    /// Checkpoints preceding the erroneous one remain applied.
+    pub fn apply_checkpoints(
+        &mut self,
+        chain: impl IntoIterator<Item = DeltaState<A::PartialAggregate>>,
+    ) -> Result<usize, CheckpointError> {
+        let tick_size_ms = self.tick_size_ms();
+        let mut applied = 0;
+        for checkpoint in chain {
+            let id = checkpoint.oldest_ts;
+            if id > self.watermark {
+                return Err(CheckpointError::Gap {
+                    watermark: self.watermark,
+                    checkpoint: id,
+                });
+            }
+            if !(self.watermark - id).is_multiple_of(tick_size_ms) {
+                return Err(CheckpointError::Misaligned { checkpoint: id });
+            }
+            let skip = ((self.watermark - id) / tick_size_ms) as usize;
+            applied += checkpoint.deltas.len().saturating_sub(skip);
+            let _ = self.delta_advance(checkpoint.deltas.into_iter().skip(skip));
+        }
+        Ok(applied)
+    }
+
     /// Returns how many wheel slots are utilised
     pub fn len(&self) -> usize {
-        self.seconds_wheel.len()
+        self.milliseconds_wheel.as_ref().map_or(0, |w| w.len())
+            + self.seconds_wheel.len()
             + self.minutes_wheel.len()
             + self.hours_wheel.len()
             + self.days_wheel.len()
@@ -502,10 +810,14 @@ where
     // Returns the current low watermark as [OffsetDateTime]
     #[inline]
     fn now(&self) -> OffsetDateTime {
-        Self::to_offset_date(self.watermark)
+        self.to_offset_date(self.watermark)
     }
     /// Returns memory used in bytes for all levels
     pub fn size_bytes(&self) -> usize {
+        let millis = self
+            .milliseconds_wheel
+            .as_ref()
+            .map_or(0, |w| w.size_bytes());
         let secs = self.seconds_wheel.size_bytes();
         let min = self.minutes_wheel.size_bytes();
         let hr = self.hours_wheel.size_bytes();
@@ -513,12 +825,62 @@ where
         let week = self.weeks_wheel.size_bytes();
         let year = self.years_wheel.size_bytes();
 
-        secs + min + hr + day + week + year
+        let calendar = self
+            .calendar_wheel
+            .as_ref()
+            .map_or(0, |calendar| calendar.size_bytes());
+
+        millis + secs + min + hr + day + week + year + calendar
+    }
+
+    /// Returns the configuration of the wheel
+    #[inline]
+    pub fn conf(&self) -> &HawConf {
+        &self.conf
+    }
+
+    /// Encodes the wheel into a versioned binary snapshot
+    ///
+    /// The snapshot starts with a header that records the format version, the aggregator and the [HawConf]
+    /// followed by the encoded wheel. Both parts are protected by checksums.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{aggregator::sum::U32SumAggregator, Haw, NumericalDuration};
+    ///
+    /// let mut haw: Haw<U32SumAggregator> = Haw::default();
+    /// haw.delta_advance(vec![Some(10), None, Some(5)]);
+    ///
+    /// let bytes = haw.to_snapshot();
+    /// let restored: Haw<U32SumAggregator> = Haw::from_snapshot(&bytes).unwrap();
+    /// assert_eq!(restored.interval(3.seconds()), Some(15));
+    /// ```
+    #[cfg(feature = "snapshot")]
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        snapshot::encode::<A, Self>(SnapshotKind::Haw, &self.conf, &self.to_v1())
+    }
+
+    /// Decodes a wheel from a snapshot created by [Self::to_snapshot]
+    ///
+    /// Returns an error if the snapshot is corrupted, was written by another aggregator or uses an unknown format version.
+    #[cfg(feature = "snapshot")]
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        snapshot::decode::<A, _>(SnapshotKind::Haw, bytes)
+    }
+
+    /// Returns the tick size of the wheel in milliseconds
+    ///
+    /// Defaults to a second unless a milliseconds wheel has been configured (see [HawConf::with_milliseconds]).
+    #[inline]
+    pub fn tick_size_ms(&self) -> u64 {
+        self.conf.tick_size_ms()
     }
 
-    /// Returns how many ticks (seconds) are left until the wheel is fully utilised
+    /// Returns how many ticks are left until the wheel is fully utilised
     pub fn remaining_ticks(&self) -> u64 {
-        Self::TOTAL_SECS_IN_WHEEL - self.current_time_in_cycle().whole_seconds() as u64
+        let elapsed_ms = self.current_time_in_cycle().whole_milliseconds() as u64;
+        (Self::to_ms(Self::TOTAL_SECS_IN_WHEEL) - elapsed_ms) / self.tick_size_ms()
     }
 
     /// Returns Duration that represents where the wheel currently is in its cycle
@@ -531,7 +893,11 @@ where
         let week_secs = self.weeks_wheel.rotation_count() as u64 * Self::WEEK_AS_SECS;
         let year_secs = self.years_wheel.rotation_count() as u64 * Self::YEAR_AS_SECS;
         let cycle_time = secs + min_secs + hr_secs + day_secs + week_secs + year_secs;
-        Duration::seconds(cycle_time as i64)
+        let millis = self
+            .milliseconds_wheel
+            .as_ref()
+            .map_or(0, |w| w.rotation_count() as u64 * self.tick_size_ms());
+        Duration::milliseconds((Self::to_ms(cycle_time) + millis) as i64)
     }
 
     #[inline]
@@ -539,8 +905,24 @@ where
         ts.saturating_mul(1000)
     }
     #[inline]
-    fn to_offset_date(ts: u64) -> OffsetDateTime {
-        OffsetDateTime::from_unix_timestamp(ts as i64 / 1000).unwrap()
+    fn to_offset_date(&self, ts: u64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(ts as i128 * 1_000_000)
+            .unwrap()
+            .to_offset(self.utc_offset())
+    }
+    #[inline]
+    fn utc_offset(&self) -> UtcOffset {
+        to_utc_offset(self.conf.utc_offset)
+    }
+    // Converts the range into local time and truncates sub-second precision if the wheel does not maintain a milliseconds wheel
+    #[inline]
+    fn align_range(&self, range: WheelRange) -> WheelRange {
+        let range = range.to_offset(self.utc_offset());
+        if self.milliseconds_wheel.is_some() {
+            range
+        } else {
+            range.truncate_millis()
+        }
     }
 
     /// Converts all wheels to be prefix-enabled
@@ -559,6 +941,10 @@ where
             "Aggregator is not invertible, must implement combine_inverse."
         );
 
+        if let Some(milliseconds) = self.milliseconds_wheel.as_mut().and_then(|w| w.as_mut()) {
+            milliseconds.to_prefix();
+        }
+
         if let Some(seconds) = self.seconds_wheel.as_mut() {
             seconds.to_prefix();
 
@@ -584,6 +970,10 @@ where
     /// If you have built a wheel in index mode and configured with explicit SIMD, then
     /// this function must be called once the index process is complete.
     pub fn to_simd_wheels(&mut self) {
+        if let Some(milliseconds) = self.milliseconds_wheel.as_mut().and_then(|w| w.as_mut()) {
+            milliseconds.to_simd();
+        }
+
         if let Some(seconds) = self.seconds_wheel.as_mut() {
             seconds.to_simd();
 
@@ -610,12 +1000,46 @@ where
         self.days_wheel.as_mut().unwrap().to_deque();
     }
 
-    /// Installs a periodic window aggregation query
-    pub fn window(&mut self, window: Window) {
-        self.window_manager = Some(WindowManager::new(self.watermark, window));
+    /// Installs a periodic window aggregation query and returns its [WindowId]
+    ///
+    /// Multiple windows may be installed at the same time. Results of all windows are returned when
+    /// advancing the wheel and are tagged with the [WindowId] of the window that produced them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{Haw, Window, aggregator::sum::U32SumAggregator, NumericalDuration};
+    ///
+    /// let mut haw: Haw<U32SumAggregator> = Haw::default();
+    /// let tumbling = haw.window(Window::tumbling(10.seconds()));
+    /// let sliding = haw.window(Window::sliding(5.minutes(), 1.minutes()));
+    /// assert_ne!(tumbling, sliding);
+    /// ```
+    pub fn window(&mut self, window: Window) -> WindowId {
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+
+        let allowed_lateness = self.conf.allowed_lateness.whole_milliseconds() as u64;
+        self.window_managers.push(WindowManager::new(
+            id,
+            self.watermark,
+            self.tick_size_ms(),
+            window,
+            allowed_lateness,
+        ));
+        id
+    }
+
+    /// Removes the window with the given [WindowId]
+    ///
+    /// Returns `true` if the window was installed.
+    pub fn remove_window(&mut self, id: WindowId) -> bool {
+        let len = self.window_managers.len();
+        self.window_managers.retain(|manager| manager.id != id);
+        self.window_managers.len() != len
     }
 
-    /// Advances the time of the wheel aligned by the lowest unit (Second)
+    /// Advances the time of the wheel aligned by the lowest unit (see [Self::tick_size_ms])
     #[inline]
     pub(crate) fn advance_to(
         &mut self,
@@ -657,12 +1081,14 @@ where
         deltas: impl IntoIterator<Item = Option<A::PartialAggregate>>,
     ) -> Vec<WindowAggregate<A::PartialAggregate>> {
         let mut windows = Vec::new();
+        self.drain_window_corrections(&mut windows);
+
         for delta in deltas {
-            self.tick(delta);
+            self.tick(delta.clone());
 
             // Store delta if configured to
             if self.conf.generate_deltas {
-                self.delta.push(delta);
+                self.delta.push(delta.clone());
             }
             // maybe handle window if there is any configured
             self.handle_window_maybe(delta, &mut windows);
@@ -677,21 +1103,23 @@ where
         duration: Duration,
         waw: &mut WriterWheel<A>,
     ) -> Vec<WindowAggregate<A::PartialAggregate>> {
-        let ticks: usize = duration.whole_seconds() as usize;
+        let tick_size_ms = self.tick_size_ms();
+        let ticks: usize = (duration.whole_milliseconds() as u64 / tick_size_ms) as usize;
         let mut windows = Vec::new();
+        self.drain_window_corrections(&mut windows);
 
-        if ticks <= Self::CYCLE_LENGTH_SECS as usize {
+        if ticks as u64 * tick_size_ms <= Self::to_ms(Self::CYCLE_LENGTH_SECS) {
             for _ in 0..ticks {
                 // tick the write wheel and freeze mutable aggregate
                 let delta = waw.tick().map(A::freeze);
 
                 // Store delta if configured to
                 if self.conf.generate_deltas {
-                    self.delta.push(delta);
+                    self.delta.push(delta.clone());
                 }
 
                 // Tick the HAW
-                self.tick(delta);
+                self.tick(delta.clone());
 
                 // maybe handle window if there is any configured
                 self.handle_window_maybe(delta, &mut windows);
@@ -703,33 +1131,59 @@ where
         windows
     }
 
+    // internal function to emit corrections of already emitted windows
+    fn drain_window_corrections(
+        &mut self,
+        windows: &mut Vec<WindowAggregate<A::PartialAggregate>>,
+    ) {
+        for manager in self.window_managers.iter_mut() {
+            manager.drain_corrections(self.watermark, windows);
+        }
+    }
+
     // internal function to handle installed window queries
     fn handle_window_maybe(
         &mut self,
         delta: Option<A::PartialAggregate>,
         windows: &mut Vec<WindowAggregate<A::PartialAggregate>>,
     ) {
-        match self.window_manager.as_ref().map(|wm| wm.window) {
-            Some(Window::Session { .. }) => self.handle_session_window(delta, windows),
-            Some(Window::Sliding { .. }) => self.handle_slicing_window(windows),
-            Some(Window::Tumbling { .. }) => self.handle_slicing_window(windows),
-            None => {
-                // do nothing, no window installed...
+        if self.window_managers.is_empty() {
+            // do nothing, no window installed...
+            return;
+        }
+
+        // NOTE: temporarily take the managers since slicing windows need to query the wheel (self.combine_range)
+        let mut managers = core::mem::take(&mut self.window_managers);
+        // pairs computed during this tick that may be shared between slicing windows
+        let mut pairs = SharedPairs::default();
+
+        for manager in managers.iter_mut() {
+            match manager.window {
+                Window::Session { .. } => {
+                    self.handle_session_window(manager, delta.clone(), windows)
+                }
+                Window::Sliding { .. } | Window::Tumbling { .. } => {
+                    self.handle_slicing_window(manager, &mut pairs, windows)
+                }
             }
         }
+
+        self.window_managers = managers;
     }
 
     fn handle_session_window(
-        &mut self,
+        &self,
+        manager: &mut WindowManager<A>,
         delta: Option<A::PartialAggregate>,
         windows: &mut Vec<WindowAggregate<A::PartialAggregate>>,
     ) {
-        // SAFETY: safe to unwrap since we confirm that there is a window manager before calling this function
-        let manager = self.window_manager.as_mut().unwrap();
+        let window_id = manager.id;
+        let tick_size_ms = self.tick_size_ms();
         let (ref mut state, ref mut aggregator) = manager.aggregator.session_as_mut();
         if let Some(partial) = delta {
             if !state.has_active_session() {
-                state.activate_session(self.watermark.saturating_sub(1000)); // subtract watermark by 1 tick to align correctly.
+                state.activate_session(self.watermark.saturating_sub(tick_size_ms));
+                // subtract watermark by 1 tick to align correctly.
             }
             // Aggregate the partial into the session
             aggregator.aggregate_session(partial);
@@ -738,8 +1192,8 @@ where
         } else {
             // only do something with empty `None` delta if there is an active session
             if state.has_active_session() {
-                // Bump session period by 1 tick (i.e. SECOND)
-                state.bump_inactive_period(Duration::SECOND);
+                // Bump session period by 1 tick
+                state.bump_inactive_period(Duration::milliseconds(tick_size_ms as i64));
 
                 // if we have reached session gap of inactivity, we can close the session
                 if state.is_inactive() {
@@ -747,62 +1201,67 @@ where
 
                     // SAFETY: safe to unwrap since we checked if there is an active session
                     let window_start_ms = state.reset().unwrap();
-                    let window_end_ms = self.watermark.saturating_sub(1000); // subtract watermark by 1 tick to align correctly.
+                    let window_end_ms = self.watermark.saturating_sub(tick_size_ms); // subtract watermark by 1 tick to align correctly.
 
                     windows.push(WindowAggregate {
+                        window_id,
                         window_start_ms,
                         window_end_ms,
                         aggregate: session_aggregate,
+                        kind: WindowAggregateKind::Insert,
                     });
                 }
             }
         }
     }
 
-    fn handle_slicing_window(&mut self, windows: &mut Vec<WindowAggregate<A::PartialAggregate>>) {
-        // NOTE: accessing window manager twice since we need to borrow mutably and immutably (self.combine_range)
-        if let Some((pairs_remaining, current_pair_len)) = self.window_manager.as_mut().map(|wm| {
-            let (ref mut state, _) = wm.aggregator.slicing_as_mut();
-            state.pair_ticks_remaining -= 1;
-            (state.pair_ticks_remaining, state.current_pair_len)
-        }) {
-            // if we have no more pairs to process
-            if pairs_remaining == 0 {
-                // query the pair range
-                let from = Self::to_offset_date(self.watermark - current_pair_len as u64);
-                let to = Self::to_offset_date(self.watermark);
-                let pair = self.combine_range(WheelRange {
+    fn handle_slicing_window(
+        &self,
+        manager: &mut WindowManager<A>,
+        pairs: &mut SharedPairs<A::PartialAggregate>,
+        windows: &mut Vec<WindowAggregate<A::PartialAggregate>>,
+    ) {
+        let window_id = manager.id;
+        let (ref mut state, ref mut aggregator) = manager.aggregator.slicing_as_mut();
+        state.pair_ticks_remaining -= 1;
+
+        // if we have no more pairs to process
+        if state.pair_ticks_remaining == 0 {
+            // query the pair range or reuse it if another window has already computed it
+            let pair = pairs.get_or_insert_with(state.current_pair_len, || {
+                let from = self.to_offset_date(self.watermark - state.current_pair_len as u64);
+                let to = self.to_offset_date(self.watermark);
+                self.combine_range(WheelRange {
                     start: from,
                     end: to,
-                });
-
-                // SAFETY: safe to unwrap at this point
-                let manager = self.window_manager.as_mut().unwrap();
-                let (ref mut state, ref mut aggregator) = manager.aggregator.slicing_as_mut();
-
-                // insert pair into window aggregator
-                aggregator.push(pair.unwrap_or(A::IDENTITY));
-
-                // Update pair metadata
-                state.update_state(self.watermark);
+                })
+                .unwrap_or(A::IDENTITY)
+            });
 
-                // handle the window itself
-                if self.watermark == state.next_window_end {
-                    let window_start_ms = self.watermark.saturating_sub(state.range as u64);
-                    let window_end_ms = self.watermark;
-                    windows.push(WindowAggregate {
-                        window_start_ms,
-                        window_end_ms,
-                        aggregate: aggregator.query(),
-                    });
+            // insert pair into window aggregator
+            aggregator.push(pair);
+
+            // Update pair metadata
+            state.update_state(self.watermark);
+
+            // handle the window itself
+            if self.watermark == state.next_window_end {
+                let window = WindowAggregate {
+                    window_id,
+                    window_start_ms: self.watermark.saturating_sub(state.range as u64),
+                    window_end_ms: self.watermark,
+                    aggregate: aggregator.query(),
+                    kind: WindowAggregateKind::Insert,
+                };
+
+                // clean up pairs
+                for _i in 0..state.total_pairs() {
+                    aggregator.pop();
+                }
 
-                    // clean up pairs
-                    for _i in 0..state.total_pairs() {
-                        aggregator.pop();
-                    }
+                state.next_window_end += state.slide as u64;
 
-                    state.next_window_end += state.slide as u64;
-                }
+                manager.emit(window, windows);
             }
         }
     }
@@ -811,12 +1270,18 @@ where
     ///
     /// Use with caution as this operation cannot be reversed.
     pub fn clear(&mut self) {
+        if let Some(milliseconds) = self.milliseconds_wheel.as_mut() {
+            milliseconds.clear();
+        }
         self.seconds_wheel.clear();
         self.minutes_wheel.clear();
         self.hours_wheel.clear();
         self.days_wheel.clear();
         self.weeks_wheel.clear();
         self.years_wheel.clear();
+        if let Some(calendar) = self.calendar_wheel.as_mut() {
+            calendar.clear();
+        }
     }
 
     /// Return the current watermark as milliseconds for this wheel
@@ -880,12 +1345,30 @@ where
         range: WheelRange,
         interval: Duration,
     ) -> Option<Vec<(u64, A::Aggregate)>> {
-        let WheelRange { start, end } = range;
+        self.group_by_partial(range, interval).map(|groups| {
+            groups
+                .into_iter()
+                .map(|(ts, partial)| (ts, A::lower(partial)))
+                .collect()
+        })
+    }
+
+    /// Groups the data into partial aggregates based on the given range and interval
+    ///
+    /// Works like [Self::group_by] but returns partial aggregates which may be combined further.
+    #[inline]
+    pub fn group_by_partial(
+        &self,
+        range: WheelRange,
+        interval: Duration,
+    ) -> Option<Vec<(u64, A::PartialAggregate)>> {
+        let WheelRange { start, end } = self.align_range(range);
+        let interval_ms = interval.whole_milliseconds() as i64;
 
         // Sanity check: return early with `None` if the range is invalid
         if start >= end
-            || interval.whole_seconds() == 0
-            || Self::to_ms(start.unix_timestamp() as u64) > self.watermark
+            || interval_ms < self.tick_size_ms() as i64
+            || to_unix_ms(start) > self.watermark
         {
             return None;
         }
@@ -897,13 +1380,77 @@ where
 
         // Query each subrange until we reach the end
         while step < end {
-            let next: OffsetDateTime = step + time::Duration::seconds(interval.whole_seconds());
+            let next: OffsetDateTime = step + time::Duration::milliseconds(interval_ms);
             let query_range = WheelRange {
                 start: step,
                 end: next,
             };
             result.push((
-                Self::to_ms(step.unix_timestamp() as u64),
+                to_unix_ms(step),
+                self.combine_range(query_range).unwrap_or(A::IDENTITY),
+            ));
+            step = next;
+        }
+
+        Some(result)
+    }
+
+    /// Groups the data into aggregates based on the given range and calendar unit
+    ///
+    /// Unlike [Self::group_by], each group follows the calendar (e.g., 28-31 days for a month).
+    /// Requires calendar rollups to be configured (see [HawConf::with_calendar]).
+    ///
+    /// Returns `None` if the range start is not aligned to the calendar unit or if the range is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{aggregator::sum::U32SumAggregator, CalendarConf, CalendarUnit, Haw, HawConf, WheelRange};
+    ///
+    /// let watermark = 1711929598000; // 2024-03-31 23:59:58
+    /// let conf = HawConf::default()
+    ///     .with_watermark(watermark)
+    ///     .with_calendar(CalendarConf::default());
+    /// let mut haw: Haw<U32SumAggregator> = Haw::new(conf);
+    ///
+    /// // cross the month (and quarter) boundary at 2024-04-01 00:00:00
+    /// haw.delta_advance(vec![Some(1), Some(2), Some(3), Some(4)]);
+    ///
+    /// // 2024-01-01 00:00:00 - 2024-04-01 00:00:00
+    /// let range = WheelRange::new_unchecked(1704067200000, 1711929600000);
+    /// assert_eq!(
+    ///     haw.group_by_calendar(range, CalendarUnit::Month),
+    ///     Some(vec![(1704067200000, 0), (1706745600000, 0), (1709251200000, 3)])
+    /// );
+    /// assert_eq!(
+    ///     haw.group_by_calendar(range, CalendarUnit::Quarter),
+    ///     Some(vec![(1704067200000, 3)])
+    /// );
+    /// ```
+    pub fn group_by_calendar(
+        &self,
+        range: WheelRange,
+        unit: CalendarUnit,
+    ) -> Option<Vec<(u64, A::Aggregate)>> {
+        let start = to_unix_ms(range.start);
+        let end = to_unix_ms(range.end);
+
+        // Sanity check: return early with `None` if the range is invalid
+        if self.calendar_wheel.is_none() || start >= end || start > self.watermark {
+            return None;
+        }
+
+        let mut result = Vec::new();
+
+        // define starting point
+        let mut step = start;
+
+        // Query each calendar unit until we reach the end
+        while step < end {
+            let next = next_unit_start(step, unit, self.utc_offset())?;
+            let query_range = WheelRange::new_unchecked(step, next);
+            result.push((
+                step,
                 A::lower(self.combine_range(query_range).unwrap_or(A::IDENTITY)),
             ));
             step = next;
@@ -931,11 +1478,17 @@ where
     /// ```
     #[inline]
     pub fn range(&self, range: impl Into<WheelRange>) -> Option<Vec<(u64, A::PartialAggregate)>> {
-        let range = range.into();
+        let range = self.align_range(range.into());
         let start = range.start;
         let end = range.end;
 
         match range.lowest_granularity() {
+            Granularity::Millisecond => {
+                let ticks = (end - start).whole_milliseconds() as u64 / self.tick_size_ms();
+                self.milliseconds_wheel
+                    .as_ref()
+                    .and_then(|wheel| wheel.range(start, ticks as usize, Granularity::Millisecond))
+            }
             Granularity::Second => {
                 let seconds = (end - start).whole_seconds() as usize;
                 self.seconds_wheel
@@ -1048,16 +1601,208 @@ where
         self.combine_range_inner(range)
     }
 
-    /// Combines partial aggregates within the given date range [start, end) into a final partial aggregate
-    #[inline]
-    fn combine_range_inner(
-        &self,
-        range: impl Into<WheelRange>,
-    ) -> (Option<A::PartialAggregate>, usize) {
-        #[cfg(feature = "profiler")]
-        profile_scope!(&self.stats.combine_range);
-
-        let range = range.into();
+    /// Combines partial aggregates within the given date range [start, end) and describes the result
+    ///
+    /// Unlike [Self::combine_range], the result carries the watermark it was computed at and the part
+    /// of the range it covers, and a [QueryError] tells why a range cannot be answered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{Haw, QueryError, WheelRange, aggregator::sum::U32SumAggregator};
+    ///
+    /// let mut haw: Haw<U32SumAggregator> = Haw::default();
+    /// haw.delta_advance(vec![Some(10), None, Some(50), None]);
+    ///
+    /// let result = haw.try_combine_range(WheelRange::new_unchecked(0, 4000)).unwrap();
+    /// assert_eq!(result.aggregate, 60);
+    /// assert_eq!(result.watermark, 4000);
+    /// assert!(result.is_complete());
+    ///
+    /// assert_eq!(
+    ///     haw.try_combine_range(WheelRange::new_unchecked(0, 5000)),
+    ///     Err(QueryError::BeyondWatermark { end_ms: 5000, watermark: 4000 })
+    /// );
+    /// ```
+    pub fn try_combine_range(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> Result<QueryResult<A::PartialAggregate>, QueryError> {
+        let requested = range.into();
+        let (start_ms, end_ms) = (requested.start_ms(), requested.end_ms());
+
+        if start_ms >= end_ms {
+            return Err(QueryError::EmptyRange { start_ms, end_ms });
+        }
+        if end_ms > self.watermark {
+            return Err(QueryError::BeyondWatermark {
+                end_ms,
+                watermark: self.watermark,
+            });
+        }
+
+        match self.combine_range(requested) {
+            Some(aggregate) => {
+                // there is no data before the wheel started
+                let aligned = self.align_range(requested);
+                let covered = WheelRange::from(
+                    cmp::max(aligned.start, self.to_offset_date(self.wheel_start())),
+                    aligned.end,
+                );
+                Ok(QueryResult {
+                    aggregate,
+                    watermark: self.watermark,
+                    requested,
+                    covered,
+                })
+            }
+            None => {
+                let retained_ms = self.retained_start();
+                if start_ms < retained_ms {
+                    Err(QueryError::Evicted {
+                        start_ms,
+                        retained_ms,
+                    })
+                } else {
+                    Err(QueryError::Unaligned { start_ms, end_ms })
+                }
+            }
+        }
+    }
+
+    /// Combines partial aggregates within the part of the date range [start, end) that the wheel can answer
+    ///
+    /// This is a best-effort variant of [Self::try_combine_range]. The range is clamped to the data
+    /// retained below the watermark and, if needed, its boundaries are rounded inwards to the slots
+    /// that cover them. The returned [QueryResult] reports the effective range in `covered`.
+    ///
+    /// Returns an error if no part of the range can be answered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{Haw, WheelRange, aggregator::sum::U32SumAggregator};
+    ///
+    /// let mut haw: Haw<U32SumAggregator> = Haw::default();
+    /// haw.delta_advance(vec![Some(10), None, Some(50), None]);
+    ///
+    /// // the range reaches beyond the watermark
+    /// let result = haw.combine_range_clamped(WheelRange::new_unchecked(2000, 10000)).unwrap();
+    /// assert_eq!(result.aggregate, 50);
+    /// assert_eq!(result.covered, WheelRange::new_unchecked(2000, 4000));
+    /// assert!(!result.is_complete());
+    /// ```
+    pub fn combine_range_clamped(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> Result<QueryResult<A::PartialAggregate>, QueryError> {
+        let requested = range.into();
+        let (start_ms, end_ms) = (requested.start_ms(), requested.end_ms());
+
+        if start_ms >= end_ms {
+            return Err(QueryError::EmptyRange { start_ms, end_ms });
+        }
+        if start_ms >= self.watermark {
+            return Err(QueryError::BeyondWatermark {
+                end_ms,
+                watermark: self.watermark,
+            });
+        }
+        let retained_ms = self.retained_start();
+        if end_ms <= retained_ms {
+            return Err(QueryError::Evicted {
+                start_ms,
+                retained_ms,
+            });
+        }
+
+        let start = cmp::max(start_ms, retained_ms);
+        let end = cmp::min(end_ms, self.watermark);
+
+        // candidate ranges rounded inwards to increasingly coarse slot boundaries
+        let offset_ms = self.utc_offset().whole_seconds() as i64 * 1000;
+        let granularities = [
+            self.tick_size_ms(),
+            SECOND_TICK_MS,
+            MINUTE_TICK_MS,
+            HOUR_TICK_MS,
+            DAY_TICK_MS,
+        ];
+        let candidates = core::iter::once((start, end)).chain(granularities.into_iter().flat_map(
+            |granularity| {
+                let start = round_up(start, granularity, offset_ms);
+                [
+                    (start, end),
+                    (start, round_down(end, granularity, offset_ms)),
+                ]
+            },
+        ));
+
+        for (start, end) in candidates {
+            if start >= end {
+                continue;
+            }
+            let Ok(covered) = WheelRange::new(start, end) else {
+                continue;
+            };
+            if let Some(aggregate) = self.combine_range(covered) {
+                return Ok(QueryResult {
+                    aggregate,
+                    watermark: self.watermark,
+                    requested,
+                    covered,
+                });
+            }
+        }
+        Err(QueryError::Unaligned { start_ms, end_ms })
+    }
+
+    /// Returns the oldest timestamp covered by a retained slot
+    ///
+    /// Returns the watermark if no slots are retained.
+    pub fn retained_start(&self) -> u64 {
+        let wheels = [
+            self.milliseconds_wheel.as_ref().and_then(|w| w.as_ref()),
+            self.seconds_wheel.as_ref(),
+            self.minutes_wheel.as_ref(),
+            self.hours_wheel.as_ref(),
+            self.days_wheel.as_ref(),
+            self.weeks_wheel.as_ref(),
+            self.years_wheel.as_ref(),
+        ];
+        let oldest_slot = wheels.into_iter().flatten().map(|wheel| {
+            wheel
+                .watermark()
+                .saturating_sub(wheel.len() as u64 * wheel.tick_size_ms())
+        });
+        let oldest_month = self
+            .calendar_wheel
+            .as_ref()
+            .filter(|calendar| !calendar.is_empty())
+            .map(|calendar| calendar.months_span(&(0..calendar.len())).0);
+
+        oldest_slot
+            .chain(oldest_month)
+            .fold(self.watermark, cmp::min)
+    }
+
+    // Returns the time at which the current cycle of the wheel started
+    #[inline]
+    fn wheel_start(&self) -> u64 {
+        self.watermark()
+            .saturating_sub(self.current_time_in_cycle().whole_milliseconds() as u64)
+    }
+
+    /// Combines partial aggregates within the given date range [start, end) into a final partial aggregate
+    #[inline]
+    fn combine_range_inner(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> (Option<A::PartialAggregate>, usize) {
+        #[cfg(feature = "profiler")]
+        profile_scope!(&self.stats.combine_range);
+
+        let range = range.into();
         let WheelRange { start, end } = range;
 
         // Return early if the range is invalid (Start exceeds End)
@@ -1078,6 +1823,10 @@ where
                 let (result, cost) = self.inverse_landmark_aggregation(wheel_aggs);
                 (Some(result), cost)
             }
+            Some(ExecutionPlan::CalendarAggregation(calendar_agg)) => (
+                self.calendar_aggregation(&calendar_agg),
+                calendar_agg.cost(),
+            ),
             None => (None, 0), // No execution plan possible
         }
     }
@@ -1085,27 +1834,32 @@ where
     ///
     /// Returns `None` if no plan can be established.
     #[inline]
-    fn create_exec_plan(&self, mut range: WheelRange) -> Option<ExecutionPlan> {
+    fn create_exec_plan(&self, range: WheelRange) -> Option<ExecutionPlan> {
         #[cfg(feature = "profiler")]
         profile_scope!(&self.stats.exec_plan);
 
+        let mut range = self.align_range(range);
         let mut best_plan: Option<ExecutionPlan> = None;
+        let unclamped_start_ms = to_unix_ms(range.start);
 
-        let wheel_start = self
-            .watermark()
-            .saturating_sub(self.current_time_in_cycle().whole_milliseconds() as u64);
+        let wheel_start = self.wheel_start();
 
         // SAFETY: ensure start range is not lower than the start of the wheel time
-        range.start = cmp::max(range.start, Self::to_offset_date(wheel_start));
+        range.start = cmp::max(range.start, self.to_offset_date(wheel_start));
 
-        let end_ms = Self::to_ms(range.end.unix_timestamp() as u64);
-        let start_ms = Self::to_ms(range.start.unix_timestamp() as u64);
+        let end_ms = to_unix_ms(range.end);
+        let start_ms = to_unix_ms(range.start);
 
         // Landmark optimization: landmark covers the whole range
         if start_ms <= wheel_start && end_ms >= self.watermark() {
             return Some(ExecutionPlan::LandmarkAggregation);
         }
 
+        // Calendar optimization: range is aligned to month boundaries and covered by frozen months
+        if let Some(plan) = self.calendar_aggregation_plan(unclamped_start_ms, end_ms) {
+            return Some(ExecutionPlan::CalendarAggregation(plan));
+        }
+
         // Create a single-wheel aggregation plan using the lowest granularity
         if let Some(plan) = self.wheel_aggregation_plan(range) {
             // Check for possible early return: Going further may just have more overhead
@@ -1169,6 +1923,12 @@ where
         let end = range.end;
 
         match range.lowest_granularity() {
+            Granularity::Millisecond => {
+                let ticks = (end - start).whole_milliseconds() as u64 / self.tick_size_ms();
+                self.milliseconds_wheel.as_ref().and_then(|wheel| {
+                    wheel.plan(start, ticks as usize, range, Granularity::Millisecond)
+                })
+            }
             Granularity::Second => {
                 let seconds = (end - start).whole_seconds() as usize;
                 self.seconds_wheel
@@ -1217,13 +1977,16 @@ where
     // helper fn to calculate the next aligned end date
     #[inline]
     fn new_curr_end(current_start: OffsetDateTime, end: OffsetDateTime) -> OffsetDateTime {
+        let millisecond = current_start.millisecond();
         let second = current_start.second();
         let minute = current_start.minute();
         let hour = current_start.hour();
         let day = current_start.day();
 
         // based on the lowest granularity figure out the duration to next alignment point
-        let next = if second > 0 {
+        let next = if millisecond > 0 {
+            time::Duration::milliseconds(1000 - millisecond as i64)
+        } else if second > 0 {
             time::Duration::seconds(SECONDS as i64 - second as i64)
         } else if minute > 0 {
             time::Duration::minutes(MINUTES as i64 - minute as i64)
@@ -1252,12 +2015,14 @@ where
         if next_aligned > end {
             // if jumping too far: check the remaining duration between current_start and end
             let rem = end - current_start;
+            let rem_millis = rem.whole_milliseconds() as i64;
             let rem_secs = rem.whole_seconds();
             let rem_mins = rem.whole_minutes();
             let rem_hours = rem.whole_hours();
             let rem_days = rem.whole_days();
 
             let remains = [
+                (rem_millis, time::Duration::milliseconds(rem_millis)),
                 (rem_secs, time::Duration::seconds(rem_secs)),
                 (rem_mins, time::Duration::minutes(rem_mins)),
                 (rem_hours, time::Duration::hours(rem_hours)),
@@ -1286,28 +2051,29 @@ where
         let mut aggregations = WheelAggregations::default();
 
         for range in ranges.into_iter() {
-            match self.wheel_aggregation_plan(range) {
-                Some(plan) => aggregations.push(plan),
-                None => return None, // early return if a single aggregation cannot be executed
-            }
+            // early return if a single aggregation cannot be executed
+            aggregations.push(self.wheel_aggregation_plan(range)?);
         }
 
         // function that returns a score of the wheel range which is used during sorting
         let granularity_score = |start: &OffsetDateTime, end: &OffsetDateTime| {
-            let (sh, sm, ss) = start.time().as_hms();
-            let (eh, em, es) = end.time().as_hms();
-            if ss > 0 || es > 0 {
-                // second rank
+            let (sh, sm, ss, sms) = start.time().as_hms_milli();
+            let (eh, em, es, ems) = end.time().as_hms_milli();
+            if sms > 0 || ems > 0 {
+                // millisecond rank
                 0
+            } else if ss > 0 || es > 0 {
+                // second rank
+                1
             } else if sm > 0 || em > 0 {
                 // minute rank
-                1
+                2
             } else if sh > 0 || eh > 0 {
                 // hour rank
-                2
+                3
             } else {
                 // day rank (00:00:00 - 00:00:00)
-                3
+                4
             }
         };
 
@@ -1322,6 +2088,22 @@ where
         Some(CombinedAggregation::from(aggregations))
     }
 
+    // Creates a calendar aggregation plan if the range [start, end) is aligned to month boundaries
+    #[inline]
+    fn calendar_aggregation_plan(&self, start_ms: u64, end_ms: u64) -> Option<CalendarAggregation> {
+        self.calendar_wheel
+            .as_ref()
+            .and_then(|calendar| calendar.plan(start_ms, end_ms))
+    }
+
+    // Performs a calendar aggregation over frozen months and quarters
+    #[inline]
+    fn calendar_aggregation(&self, plan: &CalendarAggregation) -> Option<A::PartialAggregate> {
+        self.calendar_wheel
+            .as_ref()
+            .and_then(|calendar| calendar.aggregate(plan))
+    }
+
     // Performs a inverse landmark aggregation
     fn inverse_landmark_aggregation(
         &self,
@@ -1383,6 +2165,10 @@ where
         let (start, end) = agg.slots;
         let slot_range = start..end;
         match agg.granularity {
+            Granularity::Millisecond => self
+                .milliseconds_wheel
+                .as_ref()
+                .and_then(|wheel| wheel.combine_range(slot_range)),
             Granularity::Second => self.seconds_wheel.combine_range(slot_range),
             Granularity::Minute => self.minutes_wheel.combine_range(slot_range),
             Granularity::Hour => self.hours_wheel.combine_range(slot_range),
@@ -1429,7 +2215,7 @@ where
         profile_scope!(&self.stats.interval);
 
         let to = self.now();
-        let from = to.saturating_sub(time::Duration::seconds(dur.whole_seconds()));
+        let from = to.saturating_sub(time::Duration::milliseconds(dur.whole_milliseconds() as i64));
 
         self.analyze_combine_range(WheelRange {
             start: from,
@@ -1473,6 +2259,7 @@ where
         profile_scope!(&self.stats.landmark);
 
         let wheels = [
+            self.milliseconds_wheel.as_ref().and_then(|w| w.total()),
             self.seconds_wheel.total(),
             self.minutes_wheel.total(),
             self.hours_wheel.total(),
@@ -1495,8 +2282,8 @@ where
                     combines += 1;
                     Some(A::combine(curr, agg))
                 }
-                (None, Some(_)) => b,
-                _ => acc,
+                (None, b) => b,
+                (acc, None) => acc,
             })
             .flatten();
         (agg, combines)
@@ -1519,7 +2306,7 @@ where
     pub fn schedule_once(
         &self,
         time: u64,
-        f: impl Fn(&Haw<A>) + 'static,
+        f: impl TimerFn<A>,
     ) -> Result<(), TimerError<TimerAction<A>>> {
         self.timer
             .write()
@@ -1544,14 +2331,78 @@ where
         &self,
         at: u64,
         interval: Duration,
-        f: impl Fn(&Haw<A>) + 'static,
+        f: impl TimerFn<A>,
     ) -> Result<(), TimerError<TimerAction<A>>> {
         self.timer
             .write()
             .schedule_at(at, TimerAction::Repeat((at, interval, Box::new(f))))
     }
 
-    /// Tick the wheel by a single unit (second)
+    /// Applies a late partial aggregate to the already-frozen slots covering the given timestamp
+    ///
+    /// The partial is combined into the slot of every granularity that has already rolled up the timestamp,
+    /// so that queries at any level observe the correction. Slots that have been evicted by the
+    /// configured [RetentionPolicy] are left untouched.
+    ///
+    /// If the timestamp is within the configured allowed lateness (see [HawConf::with_allowed_lateness]),
+    /// installed tumbling and sliding windows covering the timestamp are corrected as well and emitted on the next advance.
+    ///
+    /// Returns `true` if at least one slot was updated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the wheel generates deltas (see [HawConf::with_deltas]), as the correction would not be
+    /// reflected in its checkpoints and replicas.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{Haw, NumericalDuration, aggregator::sum::U32SumAggregator};
+    ///
+    /// let mut haw: Haw<U32SumAggregator> = Haw::default();
+    /// haw.delta_advance(vec![Some(10), None, Some(50), None]);
+    ///
+    /// // correct the second [1000, 2000)
+    /// assert!(haw.apply_late(1500, 5));
+    /// assert_eq!(haw.interval(3.seconds()), Some(55));
+    /// ```
+    pub fn apply_late(&mut self, timestamp: u64, partial: A::PartialAggregate) -> bool {
+        assert!(
+            !self.conf.generate_deltas,
+            "Late corrections cannot be applied to a wheel generating deltas"
+        );
+        let mut applied = false;
+        let wheels = self.milliseconds_wheel.iter_mut().chain([
+            &mut self.seconds_wheel,
+            &mut self.minutes_wheel,
+            &mut self.hours_wheel,
+            &mut self.days_wheel,
+            &mut self.weeks_wheel,
+            &mut self.years_wheel,
+        ]);
+
+        for wheel in wheels {
+            match wheel.as_mut() {
+                // the timestamp has been rolled up into this granularity
+                Some(wheel) if timestamp < wheel.watermark() => {
+                    applied |= wheel.combine_at_time(timestamp, partial.clone());
+                }
+                _ => break,
+            }
+        }
+
+        if let Some(calendar) = self.calendar_wheel.as_mut() {
+            applied |= calendar.apply_late(timestamp, partial.clone());
+        }
+
+        for manager in self.window_managers.iter_mut() {
+            manager.apply_late(self.watermark, timestamp, partial.clone());
+        }
+
+        applied
+    }
+
+    /// Tick the wheel by a single unit (see [Self::tick_size_ms])
     ///
     /// In the worst case, a tick may cause a rotation of all the wheels in the hierarchy.
     #[inline]
@@ -1559,13 +2410,58 @@ where
         #[cfg(feature = "profiler")]
         profile_scope!(&self.stats.tick);
 
-        self.watermark += Self::SECOND_AS_MS;
+        self.watermark += self.tick_size_ms();
 
         // if 'None', insert the Identity value
         let partial = partial_opt.unwrap_or(A::IDENTITY);
-        let seconds = self.seconds_wheel.get_or_insert();
 
-        seconds.insert_head(partial);
+        if let Some(calendar) = self.calendar_wheel.as_mut() {
+            calendar.tick(partial.clone(), self.watermark);
+        }
+
+        match self.milliseconds_wheel.as_mut() {
+            Some(milliseconds) => {
+                let milliseconds = milliseconds.get_or_insert();
+                milliseconds.insert_head(partial);
+
+                // full rotation of milliseconds wheel
+                if let Some(rot_data) = milliseconds.tick() {
+                    // insert a second worth of partial aggregates into seconds wheel and then tick it
+                    self.seconds_wheel.get_or_insert().insert_slot(rot_data);
+                    self.tick_seconds();
+                }
+            }
+            None => {
+                self.seconds_wheel.get_or_insert().insert_head(partial);
+                self.tick_seconds();
+            }
+        }
+
+        // Fire any outgoing timers
+        #[cfg(feature = "timer")]
+        {
+            let mut timer = self.timer.write();
+
+            for action in timer.advance_to(self.watermark) {
+                match action {
+                    TimerAction::Oneshot(udf) => {
+                        udf(self);
+                    }
+                    TimerAction::Repeat((at, interval, udf)) => {
+                        udf(self);
+                        let new_at = at + interval.whole_milliseconds() as u64;
+                        let _ =
+                            timer.schedule_at(new_at, TimerAction::Repeat((new_at, interval, udf)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Ticks the seconds wheel and rolls up aggregates on full rotations
+    #[inline]
+    fn tick_seconds(&mut self) {
+        let seconds = self.seconds_wheel.get_or_insert();
 
         // full rotation of seconds wheel
         if let Some(rot_data) = seconds.tick() {
@@ -1607,26 +2503,11 @@ where
                 }
             }
         }
+    }
 
-        // Fire any outgoing timers
-        #[cfg(feature = "timer")]
-        {
-            let mut timer = self.timer.write();
-
-            for action in timer.advance_to(self.watermark) {
-                match action {
-                    TimerAction::Oneshot(udf) => {
-                        udf(self);
-                    }
-                    TimerAction::Repeat((at, interval, udf)) => {
-                        udf(self);
-                        let new_at = at + interval.whole_milliseconds() as u64;
-                        let _ =
-                            timer.schedule_at(new_at, TimerAction::Repeat((new_at, interval, udf)));
-                    }
-                }
-            }
-        }
+    /// Returns a reference to the milliseconds wheel if configured
+    pub fn milliseconds(&self) -> Option<&Wheel<A>> {
+        self.milliseconds_wheel.as_ref().and_then(|w| w.as_ref())
     }
 
     /// Returns a reference to the seconds wheel
@@ -1682,14 +2563,16 @@ where
         self.years_wheel.as_ref().unwrap()
     }
 
-    /// Merges two wheels
-    ///
-    /// Note that the time in `other` may be advanced and thus change state
+    /// Merges another wheel into this one
     ///
-    /// # Panics
+    /// The wheel that lags behind is first advanced so that both wheels share the same watermark.
+    /// Each level is then merged slot by slot for the time both wheels retain, which allows the
+    /// wheels to use different data layouts and retention policies. Slots that cannot be merged,
+    /// for instance because a level only exists in `other` or has a different tick size, are
+    /// listed in the returned [MergeReport].
     ///
-    /// The function currently panics if another data layout than Array is used.
-    pub(crate) fn merge(&mut self, other: &mut Self) {
+    /// Note that the time in `other` may be advanced and thus change state
+    pub(crate) fn merge(&mut self, other: &mut Self) -> MergeReport {
         let other_watermark = other.watermark();
 
         // make sure both wheels are aligned by time
@@ -1699,13 +2582,76 @@ where
             self.advance_to(other_watermark, &mut WriterWheel::default());
         }
 
+        let mut report = MergeReport::new(self.watermark());
+
         // merge all aggregation wheels
-        self.seconds_wheel.merge(&other.seconds_wheel);
-        self.minutes_wheel.merge(&other.minutes_wheel);
-        self.hours_wheel.merge(&other.hours_wheel);
-        self.days_wheel.merge(&other.days_wheel);
-        self.weeks_wheel.merge(&other.weeks_wheel);
-        self.years_wheel.merge(&other.years_wheel);
+        if let Some(other_milliseconds) = other.milliseconds_wheel.as_ref() {
+            merge_level(
+                HawLevel::Milliseconds,
+                self.milliseconds_wheel
+                    .as_mut()
+                    .and_then(|wheel| wheel.as_mut()),
+                other_milliseconds.as_ref(),
+                &mut report,
+            );
+        }
+        let levels = [
+            (
+                HawLevel::Seconds,
+                &mut self.seconds_wheel,
+                &other.seconds_wheel,
+            ),
+            (
+                HawLevel::Minutes,
+                &mut self.minutes_wheel,
+                &other.minutes_wheel,
+            ),
+            (HawLevel::Hours, &mut self.hours_wheel, &other.hours_wheel),
+            (HawLevel::Days, &mut self.days_wheel, &other.days_wheel),
+            (HawLevel::Weeks, &mut self.weeks_wheel, &other.weeks_wheel),
+            (HawLevel::Years, &mut self.years_wheel, &other.years_wheel),
+        ];
+        for (level, wheel, other_wheel) in levels {
+            merge_level(level, wheel.as_mut(), other_wheel.as_ref(), &mut report);
+        }
+
+        if let Some(other_calendar) = other.calendar_wheel.as_ref() {
+            let (months, quarters) = self
+                .calendar_wheel
+                .as_mut()
+                .and_then(|calendar| calendar.merge(other_calendar))
+                .unwrap_or((0..0, 0..0));
+
+            let month_span = |slots: Range<usize>| {
+                let (start_ms, end_ms) = other_calendar.months_span(&slots);
+                MergeSpan {
+                    level: HawLevel::Months,
+                    start_ms,
+                    end_ms,
+                    slots: slots.len(),
+                }
+            };
+            report.record(month_span(months.clone()), true);
+            report.record(month_span(0..months.start), false);
+            report.record(month_span(months.end..other_calendar.len()), false);
+
+            let quarter_span = |slots: Range<usize>| {
+                let (start_ms, end_ms) = other_calendar.quarters_span(&slots);
+                MergeSpan {
+                    level: HawLevel::Quarters,
+                    start_ms,
+                    end_ms,
+                    slots: slots.len(),
+                }
+            };
+            report.record(quarter_span(quarters.clone()), true);
+            report.record(quarter_span(0..quarters.start), false);
+            report.record(
+                quarter_span(quarters.end..other_calendar.quarters_len()),
+                false,
+            );
+        }
+        report
     }
     #[cfg(feature = "profiler")]
     /// Returns a reference to the stats of the [Haw]
@@ -1714,6 +2660,20 @@ where
     }
 }
 
+// Merges a single level of `other` into `wheel` and records the outcome
+fn merge_level<A: Aggregator>(
+    level: HawLevel,
+    wheel: Option<&mut Wheel<A>>,
+    other: Option<&Wheel<A>>,
+    report: &mut MergeReport,
+) {
+    let Some(other) = other else {
+        return;
+    };
+    let merged = wheel.and_then(|wheel| wheel.merge(other)).unwrap_or(0..0);
+    report.record_wheel(level, other, merged);
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -1744,6 +2704,152 @@ mod tests {
         assert_eq!(haw.interval(4.seconds()), Some(60));
     }
 
+    #[test]
+    fn try_combine_range_test() {
+        let mut haw: Haw<U64SumAggregator> = Haw::default();
+        haw.delta_advance((0..2 * 3600).map(|_| Some(1)));
+        let watermark = haw.watermark();
+
+        let result = haw
+            .try_combine_range(WheelRange::new_unchecked(0, watermark))
+            .unwrap();
+        assert_eq!(result.aggregate, 7200);
+        assert_eq!(result.watermark, watermark);
+        assert!(result.is_complete());
+
+        assert_eq!(
+            haw.try_combine_range(WheelRange::new_unchecked(0, watermark + 1000)),
+            Err(QueryError::BeyondWatermark {
+                end_ms: watermark + 1000,
+                watermark
+            })
+        );
+        assert_eq!(
+            haw.try_combine_range(WheelRange::new_unchecked(5000, 5000)),
+            Err(QueryError::EmptyRange {
+                start_ms: 5000,
+                end_ms: 5000
+            })
+        );
+        // the seconds have been rolled up into minutes and dropped
+        assert_eq!(
+            haw.try_combine_range(WheelRange::new_unchecked(1000, 2000)),
+            Err(QueryError::Unaligned {
+                start_ms: 1000,
+                end_ms: 2000
+            })
+        );
+
+        haw.clear();
+        assert_eq!(haw.retained_start(), watermark);
+        assert_eq!(
+            haw.try_combine_range(WheelRange::new_unchecked(0, 60000)),
+            Err(QueryError::Evicted {
+                start_ms: 0,
+                retained_ms: watermark
+            })
+        );
+    }
+
+    #[test]
+    fn combine_range_clamped_test() {
+        let start = HOUR_TICK_MS;
+        let mut haw: Haw<U64SumAggregator> = Haw::new(HawConf::default().with_watermark(start));
+        haw.delta_advance((0..2 * 3600).map(|_| Some(1)));
+        let watermark = haw.watermark();
+
+        // "last 3 hours" on a wheel with only 2 hours of data
+        let requested = WheelRange::new_unchecked(watermark - 3 * HOUR_TICK_MS, watermark);
+        let result = haw.combine_range_clamped(requested).unwrap();
+        assert_eq!(result.aggregate, 7200);
+        assert_eq!(result.requested, requested);
+        assert_eq!(result.covered, WheelRange::new_unchecked(start, watermark));
+        assert!(!result.is_complete());
+
+        // reaches into the future
+        let result = haw
+            .combine_range_clamped(WheelRange::new_unchecked(
+                watermark - 10000,
+                watermark + 10000,
+            ))
+            .unwrap();
+        assert_eq!(result.aggregate, 10);
+        assert_eq!(result.covered.end_ms(), watermark);
+
+        // the seconds and minutes at the start of the range have been dropped
+        let requested =
+            WheelRange::new_unchecked(watermark - 90 * MINUTE_TICK_MS - 30000, watermark);
+        assert!(haw.try_combine_range(requested).is_err());
+        let result = haw.combine_range_clamped(requested).unwrap();
+        assert_eq!(result.aggregate, 3600);
+        assert_eq!(
+            result.covered,
+            WheelRange::new_unchecked(watermark - HOUR_TICK_MS, watermark)
+        );
+
+        assert_eq!(
+            haw.combine_range_clamped(WheelRange::new_unchecked(watermark, watermark + 1000)),
+            Err(QueryError::BeyondWatermark {
+                end_ms: watermark + 1000,
+                watermark
+            })
+        );
+    }
+
+    #[test]
+    fn delta_checkpoint_test() {
+        let conf = HawConf::default()
+            .with_watermark(1000)
+            .with_milliseconds(500.milliseconds())
+            .with_deltas();
+        let mut haw: Haw<U64SumAggregator> = Haw::new(conf);
+        assert!(Haw::<U64SumAggregator>::default()
+            .delta_checkpoint(0)
+            .is_none());
+
+        haw.delta_advance(vec![Some(1), Some(2), None, Some(4)]);
+        assert_eq!(haw.watermark(), 3000);
+
+        // misaligned or out of bounds checkpoints
+        assert!(haw.delta_checkpoint(1200).is_none());
+        assert!(haw.delta_checkpoint(500).is_none());
+        assert!(haw.delta_checkpoint(3500).is_none());
+
+        let first = haw.delta_checkpoint(2000).unwrap();
+        assert_eq!(first.oldest_ts, 2000);
+        assert_eq!(first.deltas, vec![None, Some(4)]);
+        // compacted up to the current watermark
+        assert!(haw.delta_checkpoint(2000).is_none());
+        assert!(haw.delta_state().deltas.is_empty());
+
+        haw.delta_advance(vec![Some(8)]);
+        let second = haw.delta_checkpoint(3000).unwrap();
+        assert_eq!(second.deltas, vec![Some(8)]);
+
+        let mut replica: Haw<U64SumAggregator> = Haw::new(
+            HawConf::default()
+                .with_watermark(3000)
+                .with_milliseconds(500.milliseconds()),
+        );
+        assert_eq!(
+            replica.apply_checkpoints([second.clone(), first.clone()]),
+            Ok(1)
+        );
+        assert_eq!(
+            replica.apply_checkpoints([DeltaState::new(5000, vec![Some(1)])]),
+            Err(CheckpointError::Gap {
+                watermark: 3500,
+                checkpoint: 5000
+            })
+        );
+        assert_eq!(
+            replica.apply_checkpoints([DeltaState::new(3200, vec![Some(1)])]),
+            Err(CheckpointError::Misaligned { checkpoint: 3200 })
+        );
+        assert_eq!(replica.watermark(), 3500);
+        assert_eq!(replica.landmark(), Some(8));
+    }
+
     #[test]
     fn wheel_range_test() {
         let start = datetime!(2023 - 11 - 09 00:00:00 UTC);
@@ -1814,6 +2920,205 @@ mod tests {
         );
     }
 
+    #[test]
+    fn range_query_millis_test() {
+        // 2023-11-09 00:00:00
+        let watermark = 1699488000000;
+        let conf = HawConf::default()
+            .with_watermark(watermark)
+            .with_milliseconds(100.milliseconds());
+        let mut haw: Haw<U64SumAggregator> = Haw::new(conf);
+        assert_eq!(haw.tick_size_ms(), 100);
+
+        // 1 per 100ms for 2.5 seconds
+        let deltas: Vec<Option<u64>> = (0..25).map(|_| Some(1)).collect();
+        haw.delta_advance(deltas);
+
+        assert_eq!(haw.watermark(), watermark + 2500);
+        assert_eq!(haw.interval(500.milliseconds()), Some(5));
+        assert_eq!(haw.interval(900.milliseconds()), Some(9));
+        assert_eq!(haw.interval(2500.milliseconds()), Some(25));
+        assert_eq!(haw.landmark(), Some(25));
+
+        let start = datetime!(2023-11-09 00:00:02 UTC);
+        let end = datetime!(2023-11-09 00:00:02.3 UTC);
+        assert_eq!(haw.combine_range(WheelRange { start, end }), Some(3));
+        assert_eq!(
+            haw.range(WheelRange { start, end }),
+            Some(vec![
+                (1699488002000, 1),
+                (1699488002100, 1),
+                (1699488002200, 1)
+            ])
+        );
+
+        // combines seconds and milliseconds wheels
+        let range = WheelRange::new_unchecked(watermark, watermark + 2300);
+        assert_eq!(haw.combine_range(range), Some(23));
+
+        let range = WheelRange::new_unchecked(watermark + 1600, watermark + 2500);
+        assert_eq!(haw.combine_range(range), Some(9));
+
+        let range = WheelRange::new_unchecked(watermark + 2000, watermark + 2400);
+        assert_eq!(
+            haw.group_by(range, 200.milliseconds()),
+            Some(vec![(1699488002000, 2), (1699488002200, 2)])
+        );
+    }
+
+    #[test]
+    fn range_query_without_millis_test() {
+        // 2023-11-09 00:00:00
+        let watermark = 1699488000000;
+        let conf = HawConf::default().with_watermark(watermark);
+        let mut haw: Haw<U64SumAggregator> = Haw::new(conf);
+        let deltas = vec![Some(10), Some(20), Some(30)];
+        haw.delta_advance(deltas);
+
+        // sub-second precision is truncated if no milliseconds wheel is configured
+        let range = WheelRange::new_unchecked(watermark + 500, watermark + 2500);
+        assert_eq!(haw.combine_range(range), Some(30));
+        assert!(haw.milliseconds().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_millis_tick_test() {
+        let _conf = HawConf::default().with_milliseconds(300.milliseconds());
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_late_with_deltas_test() {
+        let mut haw: Haw<U64SumAggregator> = Haw::new(HawConf::default().with_deltas());
+        haw.delta_advance(vec![Some(1), Some(2)]);
+        haw.apply_late(500, 100);
+    }
+
+    #[test]
+    fn calendar_month_test() {
+        // 2024-01-31 00:00:00
+        let watermark = 1706659200000;
+        let conf = HawConf::default()
+            .with_watermark(watermark)
+            .with_calendar(CalendarConf::default());
+        let mut haw: Haw<U64SumAggregator> = Haw::new(conf);
+
+        // 1 per minute from 2024-01-31 until 2024-03-02
+        let minutes = 31 * 24 * 60;
+        let deltas: Vec<Option<u64>> = (0..minutes * 60)
+            .map(|i| (i % 60 == 0).then_some(1))
+            .collect();
+        haw.delta_advance(deltas);
+
+        let feb = datetime!(2024-02-01 00:00:00 UTC);
+        let mar = datetime!(2024-03-01 00:00:00 UTC);
+        let feb_range = WheelRange {
+            start: feb,
+            end: mar,
+        };
+        // February 2024 has 29 days
+        assert!(matches!(
+            haw.explain_combine_range(feb_range),
+            Some(ExecutionPlan::CalendarAggregation(_))
+        ));
+        assert_eq!(haw.combine_range(feb_range), Some(29 * 24 * 60));
+
+        // exact across the month boundary
+        let range = WheelRange {
+            start: datetime!(2024-01-01 00:00:00 UTC),
+            end: mar,
+        };
+        assert_eq!(haw.combine_range(range), Some(30 * 24 * 60));
+
+        assert_eq!(
+            haw.group_by_calendar(range, CalendarUnit::Month),
+            Some(vec![
+                (1704067200000, 24 * 60),
+                (1706745600000, 29 * 24 * 60)
+            ])
+        );
+        // Q1 is still ongoing and thus answered by the wheel hierarchy
+        assert_eq!(
+            haw.group_by_calendar(range, CalendarUnit::Quarter),
+            Some(vec![(1704067200000, 31 * 24 * 60)])
+        );
+
+        // the range start must be aligned to the calendar unit
+        let range = WheelRange {
+            start: feb,
+            end: mar,
+        };
+        assert_eq!(haw.group_by_calendar(range, CalendarUnit::Quarter), None);
+
+        // late data is applied to the calendar months as well
+        assert!(haw.apply_late(feb.unix_timestamp() as u64 * 1000, 10));
+        assert_eq!(haw.combine_range(feb_range), Some(29 * 24 * 60 + 10));
+    }
+
+    #[test]
+    fn utc_offset_test() {
+        // 2023-11-09 00:00:00 +02:00
+        let local_midnight = datetime!(2023-11-09 00:00:00 +2);
+        let watermark = to_unix_ms(local_midnight);
+        let conf = HawConf::default()
+            .with_watermark(watermark)
+            .with_utc_offset(2 * 3600);
+        let mut haw: Haw<U64SumAggregator> = Haw::new(conf);
+
+        // 1 per hour for 3 days
+        let deltas: Vec<Option<u64>> = (0..3 * 24 * 3600)
+            .map(|i| (i % 3600 == 0).then_some(1))
+            .collect();
+        haw.delta_advance(deltas);
+
+        // a local day is answered by a single slot in the days wheel
+        let day = WheelRange {
+            start: datetime!(2023-11-10 00:00:00 +2),
+            end: datetime!(2023-11-11 00:00:00 +2),
+        };
+        match haw.explain_combine_range(day) {
+            Some(ExecutionPlan::WheelAggregation(agg)) => {
+                assert_eq!(agg.granularity, Granularity::Day);
+                assert_eq!(agg.cost(), 1);
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+        assert_eq!(haw.combine_range(day), Some(24));
+
+        // ranges given in UTC are interpreted in local time as well
+        let range = WheelRange::new_unchecked(watermark, watermark + 2 * DAY_TICK_MS);
+        assert_eq!(haw.combine_range(range), Some(48));
+
+        let range = WheelRange {
+            start: local_midnight,
+            end: datetime!(2023-11-12 00:00:00 +2),
+        };
+        assert_eq!(
+            haw.group_by(range, 1.days()),
+            Some(vec![
+                (watermark, 24),
+                (watermark + DAY_TICK_MS, 24),
+                (watermark + 2 * DAY_TICK_MS, 24)
+            ])
+        );
+
+        // without an offset the local day is not aligned to the days wheel
+        let conf = HawConf::default().with_watermark(watermark);
+        let mut haw: Haw<U64SumAggregator> = Haw::new(conf);
+        haw.delta_advance((0..3 * 24 * 3600).map(|i| (i % 3600 == 0).then_some(1)));
+        assert!(!matches!(
+            haw.explain_combine_range(day),
+            Some(ExecutionPlan::WheelAggregation(agg)) if agg.granularity == Granularity::Day
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_utc_offset_test() {
+        let _conf = HawConf::default().with_utc_offset(26 * 3600);
+    }
+
     #[test]
     fn range_query_min_test() {
         // 2023-11-09 00:00:00
@@ -0,0 +1,116 @@
+use core::ops::Range;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use super::aggregation::Wheel;
+use crate::aggregator::Aggregator;
+
+/// A level of a [Haw](super::Haw)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HawLevel {
+    /// The optional milliseconds wheel
+    Milliseconds,
+    /// The seconds wheel
+    Seconds,
+    /// The minutes wheel
+    Minutes,
+    /// The hours wheel
+    Hours,
+    /// The days wheel
+    Days,
+    /// The weeks wheel
+    Weeks,
+    /// The years wheel
+    Years,
+    /// Calendar-aligned months
+    Months,
+    /// Calendar-aligned quarters
+    Quarters,
+}
+
+/// A span of slots within a single level of a wheel
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MergeSpan {
+    /// The level the slots belong to
+    pub level: HawLevel,
+    /// Start of the span in milliseconds (inclusive)
+    pub start_ms: u64,
+    /// End of the span in milliseconds (exclusive)
+    pub end_ms: u64,
+    /// Number of slots covered by the span
+    pub slots: usize,
+}
+
+/// The outcome of merging one [Haw](super::Haw) into another
+///
+/// Both wheels are first aligned to the same watermark. Slots are then merged level by level
+/// for the time both wheels retain. Slots of the other wheel that this wheel cannot hold, for
+/// example due to a shorter retention policy or a level with a different tick size, are reported
+/// as unmerged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// The watermark both wheels were aligned to
+    pub watermark: u64,
+    /// Spans of the other wheel that were merged
+    pub merged: Vec<MergeSpan>,
+    /// Spans of the other wheel that could not be merged
+    pub unmerged: Vec<MergeSpan>,
+}
+
+impl MergeReport {
+    pub(crate) fn new(watermark: u64) -> Self {
+        Self {
+            watermark,
+            merged: Vec::new(),
+            unmerged: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if every slot of the other wheel was merged
+    pub fn is_complete(&self) -> bool {
+        self.unmerged.is_empty()
+    }
+
+    /// Returns the total number of merged slots
+    pub fn merged_slots(&self) -> usize {
+        self.merged.iter().map(|span| span.slots).sum()
+    }
+
+    /// Returns the total number of slots that could not be merged
+    pub fn unmerged_slots(&self) -> usize {
+        self.unmerged.iter().map(|span| span.slots).sum()
+    }
+
+    /// Records the outcome of merging the slots in `merged` of `other`
+    pub(crate) fn record_wheel<A: Aggregator>(
+        &mut self,
+        level: HawLevel,
+        other: &Wheel<A>,
+        merged: Range<usize>,
+    ) {
+        let tick = other.tick_size_ms();
+        let watermark = other.watermark();
+        let span = |slots: Range<usize>| MergeSpan {
+            level,
+            start_ms: watermark.saturating_sub(slots.end as u64 * tick),
+            end_ms: watermark.saturating_sub(slots.start as u64 * tick),
+            slots: slots.len(),
+        };
+        self.record(span(merged.clone()), true);
+        self.record(span(0..merged.start), false);
+        self.record(span(merged.end..other.len()), false);
+    }
+
+    /// Records a span of slots as merged or unmerged
+    pub(crate) fn record(&mut self, span: MergeSpan, merged: bool) {
+        if span.slots == 0 {
+            return;
+        }
+        if merged {
+            self.merged.push(span);
+        } else {
+            self.unmerged.push(span);
+        }
+    }
+}
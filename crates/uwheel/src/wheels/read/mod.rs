@@ -2,26 +2,33 @@
 ///
 /// This is the core data structure that is reused between different hierarchies (e.g., seconds, minutes, hours, days)
 pub mod aggregation;
+/// This module contains calendar-aligned rollups of months and quarters.
+pub mod calendar;
 /// This module contains the Hierarchical Aggregate Wheel (HAW).
 pub mod hierarchical;
 
+mod merge;
 mod plan;
+mod query;
 
 #[cfg(feature = "profiler")]
 pub(crate) mod stats;
 #[cfg(feature = "timer")]
-use crate::wheels::timer::{TimerAction, TimerError};
+use crate::wheels::timer::{TimerAction, TimerError, TimerFn};
 
 use crate::{
     cfg_not_sync,
     cfg_sync,
-    delta::DeltaState,
+    delta::{CheckpointError, DeltaState},
     duration::Duration,
-    window::WindowAggregate,
+    window::{WindowAggregate, WindowId},
+    CalendarUnit,
     WheelRange,
 };
 pub use hierarchical::{Haw, DAYS, HOURS, MINUTES, SECONDS, WEEKS, YEARS};
+pub use merge::{HawLevel, MergeReport, MergeSpan};
 pub use plan::ExecutionPlan;
+pub use query::{QueryError, QueryResult};
 
 use crate::aggregator::Aggregator;
 
@@ -74,6 +81,48 @@ where
     pub fn delta_state(&self) -> DeltaState<A::PartialAggregate> {
         self.inner.read().delta_state()
     }
+
+    /// Returns the deltas generated since the given checkpoint and compacts them
+    ///
+    /// See [`Haw::delta_checkpoint`] for more information.
+    pub fn delta_checkpoint(&self, since: u64) -> Option<DeltaState<A::PartialAggregate>> {
+        self.inner.write().delta_checkpoint(since)
+    }
+
+    /// Applies a chain of incremental checkpoints on top of the wheel
+    ///
+    /// The wheel is typically restored from a base snapshot before applying the chain.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{aggregator::sum::U32SumAggregator, wheels::read::ReaderWheel, Haw, HawConf, NumericalDuration};
+    ///
+    /// let mut primary: Haw<U32SumAggregator> = Haw::new(HawConf::default().with_deltas());
+    /// primary.delta_advance(vec![Some(10), None]);
+    ///
+    /// // base state of the replica
+    /// let base = primary.delta_checkpoint(0).unwrap();
+    /// let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::from_delta_state(base.clone());
+    ///
+    /// primary.delta_advance(vec![Some(5)]);
+    /// let first = primary.delta_checkpoint(2000).unwrap();
+    /// primary.delta_advance(vec![Some(1), Some(2)]);
+    /// let second = primary.delta_checkpoint(3000).unwrap();
+    ///
+    /// // deltas already covered by the replica are skipped
+    /// assert_eq!(replica.apply_checkpoints([base, first, second]), Ok(3));
+    /// assert_eq!(replica.watermark(), primary.watermark());
+    /// assert_eq!(replica.interval(5.seconds()), Some(18));
+    /// ```
+    ///
+    /// See [`Haw::apply_checkpoints`] for more information.
+    pub fn apply_checkpoints(
+        &self,
+        chain: impl IntoIterator<Item = DeltaState<A::PartialAggregate>>,
+    ) -> Result<usize, CheckpointError> {
+        self.inner.write().apply_checkpoints(chain)
+    }
     /// Returns the number of wheel slots used
     pub fn len(&self) -> usize {
         self.inner.read().len()
@@ -93,6 +142,12 @@ where
     pub fn remaining_ticks(&self) -> u64 {
         self.inner.read().remaining_ticks()
     }
+
+    /// Returns the tick size of the wheel in milliseconds
+    #[inline]
+    pub fn tick_size_ms(&self) -> u64 {
+        self.inner.read().tick_size_ms()
+    }
     #[doc(hidden)]
     pub fn set_optimizer_hints(&self, hints: bool) {
         self.inner.write().set_optimizer_hints(hints);
@@ -129,7 +184,7 @@ where
     pub fn schedule_once(
         &self,
         at: u64,
-        f: impl Fn(&Haw<A>) + 'static,
+        f: impl TimerFn<A>,
     ) -> Result<(), TimerError<TimerAction<A>>> {
         self.inner.write().schedule_once(at, f)
     }
@@ -142,14 +197,19 @@ where
         &self,
         at: u64,
         interval: Duration,
-        f: impl Fn(&Haw<A>) + 'static,
+        f: impl TimerFn<A>,
     ) -> Result<(), TimerError<TimerAction<A>>> {
         self.inner.write().schedule_repeat(at, interval, f)
     }
 
     #[doc(hidden)]
-    pub fn window(&mut self, window: Window) {
-        self.inner.write().window(window);
+    pub fn window(&mut self, window: Window) -> WindowId {
+        self.inner.write().window(window)
+    }
+
+    #[doc(hidden)]
+    pub fn remove_window(&mut self, id: WindowId) -> bool {
+        self.inner.write().remove_window(id)
     }
 
     /// Advance the watermark of the wheel by the given [Duration]
@@ -181,6 +241,14 @@ where
         self.inner.write().delta_advance(deltas);
     }
 
+    /// Applies a late partial aggregate to the already-frozen slots covering the given timestamp
+    ///
+    /// See [`Haw::apply_late`] for more information.
+    #[inline]
+    pub fn apply_late(&self, timestamp: u64, partial: A::PartialAggregate) -> bool {
+        self.inner.write().apply_late(timestamp, partial)
+    }
+
     /// Clears the state of all wheels
     pub fn clear(&self) {
         self.inner.write().clear();
@@ -219,6 +287,28 @@ where
         self.inner.read().combine_range(range)
     }
 
+    /// Combines partial aggregates within the given date range [start, end) and describes the result
+    ///
+    /// See [`Haw::try_combine_range`] for more information.
+    #[inline]
+    pub fn try_combine_range(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> Result<QueryResult<A::PartialAggregate>, QueryError> {
+        self.inner.read().try_combine_range(range)
+    }
+
+    /// Combines partial aggregates within the part of the date range [start, end) that the wheel can answer
+    ///
+    /// See [`Haw::combine_range_clamped`] for more information.
+    #[inline]
+    pub fn combine_range_clamped(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> Result<QueryResult<A::PartialAggregate>, QueryError> {
+        self.inner.read().combine_range_clamped(range)
+    }
+
     /// Combines aggregates within the given date range [start, end) into a final partial aggregate
     ///
     /// Returns `None` if the range cannot be answered by the wheel
@@ -248,6 +338,18 @@ where
         self.inner.read().group_by(range, interval)
     }
 
+    /// Groups the data into aggregates based on the given range and calendar unit (month or quarter)
+    ///
+    /// See [`Haw::group_by_calendar`] for more information.
+    #[inline]
+    pub fn group_by_calendar(
+        &self,
+        range: WheelRange,
+        unit: CalendarUnit,
+    ) -> Option<Vec<(u64, A::Aggregate)>> {
+        self.inner.read().group_by_calendar(range, unit)
+    }
+
     /// Returns partial aggregates within the given date range [start, end) using the lowest granularity
     ///
     /// Returns `None` if the range cannot be answered by the wheel
@@ -279,9 +381,12 @@ where
         self.inner.read().landmark()
     }
     /// Merges another [ReaderWheel] into this one
+    ///
+    /// The wheel that lags behind is advanced to the watermark of the other before merging.
+    /// Returns a [MergeReport] describing which parts of `other` were merged and which were not.
     #[inline]
-    pub fn merge(&self, other: &Self) {
-        self.inner.write().merge(&mut other.inner.write());
+    pub fn merge(&self, other: &Self) -> MergeReport {
+        self.inner.write().merge(&mut other.inner.write())
     }
     /// Returns a reference to the internal [Haw] data structure
     pub fn as_ref(&self) -> HawRef<'_, A> {
@@ -26,6 +26,8 @@ pub enum ExecutionPlan {
     LandmarkAggregation,
     /// Execution can be queried through a combination of landmark window + inverse combine
     InverseLandmarkAggregation(WheelAggregations),
+    /// Execution consisting of calendar-aligned months and quarters
+    CalendarAggregation(CalendarAggregation),
 }
 
 impl ExecutionPlan {
@@ -47,6 +49,7 @@ impl ExecutionPlan {
             ExecutionPlan::InverseLandmarkAggregation(w) => {
                 w.iter().map(|m| m.cost()).sum::<usize>() + 6 + 2
             }
+            ExecutionPlan::CalendarAggregation(c) => c.cost(),
         }
     }
 }
@@ -102,6 +105,24 @@ impl WheelAggregation {
     }
 }
 
+/// A calendar-aligned aggregation over frozen months and quarters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarAggregation {
+    /// Absolute month number where the aggregation starts (inclusive)
+    pub(crate) start_month: i32,
+    /// Absolute month number where the aggregation ends (exclusive)
+    pub(crate) end_month: i32,
+    /// Number of months and quarters to combine
+    pub(crate) cost: usize,
+}
+
+impl CalendarAggregation {
+    /// Returns the cost of executing the Calendar Aggregation
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+}
+
 /// Aggregation method
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Aggregation {
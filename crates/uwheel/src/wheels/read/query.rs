@@ -0,0 +1,107 @@
+use core::fmt::{self, Display};
+
+use super::hierarchical::{RangeError, WheelRange};
+
+/// The result of a query together with the state it was computed at
+///
+/// Returned by [Haw::try_combine_range](super::Haw::try_combine_range).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct QueryResult<T> {
+    /// The result of the query
+    pub aggregate: T,
+    /// The watermark of the wheel when the query was executed
+    pub watermark: u64,
+    /// The range that was requested
+    pub requested: WheelRange,
+    /// The part of the requested range that the result covers
+    pub covered: WheelRange,
+}
+
+impl<T> QueryResult<T> {
+    /// Returns `true` if the result covers the whole requested range
+    pub fn is_complete(&self) -> bool {
+        self.requested == self.covered
+    }
+
+    /// Maps the result of the query while keeping its metadata
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> QueryResult<U> {
+        QueryResult {
+            aggregate: f(self.aggregate),
+            watermark: self.watermark,
+            requested: self.requested,
+            covered: self.covered,
+        }
+    }
+}
+
+/// Reasons why a query could not be answered
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// The range could not be created
+    Range(RangeError),
+    /// The range start is not before the range end
+    EmptyRange {
+        /// Start of the range as a unix timestamp in milliseconds
+        start_ms: u64,
+        /// End of the range as a unix timestamp in milliseconds
+        end_ms: u64,
+    },
+    /// The range ends beyond the watermark of the wheel
+    BeyondWatermark {
+        /// End of the range as a unix timestamp in milliseconds
+        end_ms: u64,
+        /// Current watermark of the wheel
+        watermark: u64,
+    },
+    /// The range starts before the oldest data retained by the wheel
+    Evicted {
+        /// Start of the range as a unix timestamp in milliseconds
+        start_ms: u64,
+        /// Oldest timestamp covered by a retained slot
+        retained_ms: u64,
+    },
+    /// The range boundaries do not line up with the slots that cover it
+    ///
+    /// For example, a range starting mid-minute can no longer be answered once the
+    /// seconds covering it have been rolled up into minutes and dropped.
+    Unaligned {
+        /// Start of the range as a unix timestamp in milliseconds
+        start_ms: u64,
+        /// End of the range as a unix timestamp in milliseconds
+        end_ms: u64,
+    },
+}
+
+impl From<RangeError> for QueryError {
+    fn from(err: RangeError) -> Self {
+        QueryError::Range(err)
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Range(err) => write!(f, "invalid range: {err}"),
+            QueryError::EmptyRange { start_ms, end_ms } => {
+                write!(f, "range start {start_ms} is not before range end {end_ms}")
+            }
+            QueryError::BeyondWatermark { end_ms, watermark } => {
+                write!(f, "range end {end_ms} is beyond the watermark {watermark}")
+            }
+            QueryError::Evicted {
+                start_ms,
+                retained_ms,
+            } => write!(
+                f,
+                "range start {start_ms} is older than the retained data starting at {retained_ms}"
+            ),
+            QueryError::Unaligned { start_ms, end_ms } => write!(
+                f,
+                "range [{start_ms}, {end_ms}) does not line up with the retained slots"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QueryError {}
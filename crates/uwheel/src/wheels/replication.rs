@@ -0,0 +1,651 @@
+use core::{
+    fmt::{self, Display},
+    marker::PhantomData,
+};
+use std::io::{self, Read, Write};
+
+use super::{read::ReaderWheel, snapshot::crc32};
+use crate::{aggregator::Aggregator, delta::CheckpointError, DeltaState};
+
+/// Magic bytes that prefix every replication frame
+pub const FRAME_MAGIC: [u8; 2] = *b"UW";
+
+// magic + sequence number + watermark + payload length + checksum
+const FRAME_PREFIX_LEN: usize = 2 + 8 + 8 + 4 + 4;
+
+/// A batch of deltas shipped from a sender to a receiver
+#[derive(Debug, Clone)]
+pub struct ReplicationBatch<T> {
+    /// Sequence number of the batch starting from 1
+    pub seq: u64,
+    /// Watermark of the sending wheel after the deltas of the batch
+    pub watermark: u64,
+    /// Deltas of the batch
+    pub state: DeltaState<T>,
+}
+
+/// Outcome of applying a received batch onto a replica
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ApplyOutcome {
+    /// The batch was applied onto the replica
+    Applied {
+        /// Sequence number of the batch
+        seq: u64,
+        /// Number of deltas applied
+        deltas: usize,
+    },
+    /// The batch has already been received and was ignored
+    Duplicate {
+        /// Sequence number of the batch
+        seq: u64,
+    },
+}
+
+/// Errors that may occur while shipping or receiving replication batches
+#[derive(Debug)]
+pub enum ReplicationError {
+    /// The underlying reader or writer failed
+    Io(io::Error),
+    /// A frame is corrupted or could not be decoded
+    Corrupted,
+    /// One or more batches between the last received and the given one are missing
+    Gap {
+        /// The expected sequence number
+        expected: u64,
+        /// The received sequence number
+        found: u64,
+    },
+    /// The deltas since the last shipped checkpoint are not available at the sender
+    ///
+    /// This happens if the wheel does not generate deltas or if they have been compacted elsewhere.
+    MissingDeltas {
+        /// Watermark of the last shipped checkpoint
+        checkpoint: u64,
+    },
+    /// The batch could not be applied onto the replica
+    Checkpoint(CheckpointError),
+    /// The watermark of the replica would differ from the watermark of the sender after applying a batch
+    ///
+    /// The batch is not applied.
+    WatermarkMismatch {
+        /// Watermark of the sender
+        expected: u64,
+        /// Watermark of the replica
+        found: u64,
+    },
+}
+
+impl Display for ReplicationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplicationError::Io(err) => write!(f, "replication io error: {err}"),
+            ReplicationError::Corrupted => write!(f, "corrupted replication frame"),
+            ReplicationError::Gap { expected, found } => {
+                write!(f, "expected batch {expected} but received {found}")
+            }
+            ReplicationError::MissingDeltas { checkpoint } => {
+                write!(f, "deltas since checkpoint {checkpoint} are not available")
+            }
+            ReplicationError::Checkpoint(err) => write!(f, "{err}"),
+            ReplicationError::WatermarkMismatch { expected, found } => write!(
+                f,
+                "replica watermark {found} differs from sender watermark {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplicationError {}
+
+impl From<io::Error> for ReplicationError {
+    fn from(err: io::Error) -> Self {
+        ReplicationError::Io(err)
+    }
+}
+
+impl From<CheckpointError> for ReplicationError {
+    fn from(err: CheckpointError) -> Self {
+        ReplicationError::Checkpoint(err)
+    }
+}
+
+/// Ships [DeltaState] batches of a wheel over any [Write]
+///
+/// Every batch is framed with a sequence number, the watermark of the wheel and a checksum.
+///
+/// Replicas only observe advancements of the wheel. Late corrections are therefore rejected by wheels
+/// that generate deltas (see [crate::HawConf::with_deltas]), which keeps replicas in sync with the primary.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{
+///     aggregator::sum::U32SumAggregator,
+///     replication::{ReplicationReceiver, ReplicationSender},
+///     wheels::read::ReaderWheel,
+///     Haw,
+///     HawConf,
+/// };
+///
+/// let primary: ReaderWheel<U32SumAggregator> = Haw::new(HawConf::default().with_deltas()).into();
+/// let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(0);
+///
+/// let mut sender = ReplicationSender::new(Vec::new(), primary.watermark());
+/// primary.delta_advance(vec![Some(10), None, Some(5)]);
+/// sender.ship(&primary).unwrap();
+///
+/// let mut receiver = ReplicationReceiver::new(sender.get_ref().as_slice());
+/// assert_eq!(receiver.sync(&replica).unwrap(), 1);
+/// assert_eq!(replica.watermark(), 3000);
+/// assert_eq!(replica.landmark(), Some(15));
+/// ```
+pub struct ReplicationSender<A: Aggregator, W: Write> {
+    writer: W,
+    /// Sequence number of the last sent batch
+    seq: u64,
+    /// Watermark of the last shipped checkpoint
+    checkpoint: u64,
+    /// Frame that failed to be written completely
+    pending: Option<PendingFrame>,
+    _marker: PhantomData<A>,
+}
+
+// An encoded batch and how many of its bytes have reached the writer
+struct PendingFrame {
+    seq: u64,
+    watermark: u64,
+    frame: Vec<u8>,
+    written: usize,
+}
+
+impl<A: Aggregator, W: Write> ReplicationSender<A, W> {
+    /// Creates a sender for a wheel whose replicas are in sync up to the given watermark
+    pub fn new(writer: W, watermark: u64) -> Self {
+        Self {
+            writer,
+            seq: 0,
+            checkpoint: watermark,
+            pending: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Ships the deltas generated by the wheel since the last shipped checkpoint
+    ///
+    /// The wheel must be configured to generate deltas (see [crate::HawConf::with_deltas]) and the shipped
+    /// deltas are compacted (see [crate::Haw::delta_checkpoint]). If writing a batch fails, possibly after
+    /// part of its frame has been written, the next call resumes writing the frame where it stopped before
+    /// taking a new checkpoint. Returns the sequence number of the sent batch or `None` if no new deltas
+    /// were generated.
+    pub fn ship(&mut self, wheel: &ReaderWheel<A>) -> Result<Option<u64>, ReplicationError> {
+        if self.pending.is_some() {
+            return self.write_pending().map(Some);
+        }
+        let state =
+            wheel
+                .delta_checkpoint(self.checkpoint)
+                .ok_or(ReplicationError::MissingDeltas {
+                    checkpoint: self.checkpoint,
+                })?;
+        if state.deltas.is_empty() {
+            return Ok(None);
+        }
+        self.write_batch(&state, wheel.watermark()).map(Some)
+    }
+
+    /// Sends a batch of deltas ending at the given watermark
+    ///
+    /// A batch that previously failed to be written completely is resumed first.
+    /// Returns the sequence number of the sent batch.
+    pub fn send(
+        &mut self,
+        state: DeltaState<A::PartialAggregate>,
+        watermark: u64,
+    ) -> Result<u64, ReplicationError> {
+        if self.pending.is_some() {
+            self.write_pending()?;
+        }
+        self.write_batch(&state, watermark)
+    }
+
+    fn write_batch(
+        &mut self,
+        state: &DeltaState<A::PartialAggregate>,
+        watermark: u64,
+    ) -> Result<u64, ReplicationError> {
+        let seq = self.seq + 1;
+        let payload = postcard::to_allocvec(state).expect("failed to encode delta state");
+
+        let mut frame = Vec::with_capacity(FRAME_PREFIX_LEN + payload.len());
+        frame.extend_from_slice(&FRAME_MAGIC);
+        frame.extend_from_slice(&seq.to_le_bytes());
+        frame.extend_from_slice(&watermark.to_le_bytes());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&(crc32(&frame[2..]) ^ crc32(&payload)).to_le_bytes());
+        frame.extend_from_slice(&payload);
+
+        self.pending = Some(PendingFrame {
+            seq,
+            watermark,
+            frame,
+            written: 0,
+        });
+        self.write_pending()
+    }
+
+    // Writes the remainder of the pending frame and returns its sequence number once complete
+    fn write_pending(&mut self) -> Result<u64, ReplicationError> {
+        let pending = self.pending.as_mut().expect("no pending frame");
+        while pending.written < pending.frame.len() {
+            match self.writer.write(&pending.frame[pending.written..]) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero).into()),
+                Ok(n) => pending.written += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        self.writer.flush()?;
+
+        let PendingFrame { seq, watermark, .. } = self.pending.take().unwrap();
+        self.seq = seq;
+        self.checkpoint = watermark;
+        Ok(seq)
+    }
+
+    /// Returns the sequence number of the last sent batch
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns a reference to the underlying writer
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consumes the sender and returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Receives [DeltaState] batches from a [ReplicationSender] over any [Read]
+///
+/// Duplicate batches are ignored while missing batches are reported as [ReplicationError::Gap].
+pub struct ReplicationReceiver<A: Aggregator, R: Read> {
+    reader: R,
+    /// Sequence number of the last received batch
+    seq: u64,
+    _marker: PhantomData<A>,
+}
+
+impl<A: Aggregator, R: Read> ReplicationReceiver<A, R> {
+    /// Creates a receiver that expects the first batch of a sender
+    pub fn new(reader: R) -> Self {
+        Self::with_seq(reader, 0)
+    }
+
+    /// Creates a receiver that has already received batches up to the given sequence number
+    pub fn with_seq(reader: R, seq: u64) -> Self {
+        Self {
+            reader,
+            seq,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reads the next batch
+    ///
+    /// Returns `None` once the reader reaches the end of the stream. Unlike [Self::apply], the batch is
+    /// returned without checking its sequence number.
+    pub fn recv(
+        &mut self,
+    ) -> Result<Option<ReplicationBatch<A::PartialAggregate>>, ReplicationError> {
+        let mut prefix = [0u8; FRAME_PREFIX_LEN];
+        if !read_frame(&mut self.reader, &mut prefix)? {
+            return Ok(None);
+        }
+        if prefix[..2] != FRAME_MAGIC {
+            return Err(ReplicationError::Corrupted);
+        }
+        let seq = u64::from_le_bytes(prefix[2..10].try_into().unwrap());
+        let watermark = u64::from_le_bytes(prefix[10..18].try_into().unwrap());
+        let len = u32::from_le_bytes(prefix[18..22].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(prefix[22..26].try_into().unwrap());
+
+        let mut payload = vec![0u8; len];
+        self.reader.read_exact(&mut payload)?;
+        if crc32(&prefix[2..22]) ^ crc32(&payload) != checksum {
+            return Err(ReplicationError::Corrupted);
+        }
+        let state = postcard::from_bytes(&payload).map_err(|_| ReplicationError::Corrupted)?;
+
+        Ok(Some(ReplicationBatch {
+            seq,
+            watermark,
+            state,
+        }))
+    }
+
+    /// Reads the next batch and applies it onto the replica
+    ///
+    /// Returns `None` once the reader reaches the end of the stream.
+    pub fn apply(
+        &mut self,
+        replica: &ReaderWheel<A>,
+    ) -> Result<Option<ApplyOutcome>, ReplicationError> {
+        let Some(batch) = self.recv()? else {
+            return Ok(None);
+        };
+
+        if batch.seq <= self.seq {
+            return Ok(Some(ApplyOutcome::Duplicate { seq: batch.seq }));
+        }
+        if batch.seq != self.seq + 1 {
+            return Err(ReplicationError::Gap {
+                expected: self.seq + 1,
+                found: batch.seq,
+            });
+        }
+
+        // validate the end watermark before touching the replica so that a rejected batch leaves it unchanged
+        let watermark = replica.watermark();
+        let end = batch.state.oldest_ts + batch.state.deltas.len() as u64 * replica.tick_size_ms();
+        if batch.state.oldest_ts <= watermark && watermark.max(end) != batch.watermark {
+            return Err(ReplicationError::WatermarkMismatch {
+                expected: batch.watermark,
+                found: watermark.max(end),
+            });
+        }
+        let deltas = replica.apply_checkpoints([batch.state])?;
+        self.seq = batch.seq;
+        Ok(Some(ApplyOutcome::Applied {
+            seq: batch.seq,
+            deltas,
+        }))
+    }
+
+    /// Applies batches onto the replica until the end of the stream
+    ///
+    /// Returns the number of applied batches.
+    pub fn sync(&mut self, replica: &ReaderWheel<A>) -> Result<usize, ReplicationError> {
+        let mut applied = 0;
+        while let Some(outcome) = self.apply(replica)? {
+            if let ApplyOutcome::Applied { .. } = outcome {
+                applied += 1;
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Returns the sequence number of the last applied batch
+    #[inline]
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Consumes the receiver and returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+// Fills the buffer with the next frame prefix and returns `false` if the stream ended before it
+fn read_frame(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aggregator::sum::U32SumAggregator, Haw, HawConf, NumericalDuration};
+
+    fn primary() -> ReaderWheel<U32SumAggregator> {
+        Haw::new(HawConf::default().with_watermark(10000).with_deltas()).into()
+    }
+
+    #[test]
+    fn replication_pipe_test() {
+        let primary = primary();
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(10000);
+        let (reader, writer) = io::pipe().unwrap();
+
+        let mut sender = ReplicationSender::new(writer, primary.watermark());
+        let mut receiver = ReplicationReceiver::new(reader);
+
+        // nothing to ship
+        assert_eq!(sender.ship(&primary).unwrap(), None);
+
+        primary.delta_advance(vec![Some(1), Some(2)]);
+        assert_eq!(sender.ship(&primary).unwrap(), Some(1));
+        primary.delta_advance(vec![None, Some(4)]);
+        assert_eq!(sender.ship(&primary).unwrap(), Some(2));
+
+        assert_eq!(
+            receiver.apply(&replica).unwrap(),
+            Some(ApplyOutcome::Applied { seq: 1, deltas: 2 })
+        );
+        assert_eq!(
+            receiver.apply(&replica).unwrap(),
+            Some(ApplyOutcome::Applied { seq: 2, deltas: 2 })
+        );
+        assert_eq!(receiver.seq(), 2);
+        assert_eq!(replica.watermark(), primary.watermark());
+        assert_eq!(replica.interval(4.seconds()), Some(7));
+
+        // closing the sender ends the stream
+        drop(sender);
+        assert_eq!(receiver.apply(&replica).unwrap(), None);
+    }
+
+    #[test]
+    fn replication_duplicate_and_gap_test() {
+        let mut sender: ReplicationSender<U32SumAggregator, _> =
+            ReplicationSender::new(Vec::new(), 10000);
+        sender
+            .send(DeltaState::new(10000, vec![Some(1)]), 11000)
+            .unwrap();
+        let first = sender.get_ref().clone();
+        sender
+            .send(DeltaState::new(11000, vec![Some(2)]), 12000)
+            .unwrap();
+        sender
+            .send(DeltaState::new(12000, vec![Some(3)]), 13000)
+            .unwrap();
+        let all = sender.into_inner();
+
+        // the first batch is delivered twice
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(10000);
+        let mut stream = first.clone();
+        stream.extend_from_slice(&all);
+        let mut receiver = ReplicationReceiver::new(stream.as_slice());
+        assert_eq!(
+            receiver.apply(&replica).unwrap(),
+            Some(ApplyOutcome::Applied { seq: 1, deltas: 1 })
+        );
+        assert_eq!(
+            receiver.apply(&replica).unwrap(),
+            Some(ApplyOutcome::Duplicate { seq: 1 })
+        );
+        assert_eq!(receiver.sync(&replica).unwrap(), 2);
+        assert_eq!(replica.landmark(), Some(6));
+
+        // the second batch is lost
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(10000);
+        let second_len = (all.len() - first.len()) / 2;
+        let mut stream = first.clone();
+        stream.extend_from_slice(&all[first.len() + second_len..]);
+        let mut receiver = ReplicationReceiver::new(stream.as_slice());
+        assert_eq!(
+            receiver.sync(&replica).unwrap_err().to_string(),
+            "expected batch 2 but received 3"
+        );
+        assert_eq!(replica.landmark(), Some(1));
+    }
+
+    #[test]
+    fn replication_corrupted_test() {
+        let mut sender: ReplicationSender<U32SumAggregator, _> =
+            ReplicationSender::new(Vec::new(), 0);
+        sender
+            .send(DeltaState::new(0, vec![Some(1)]), 1000)
+            .unwrap();
+        let bytes = sender.into_inner();
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(0);
+
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        let mut receiver = ReplicationReceiver::new(corrupted.as_slice());
+        assert!(matches!(
+            receiver.apply(&replica),
+            Err(ReplicationError::Corrupted)
+        ));
+
+        let truncated = &bytes[..bytes.len() - 1];
+        let mut receiver = ReplicationReceiver::new(truncated);
+        assert!(matches!(
+            receiver.apply(&replica),
+            Err(ReplicationError::Io(_))
+        ));
+
+        // the replica is behind the first batch
+        let behind: ReaderWheel<U32SumAggregator> = ReaderWheel::new(0);
+        let mut sender: ReplicationSender<U32SumAggregator, _> =
+            ReplicationSender::new(Vec::new(), 0);
+        sender
+            .send(DeltaState::new(5000, vec![Some(1)]), 6000)
+            .unwrap();
+        let bytes = sender.into_inner();
+        let mut receiver = ReplicationReceiver::new(bytes.as_slice());
+        assert!(matches!(
+            receiver.apply(&behind),
+            Err(ReplicationError::Checkpoint(CheckpointError::Gap { .. }))
+        ));
+    }
+
+    // A writer that fails once it has accepted `budget` bytes
+    struct FlakyWriter {
+        buf: Vec<u8>,
+        budget: usize,
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.budget == 0 {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            let len = buf.len().min(self.budget);
+            self.budget -= len;
+            self.buf.write(&buf[..len])
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn replication_retry_failed_write_test() {
+        let primary = primary();
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(10000);
+        let writer = FlakyWriter {
+            buf: Vec::new(),
+            budget: 0,
+        };
+        let mut sender = ReplicationSender::new(writer, primary.watermark());
+
+        primary.delta_advance(vec![Some(1), Some(2)]);
+        assert!(matches!(
+            sender.ship(&primary),
+            Err(ReplicationError::Io(_))
+        ));
+        assert_eq!(sender.seq(), 0);
+
+        // the pending batch is retried before the new deltas are shipped
+        primary.delta_advance(vec![Some(3)]);
+        sender.writer.budget = usize::MAX;
+        assert_eq!(sender.ship(&primary).unwrap(), Some(1));
+        assert_eq!(sender.ship(&primary).unwrap(), Some(2));
+        assert_eq!(sender.ship(&primary).unwrap(), None);
+
+        let bytes = sender.into_inner().buf;
+        let mut receiver = ReplicationReceiver::new(bytes.as_slice());
+        assert_eq!(receiver.sync(&replica).unwrap(), 2);
+        assert_eq!(replica.watermark(), primary.watermark());
+        assert_eq!(replica.landmark(), Some(6));
+    }
+
+    #[test]
+    fn replication_resume_partial_write_test() {
+        let primary = primary();
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(10000);
+        // fails in the middle of the first frame
+        let writer = FlakyWriter {
+            buf: Vec::new(),
+            budget: FRAME_PREFIX_LEN / 2,
+        };
+        let mut sender = ReplicationSender::new(writer, primary.watermark());
+
+        primary.delta_advance(vec![Some(1), Some(2)]);
+        assert!(matches!(
+            sender.ship(&primary),
+            Err(ReplicationError::Io(_))
+        ));
+        assert_eq!(sender.get_ref().buf.len(), FRAME_PREFIX_LEN / 2);
+
+        // the frame is resumed from where it stopped rather than written again
+        sender.writer.budget = 1;
+        assert!(sender
+            .send(DeltaState::new(12000, vec![Some(3)]), 13000)
+            .is_err());
+        sender.writer.budget = usize::MAX;
+        assert_eq!(
+            sender
+                .send(DeltaState::new(12000, vec![Some(3)]), 13000)
+                .unwrap(),
+            2
+        );
+        primary.delta_advance(vec![Some(3)]);
+
+        let bytes = sender.into_inner().buf;
+        let mut receiver = ReplicationReceiver::new(bytes.as_slice());
+        assert_eq!(receiver.sync(&replica).unwrap(), 2);
+        assert_eq!(replica.watermark(), primary.watermark());
+        assert_eq!(replica.landmark(), Some(6));
+    }
+
+    #[test]
+    fn replication_watermark_mismatch_test() {
+        let mut sender: ReplicationSender<U32SumAggregator, _> =
+            ReplicationSender::new(Vec::new(), 10000);
+        // two deltas cannot end at the announced watermark
+        sender
+            .send(DeltaState::new(10000, vec![Some(1), Some(2)]), 11000)
+            .unwrap();
+        let bytes = sender.into_inner();
+
+        let replica: ReaderWheel<U32SumAggregator> = ReaderWheel::new(10000);
+        let mut receiver = ReplicationReceiver::new(bytes.as_slice());
+        assert!(matches!(
+            receiver.apply(&replica),
+            Err(ReplicationError::WatermarkMismatch {
+                expected: 11000,
+                found: 12000
+            })
+        ));
+        assert_eq!(receiver.seq(), 0);
+        assert_eq!(replica.watermark(), 10000);
+        assert_eq!(replica.landmark(), None);
+    }
+}
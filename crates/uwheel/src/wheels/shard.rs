@@ -0,0 +1,274 @@
+use super::read::ReaderWheel;
+use crate::{aggregator::Aggregator, duration::Duration, WheelRange};
+use core::mem;
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+// Aggregates ordered by their timestamp
+type Timestamped<T> = Vec<(u64, T)>;
+
+/// The result of a query executed across a [ShardSet]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardResult<T> {
+    /// The combined result of all shards
+    pub value: T,
+    /// The lowest watermark among the shards
+    ///
+    /// Data above this watermark may only be reflected by some of the shards.
+    pub watermark: u64,
+}
+
+/// A set of [ReaderWheel]s that partition a stream and are queried as one
+///
+/// Queries are executed on every shard and the partial aggregates are combined into a single result.
+/// Query ranges are clamped to the lowest watermark of the set so that every shard covers the same
+/// time, and each result carries that watermark (see [ShardResult]). A query returns `None` if the
+/// set is empty or if any of the shards cannot answer it.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::sum::U32SumAggregator, Entry, NumericalDuration, RwWheel, ShardSet};
+///
+/// let mut shard_a: RwWheel<U32SumAggregator> = RwWheel::new(0);
+/// let mut shard_b: RwWheel<U32SumAggregator> = RwWheel::new(0);
+/// shard_a.insert(Entry::new(1, 1000));
+/// shard_b.insert(Entry::new(2, 2000));
+/// shard_a.advance(5.seconds());
+/// shard_b.advance(5.seconds());
+///
+/// let shards = ShardSet::from(vec![shard_a.read().clone(), shard_b.read().clone()]);
+/// let result = shards.interval(5.seconds()).unwrap();
+/// assert_eq!(result.value, 3);
+/// assert_eq!(result.watermark, 5000);
+/// ```
+#[derive(Clone)]
+pub struct ShardSet<A: Aggregator> {
+    shards: Vec<ReaderWheel<A>>,
+}
+
+impl<A: Aggregator> Default for ShardSet<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Aggregator> From<Vec<ReaderWheel<A>>> for ShardSet<A> {
+    fn from(shards: Vec<ReaderWheel<A>>) -> Self {
+        Self { shards }
+    }
+}
+
+impl<A: Aggregator> FromIterator<ReaderWheel<A>> for ShardSet<A> {
+    fn from_iter<I: IntoIterator<Item = ReaderWheel<A>>>(iter: I) -> Self {
+        Self {
+            shards: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<A: Aggregator> ShardSet<A> {
+    /// Creates an empty shard set
+    pub fn new() -> Self {
+        Self { shards: Vec::new() }
+    }
+
+    /// Adds a shard to the set
+    pub fn push(&mut self, shard: ReaderWheel<A>) {
+        self.shards.push(shard);
+    }
+
+    /// Returns the shards of the set
+    pub fn shards(&self) -> &[ReaderWheel<A>] {
+        &self.shards
+    }
+
+    /// Returns the number of shards
+    pub fn len(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns `true` if the set contains no shards
+    pub fn is_empty(&self) -> bool {
+        self.shards.is_empty()
+    }
+
+    /// Returns the lowest watermark among the shards or `None` if the set is empty
+    pub fn watermark(&self) -> Option<u64> {
+        self.shards.iter().map(ReaderWheel::watermark).min()
+    }
+
+    /// Returns the partial aggregate in the given time interval across all shards
+    ///
+    /// The interval ends at the lowest watermark of the set. If the shards are at different
+    /// watermarks, the interval is executed as a [Self::combine_range] query so that every shard
+    /// covers the same time.
+    pub fn interval(&self, dur: Duration) -> Option<ShardResult<A::PartialAggregate>> {
+        let watermark = self.watermark()?;
+        if self
+            .shards
+            .iter()
+            .all(|shard| shard.watermark() == watermark)
+        {
+            self.fold(watermark, |shard| shard.interval(dur))
+        } else {
+            let start = watermark.checked_sub(dur.whole_milliseconds() as u64)?;
+            let range = WheelRange::new(start, watermark).ok()?;
+            self.combine_range(range)
+        }
+    }
+
+    /// Combines partial aggregates within the given date range [start, end) across all shards
+    pub fn combine_range(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> Option<ShardResult<A::PartialAggregate>> {
+        let (range, watermark) = self.clamp(range.into())?;
+        self.fold(watermark, |shard| shard.combine_range(range))
+    }
+
+    /// Groups the data of all shards into aggregates based on the given range and interval
+    ///
+    /// Partial aggregates of the same group are combined across shards before being lowered.
+    pub fn group_by(
+        &self,
+        range: WheelRange,
+        interval: Duration,
+    ) -> Option<ShardResult<Timestamped<A::Aggregate>>> {
+        let (range, watermark) = self.clamp(range)?;
+        let groups =
+            self.merge_by_time(|shard| shard.as_ref().group_by_partial(range, interval))?;
+        Some(ShardResult {
+            value: groups
+                .into_iter()
+                .map(|(ts, partial)| (ts, A::lower(partial)))
+                .collect(),
+            watermark,
+        })
+    }
+
+    /// Returns partial aggregates within the given date range [start, end) across all shards
+    ///
+    /// Partial aggregates with the same timestamp are combined across shards.
+    pub fn range(
+        &self,
+        range: impl Into<WheelRange>,
+    ) -> Option<ShardResult<Timestamped<A::PartialAggregate>>> {
+        let (range, watermark) = self.clamp(range.into())?;
+        let value = self.merge_by_time(|shard| shard.range(range))?;
+        Some(ShardResult { value, watermark })
+    }
+
+    // Clamps the end of the range to the lowest watermark of the set
+    #[inline]
+    fn clamp(&self, range: WheelRange) -> Option<(WheelRange, u64)> {
+        let watermark = self.watermark()?;
+        let end = range.end_ms().min(watermark);
+        if range.start_ms() >= end {
+            return None;
+        }
+        Some((WheelRange::new(range.start_ms(), end).ok()?, watermark))
+    }
+
+    // Combines a single partial aggregate from each shard
+    #[inline]
+    fn fold(
+        &self,
+        watermark: u64,
+        query: impl Fn(&ReaderWheel<A>) -> Option<A::PartialAggregate>,
+    ) -> Option<ShardResult<A::PartialAggregate>> {
+        let value = self.shards.iter().try_fold(A::IDENTITY, |acc, shard| {
+            Some(A::combine(acc, query(shard)?))
+        })?;
+        Some(ShardResult { value, watermark })
+    }
+
+    // Combines timestamped partial aggregates from each shard ordered by time
+    #[inline]
+    fn merge_by_time(
+        &self,
+        query: impl Fn(&ReaderWheel<A>) -> Option<Timestamped<A::PartialAggregate>>,
+    ) -> Option<Timestamped<A::PartialAggregate>> {
+        let mut merged: BTreeMap<u64, A::PartialAggregate> = BTreeMap::new();
+        for shard in &self.shards {
+            for (ts, partial) in query(shard)? {
+                let entry = merged.entry(ts).or_insert(A::IDENTITY);
+                *entry = A::combine(mem::take(entry), partial);
+            }
+        }
+        Some(merged.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aggregator::sum::U32SumAggregator, duration::NumericalDuration, Entry, RwWheel};
+
+    fn shards(watermarks: &[u64]) -> (Vec<RwWheel<U32SumAggregator>>, ShardSet<U32SumAggregator>) {
+        let mut wheels = Vec::new();
+        for (i, watermark) in watermarks.iter().enumerate() {
+            let mut wheel = RwWheel::new(0);
+            for ts in (0..*watermark).step_by(1000) {
+                wheel.insert(Entry::new(i as u32 + 1, ts));
+            }
+            wheel.advance_to(*watermark);
+            wheels.push(wheel);
+        }
+        let set = wheels.iter().map(|wheel| wheel.read().clone()).collect();
+        (wheels, set)
+    }
+
+    #[test]
+    fn shard_set_query_test() {
+        let (_wheels, set) = shards(&[10000, 10000, 10000]);
+
+        let result = set.interval(5.seconds()).unwrap();
+        assert_eq!(result.value, 30);
+        assert_eq!(result.watermark, 10000);
+
+        let result = set
+            .combine_range(WheelRange::new_unchecked(0, 4000))
+            .unwrap();
+        assert_eq!(result.value, 24);
+
+        let result = set.range(WheelRange::new_unchecked(0, 3000)).unwrap();
+        assert_eq!(result.value, vec![(0, 6), (1000, 6), (2000, 6)]);
+
+        let result = set
+            .group_by(WheelRange::new_unchecked(0, 10000), 5.seconds())
+            .unwrap();
+        assert_eq!(result.value, vec![(0, 30), (5000, 30)]);
+    }
+
+    #[test]
+    fn shard_set_watermark_test() {
+        let (_wheels, set) = shards(&[10000, 8000]);
+        assert_eq!(set.watermark(), Some(8000));
+
+        // the interval ends at the lowest watermark for every shard
+        let result = set.interval(2.seconds()).unwrap();
+        assert_eq!(result.value, 6);
+        assert_eq!(result.watermark, 8000);
+
+        // ranges are clamped to the lowest watermark
+        let result = set
+            .combine_range(WheelRange::new_unchecked(6000, 10000))
+            .unwrap();
+        assert_eq!(result.value, 6);
+        assert!(set
+            .combine_range(WheelRange::new_unchecked(8000, 10000))
+            .is_none());
+    }
+
+    #[test]
+    fn shard_set_empty_test() {
+        let set: ShardSet<U32SumAggregator> = ShardSet::new();
+        assert!(set.is_empty());
+        assert_eq!(set.watermark(), None);
+        assert!(set.interval(1.seconds()).is_none());
+    }
+}
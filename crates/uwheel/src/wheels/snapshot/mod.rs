@@ -0,0 +1,472 @@
+use core::fmt::{self, Display};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::read::hierarchical::HawConf;
+use crate::aggregator::Aggregator;
+
+mod v1;
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+/// Magic bytes that prefix every µWheel snapshot
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"UWHL";
+
+/// The snapshot format version written by this version of µWheel
+///
+/// Snapshots written with an older format version can still be loaded.
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+// magic + version + header length + header checksum
+const PREFIX_LEN: usize = 4 + 2 + 4 + 4;
+
+/// The kind of wheel stored in a snapshot
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SnapshotKind {
+    /// A Hierarchical Aggregate Wheel ([crate::Haw])
+    Haw,
+    /// A Reader-Writer Wheel ([crate::RwWheel])
+    RwWheel,
+    /// A Reader-Writer Wheel with its write-ahead log position
+    Durable,
+}
+
+/// Header of a µWheel snapshot
+///
+/// The header may be inspected without decoding the wheel using [read_header].
+#[derive(Debug, Clone)]
+pub struct SnapshotHeader {
+    /// Format version of the snapshot
+    pub version: u16,
+    /// The kind of wheel stored in the snapshot
+    pub kind: SnapshotKind,
+    /// Name of the aggregator (see [Aggregator::name])
+    pub aggregator: String,
+    /// Size in bytes of the aggregator's partial aggregate
+    pub partial_size: u32,
+    /// Configuration of the Hierarchical Aggregate Wheel
+    pub conf: HawConf,
+    /// Length in bytes of the encoded wheel
+    pub body_len: u64,
+    /// CRC-32 checksum of the encoded wheel
+    pub body_checksum: u32,
+}
+
+/// Errors that may occur while loading a µWheel snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The bytes do not start with [SNAPSHOT_MAGIC]
+    InvalidMagic,
+    /// The snapshot was written with an unknown format version
+    UnsupportedVersion {
+        /// Format version found in the snapshot
+        version: u16,
+    },
+    /// The snapshot is shorter than what its header describes
+    Truncated,
+    /// The header checksum does not match its content
+    HeaderChecksumMismatch,
+    /// The wheel checksum does not match its content
+    BodyChecksumMismatch,
+    /// The snapshot contains a different kind of wheel
+    KindMismatch {
+        /// The kind that was requested
+        expected: SnapshotKind,
+        /// The kind stored in the snapshot
+        found: SnapshotKind,
+    },
+    /// The snapshot was written using a different aggregator
+    AggregatorMismatch {
+        /// Name of the requested aggregator
+        expected: String,
+        /// Name of the aggregator stored in the snapshot
+        found: String,
+    },
+    /// The header or wheel could not be decoded
+    Decode,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::InvalidMagic => write!(f, "not a µWheel snapshot"),
+            SnapshotError::UnsupportedVersion { version } => {
+                write!(f, "unsupported snapshot version {version}")
+            }
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::HeaderChecksumMismatch => write!(f, "snapshot header checksum mismatch"),
+            SnapshotError::BodyChecksumMismatch => write!(f, "snapshot body checksum mismatch"),
+            SnapshotError::KindMismatch { expected, found } => {
+                write!(f, "expected a {expected:?} snapshot but found {found:?}")
+            }
+            SnapshotError::AggregatorMismatch { expected, found } => {
+                write!(f, "expected aggregator {expected} but found {found}")
+            }
+            SnapshotError::Decode => write!(f, "snapshot could not be decoded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnapshotError {}
+
+/// Reads and validates the header of a snapshot without decoding the wheel
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::sum::U32SumAggregator, snapshot, Haw};
+///
+/// let haw: Haw<U32SumAggregator> = Haw::default();
+/// let bytes = haw.to_snapshot();
+/// let header = snapshot::read_header(&bytes).unwrap();
+/// assert_eq!(header.version, snapshot::SNAPSHOT_VERSION);
+/// assert_eq!(header.kind, snapshot::SnapshotKind::Haw);
+/// ```
+pub fn read_header(bytes: &[u8]) -> Result<SnapshotHeader, SnapshotError> {
+    split(bytes).map(|(header, _)| header)
+}
+
+/// A wheel that can be stored in a snapshot
+///
+/// Every format version has a frozen schema that the wheel is converted to and from, which keeps
+/// snapshots of older versions loadable after the wheel itself changes.
+pub(crate) trait Snapshot: Sized {
+    /// The schema of format version 1
+    type V1: Serialize + DeserializeOwned;
+
+    /// Converts the wheel into the schema of format version 1
+    fn to_v1(&self) -> Self::V1;
+
+    /// Restores the wheel from the schema of format version 1
+    fn from_v1(snapshot: Self::V1) -> Self;
+}
+
+/// Encodes a wheel converted by [Snapshot::to_v1] into a snapshot
+pub(crate) fn encode<A: Aggregator, T: Snapshot>(
+    kind: SnapshotKind,
+    conf: &HawConf,
+    wheel: &T::V1,
+) -> Vec<u8> {
+    let body = postcard::to_allocvec(wheel).expect("failed to encode wheel");
+    let header = v1::SnapshotHeaderV1 {
+        kind,
+        aggregator: A::name(),
+        partial_size: core::mem::size_of::<A::PartialAggregate>() as u32,
+        conf: conf.into(),
+        body_len: body.len() as u64,
+        body_checksum: crc32(&body),
+    };
+    let header_bytes = postcard::to_allocvec(&header).expect("failed to encode snapshot header");
+
+    let mut bytes = Vec::with_capacity(PREFIX_LEN + header_bytes.len() + body.len());
+    bytes.extend_from_slice(&SNAPSHOT_MAGIC);
+    bytes.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(header_bytes.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&crc32(&header_bytes).to_le_bytes());
+    bytes.extend_from_slice(&header_bytes);
+    bytes.extend_from_slice(&body);
+    bytes
+}
+
+/// Decodes a wheel from a snapshot after validating its header and checksums
+pub(crate) fn decode<A: Aggregator, T: Snapshot>(
+    kind: SnapshotKind,
+    bytes: &[u8],
+) -> Result<T, SnapshotError> {
+    let (header, body) = split(bytes)?;
+
+    if header.kind != kind {
+        return Err(SnapshotError::KindMismatch {
+            expected: kind,
+            found: header.kind,
+        });
+    }
+
+    let name = A::name();
+    if name != header.aggregator
+        || header.partial_size as usize != core::mem::size_of::<A::PartialAggregate>()
+    {
+        return Err(SnapshotError::AggregatorMismatch {
+            expected: name,
+            found: header.aggregator,
+        });
+    }
+
+    match header.version {
+        1 => postcard::from_bytes(body)
+            .map(T::from_v1)
+            .map_err(|_| SnapshotError::Decode),
+        version => Err(SnapshotError::UnsupportedVersion { version }),
+    }
+}
+
+// Validates the snapshot and splits it into its header and encoded wheel
+fn split(bytes: &[u8]) -> Result<(SnapshotHeader, &[u8]), SnapshotError> {
+    if bytes.len() < PREFIX_LEN {
+        return Err(if bytes.starts_with(&SNAPSHOT_MAGIC) {
+            SnapshotError::Truncated
+        } else {
+            SnapshotError::InvalidMagic
+        });
+    }
+    if bytes[..4] != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version == 0 || version > SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion { version });
+    }
+    let header_len = u32::from_le_bytes(bytes[6..10].try_into().unwrap()) as usize;
+    let header_checksum = u32::from_le_bytes(bytes[10..14].try_into().unwrap());
+
+    let rest = &bytes[PREFIX_LEN..];
+    if rest.len() < header_len {
+        return Err(SnapshotError::Truncated);
+    }
+    let (header_bytes, body) = rest.split_at(header_len);
+    if crc32(header_bytes) != header_checksum {
+        return Err(SnapshotError::HeaderChecksumMismatch);
+    }
+
+    // NOTE: versions that change the header must decode it using their own schema
+    let header: v1::SnapshotHeaderV1 =
+        postcard::from_bytes(header_bytes).map_err(|_| SnapshotError::Decode)?;
+    let header = SnapshotHeader {
+        version,
+        kind: header.kind,
+        aggregator: header.aggregator,
+        partial_size: header.partial_size,
+        conf: header.conf.into(),
+        body_len: header.body_len,
+        body_checksum: header.body_checksum,
+    };
+
+    if (body.len() as u64) < header.body_len {
+        return Err(SnapshotError::Truncated);
+    }
+    let body = &body[..header.body_len as usize];
+    if crc32(body) != header.body_checksum {
+        return Err(SnapshotError::BodyChecksumMismatch);
+    }
+
+    Ok((header, body))
+}
+
+// CRC-32 (IEEE 802.3) checksum
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        aggregator::{
+            adapter::{FilterAggregator, Predicate},
+            sum::{U32SumAggregator, U64SumAggregator},
+        },
+        wheels::read::ReaderWheel,
+        Entry,
+        Haw,
+        NumericalDuration,
+        RwWheel,
+        Window,
+    };
+
+    #[test]
+    fn crc32_test() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn haw_snapshot_test() {
+        let mut haw: Haw<U32SumAggregator> = Haw::new(HawConf::default().with_watermark(1000));
+        haw.delta_advance(vec![Some(10), None, Some(5)]);
+
+        let bytes = haw.to_snapshot();
+        let header = read_header(&bytes).unwrap();
+        assert_eq!(header.version, SNAPSHOT_VERSION);
+        assert_eq!(header.kind, SnapshotKind::Haw);
+        assert_eq!(header.conf.watermark, 1000);
+        assert_eq!(header.partial_size, 4);
+
+        let restored: Haw<U32SumAggregator> = Haw::from_snapshot(&bytes).unwrap();
+        assert_eq!(restored.watermark(), 4000);
+        assert_eq!(restored.interval(3.seconds()), Some(15));
+        assert_eq!(restored.landmark(), Some(15));
+    }
+
+    #[test]
+    fn incremental_checkpoint_test() {
+        let mut haw: Haw<U32SumAggregator> = Haw::new(HawConf::default().with_deltas());
+        haw.delta_advance(vec![Some(1), None]);
+
+        // base snapshot followed by a chain of incremental checkpoints
+        let base = haw.to_snapshot();
+        let mut chain = Vec::new();
+        let mut checkpoint = haw.watermark();
+        for delta in [Some(2), None, Some(3)] {
+            haw.delta_advance(vec![delta]);
+            chain.push(haw.delta_checkpoint(checkpoint).unwrap());
+            checkpoint = haw.watermark();
+        }
+
+        let replica = ReaderWheel::from(Haw::<U32SumAggregator>::from_snapshot(&base).unwrap());
+        assert_eq!(replica.apply_checkpoints(chain), Ok(3));
+        assert_eq!(replica.watermark(), haw.watermark());
+        assert_eq!(replica.landmark(), Some(6));
+        assert_eq!(replica.interval(2.seconds()), haw.interval(2.seconds()));
+    }
+
+    #[test]
+    fn rw_wheel_snapshot_test() {
+        let mut wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
+        wheel.insert(Entry::new(1, 500));
+        wheel.insert(Entry::new(2, 1500));
+        wheel.advance_to(1000);
+
+        let bytes = wheel.to_snapshot();
+        let mut restored: RwWheel<U32SumAggregator> = RwWheel::from_snapshot(&bytes).unwrap();
+        assert_eq!(restored.watermark(), 1000);
+        assert_eq!(restored.read().landmark(), Some(1));
+
+        // entries above the watermark are part of the snapshot
+        restored.advance_to(2000);
+        assert_eq!(restored.read().landmark(), Some(3));
+    }
+
+    #[test]
+    fn rw_wheel_window_snapshot_test() {
+        let mut wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
+        wheel.window(Window::sliding(10.seconds(), 3.seconds()));
+        wheel.insert(Entry::new(1, 500));
+        wheel.insert(Entry::new(2, 4500));
+        // far enough ahead to land in the overflow list of the writer timer
+        wheel.insert(Entry::new(3, 2_000_000));
+        wheel.advance_to(5000);
+
+        let bytes = wheel.to_snapshot();
+        let mut restored: RwWheel<U32SumAggregator> = RwWheel::from_snapshot(&bytes).unwrap();
+        assert_eq!(restored.to_snapshot(), bytes);
+
+        // the restored wheel emits the same windows as the original
+        assert_eq!(wheel.advance_to(30000), restored.advance_to(30000));
+        assert_eq!(wheel.advance_to(2_001_000), restored.advance_to(2_001_000));
+        assert_eq!(restored.read().landmark(), Some(6));
+    }
+
+    #[test]
+    fn snapshot_errors_test() {
+        let haw: Haw<U32SumAggregator> = Haw::default();
+        let bytes = haw.to_snapshot();
+
+        assert_eq!(
+            Haw::<U64SumAggregator>::from_snapshot(&bytes).err(),
+            Some(SnapshotError::AggregatorMismatch {
+                expected: "uwheel::aggregator::sum::U64SumAggregator".to_string(),
+                found: "uwheel::aggregator::sum::U32SumAggregator".to_string(),
+            })
+        );
+
+        // tuples are named after their inner aggregators
+        let tuple: Haw<(U32SumAggregator, U32SumAggregator)> = Haw::default();
+        let tuple_bytes = tuple.to_snapshot();
+        assert_eq!(
+            read_header(&tuple_bytes).unwrap().aggregator,
+            "(uwheel::aggregator::sum::U32SumAggregator, uwheel::aggregator::sum::U32SumAggregator)"
+        );
+        assert!(Haw::<(U32SumAggregator, U32SumAggregator)>::from_snapshot(&tuple_bytes).is_ok());
+        assert_eq!(
+            Haw::<U64SumAggregator>::from_snapshot(&tuple_bytes).err(),
+            Some(SnapshotError::AggregatorMismatch {
+                expected: "uwheel::aggregator::sum::U64SumAggregator".to_string(),
+                found: read_header(&tuple_bytes).unwrap().aggregator,
+            })
+        );
+
+        // adapters share the partial aggregate of the inner aggregator but not its name
+        #[derive(Default, Debug, Clone, Copy)]
+        struct Even;
+        impl Predicate<u32> for Even {
+            fn test(input: &u32) -> bool {
+                input % 2 == 0
+            }
+        }
+        type EvenSum = FilterAggregator<U32SumAggregator, Even>;
+        assert!(matches!(
+            Haw::<EvenSum>::from_snapshot(&bytes),
+            Err(SnapshotError::AggregatorMismatch { .. })
+        ));
+        let filtered = Haw::<EvenSum>::default().to_snapshot();
+        assert!(read_header(&filtered)
+            .unwrap()
+            .aggregator
+            .starts_with("uwheel::aggregator::adapter::FilterAggregator<uwheel::aggregator::sum::U32SumAggregator, "));
+        assert!(Haw::<EvenSum>::from_snapshot(&filtered).is_ok());
+
+        assert_eq!(
+            RwWheel::<U32SumAggregator>::from_snapshot(&bytes).err(),
+            Some(SnapshotError::KindMismatch {
+                expected: SnapshotKind::RwWheel,
+                found: SnapshotKind::Haw,
+            })
+        );
+        assert_eq!(
+            read_header(b"not a snapshot").unwrap_err(),
+            SnapshotError::InvalidMagic
+        );
+        assert_eq!(
+            read_header(&bytes[..bytes.len() - 1]).unwrap_err(),
+            SnapshotError::Truncated
+        );
+
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        assert_eq!(
+            read_header(&corrupted).unwrap_err(),
+            SnapshotError::BodyChecksumMismatch
+        );
+
+        let mut corrupted = bytes.clone();
+        corrupted[PREFIX_LEN] ^= 0xFF;
+        assert_eq!(
+            read_header(&corrupted).unwrap_err(),
+            SnapshotError::HeaderChecksumMismatch
+        );
+
+        let mut newer = bytes;
+        newer[4..6].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            read_header(&newer).unwrap_err(),
+            SnapshotError::UnsupportedVersion {
+                version: SNAPSHOT_VERSION + 1
+            }
+        );
+    }
+
+    // A snapshot written with format version 1 must keep loading after upgrades
+    #[test]
+    fn haw_snapshot_v1_compat_test() {
+        let bytes = include_bytes!("testdata/haw_v1.snapshot");
+        let header = read_header(bytes).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.conf.watermark, 1000);
+
+        let haw: Haw<U32SumAggregator> = Haw::from_snapshot(bytes).unwrap();
+        assert_eq!(haw.watermark(), 4000);
+        assert_eq!(haw.interval(3.seconds()), Some(15));
+    }
+}
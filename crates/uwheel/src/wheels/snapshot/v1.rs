@@ -0,0 +1,1163 @@
+//! Frozen schema of snapshot format version 1
+//!
+//! The types mirror the layout of the wheels at the time the format was introduced and must never change.
+//! Snapshots are decoded into these types first and then converted into the current wheel types, which
+//! keeps version 1 snapshots loadable however the wheels evolve.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_big_array::BigArray;
+
+use super::{Snapshot, SnapshotKind};
+use crate::{
+    aggregator::Aggregator,
+    duration::{Duration, Padding},
+    wheels::{
+        read::{
+            aggregation::{
+                conf::{DataLayout, RetentionPolicy, WheelConf, WheelMode},
+                data::Data,
+                deque::{CompressedDeque, MutablePartialDeque, PrefixDeque},
+                maybe::MaybeWheel,
+                Wheel,
+            },
+            calendar::{CalendarConf, CalendarWheel},
+            hierarchical::{HawConf, Heuristics, Optimizer},
+            Haw,
+            ReaderWheel,
+        },
+        timer::{
+            byte_wheel::{ByteWheel, WheelEntry},
+            quad_wheel::{OverflowEntry, PruneDecision, QuadWheelWithOverflow},
+            raw_wheel::RawTimerWheel,
+        },
+        write::{LatePolicy, WriterWheel},
+        RwWheel,
+    },
+    window::{
+        state::{SessionState, SlicingState},
+        util::PairType,
+        EmittedWindow,
+        SessionAggregator,
+        SlicingAggregator,
+        SubtractOnEvict,
+        TwoStacks,
+        Value,
+        Window,
+        WindowAggregate,
+        WindowAggregateKind,
+        WindowAggregator,
+        WindowId,
+        WindowManager,
+    },
+    DeltaState,
+    Entry,
+};
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SnapshotHeaderV1 {
+    pub kind: SnapshotKind,
+    pub aggregator: String,
+    pub partial_size: u32,
+    pub conf: HawConfV1,
+    pub body_len: u64,
+    pub body_checksum: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct HawConfV1 {
+    watermark: u64,
+    milliseconds: Option<WheelConfV1>,
+    seconds: WheelConfV1,
+    minutes: WheelConfV1,
+    hours: WheelConfV1,
+    days: WheelConfV1,
+    weeks: WheelConfV1,
+    years: WheelConfV1,
+    calendar: Option<CalendarConfV1>,
+    optimizer: OptimizerV1,
+    generate_deltas: bool,
+    allowed_lateness: DurationV1,
+    utc_offset: i32,
+}
+
+impl From<&HawConf> for HawConfV1 {
+    fn from(conf: &HawConf) -> Self {
+        Self {
+            watermark: conf.watermark,
+            milliseconds: conf.milliseconds.as_ref().map(Into::into),
+            seconds: (&conf.seconds).into(),
+            minutes: (&conf.minutes).into(),
+            hours: (&conf.hours).into(),
+            days: (&conf.days).into(),
+            weeks: (&conf.weeks).into(),
+            years: (&conf.years).into(),
+            calendar: conf.calendar.as_ref().map(Into::into),
+            optimizer: OptimizerV1 {
+                use_hints: conf.optimizer.use_hints,
+                simd_threshold: conf.optimizer.heuristics.simd_threshold,
+            },
+            generate_deltas: conf.generate_deltas,
+            allowed_lateness: (&conf.allowed_lateness).into(),
+            utc_offset: conf.utc_offset,
+        }
+    }
+}
+
+impl From<HawConfV1> for HawConf {
+    fn from(conf: HawConfV1) -> Self {
+        Self {
+            watermark: conf.watermark,
+            milliseconds: conf.milliseconds.map(Into::into),
+            seconds: conf.seconds.into(),
+            minutes: conf.minutes.into(),
+            hours: conf.hours.into(),
+            days: conf.days.into(),
+            weeks: conf.weeks.into(),
+            years: conf.years.into(),
+            calendar: conf.calendar.map(Into::into),
+            optimizer: Optimizer {
+                use_hints: conf.optimizer.use_hints,
+                heuristics: Heuristics {
+                    simd_threshold: conf.optimizer.simd_threshold,
+                },
+            },
+            generate_deltas: conf.generate_deltas,
+            allowed_lateness: conf.allowed_lateness.into(),
+            utc_offset: conf.utc_offset,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct OptimizerV1 {
+    use_hints: bool,
+    simd_threshold: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WheelConfV1 {
+    capacity: usize,
+    watermark: u64,
+    data_layout: DataLayoutV1,
+    tick_size_ms: u64,
+    retention: RetentionPolicyV1,
+    mode: WheelModeV1,
+}
+
+impl From<&WheelConf> for WheelConfV1 {
+    fn from(conf: &WheelConf) -> Self {
+        Self {
+            capacity: conf.capacity,
+            watermark: conf.watermark,
+            data_layout: conf.data_layout.into(),
+            tick_size_ms: conf.tick_size_ms,
+            retention: conf.retention.into(),
+            mode: conf.mode.into(),
+        }
+    }
+}
+
+impl From<WheelConfV1> for WheelConf {
+    fn from(conf: WheelConfV1) -> Self {
+        Self {
+            capacity: conf.capacity,
+            watermark: conf.watermark,
+            data_layout: conf.data_layout.into(),
+            tick_size_ms: conf.tick_size_ms,
+            retention: conf.retention.into(),
+            mode: conf.mode.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum DataLayoutV1 {
+    Normal,
+    Compressed(usize),
+    Prefix,
+}
+
+impl From<DataLayout> for DataLayoutV1 {
+    fn from(layout: DataLayout) -> Self {
+        match layout {
+            DataLayout::Normal => Self::Normal,
+            DataLayout::Compressed(chunk_size) => Self::Compressed(chunk_size),
+            DataLayout::Prefix => Self::Prefix,
+        }
+    }
+}
+
+impl From<DataLayoutV1> for DataLayout {
+    fn from(layout: DataLayoutV1) -> Self {
+        match layout {
+            DataLayoutV1::Normal => Self::Normal,
+            DataLayoutV1::Compressed(chunk_size) => Self::Compressed(chunk_size),
+            DataLayoutV1::Prefix => Self::Prefix,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum RetentionPolicyV1 {
+    Drop,
+    Keep,
+    KeepWithLimit(usize),
+}
+
+impl From<RetentionPolicy> for RetentionPolicyV1 {
+    fn from(policy: RetentionPolicy) -> Self {
+        match policy {
+            RetentionPolicy::Drop => Self::Drop,
+            RetentionPolicy::Keep => Self::Keep,
+            RetentionPolicy::KeepWithLimit(limit) => Self::KeepWithLimit(limit),
+        }
+    }
+}
+
+impl From<RetentionPolicyV1> for RetentionPolicy {
+    fn from(policy: RetentionPolicyV1) -> Self {
+        match policy {
+            RetentionPolicyV1::Drop => Self::Drop,
+            RetentionPolicyV1::Keep => Self::Keep,
+            RetentionPolicyV1::KeepWithLimit(limit) => Self::KeepWithLimit(limit),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum WheelModeV1 {
+    Stream,
+    Index,
+}
+
+impl From<WheelMode> for WheelModeV1 {
+    fn from(mode: WheelMode) -> Self {
+        match mode {
+            WheelMode::Stream => Self::Stream,
+            WheelMode::Index => Self::Index,
+        }
+    }
+}
+
+impl From<WheelModeV1> for WheelMode {
+    fn from(mode: WheelModeV1) -> Self {
+        match mode {
+            WheelModeV1::Stream => Self::Stream,
+            WheelModeV1::Index => Self::Index,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CalendarConfV1 {
+    months: usize,
+    quarters: usize,
+}
+
+impl From<&CalendarConf> for CalendarConfV1 {
+    fn from(conf: &CalendarConf) -> Self {
+        Self {
+            months: conf.months,
+            quarters: conf.quarters,
+        }
+    }
+}
+
+impl From<CalendarConfV1> for CalendarConf {
+    fn from(conf: CalendarConfV1) -> Self {
+        Self {
+            months: conf.months,
+            quarters: conf.quarters,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DurationV1 {
+    seconds: i64,
+    nanoseconds: i32,
+    // a single unit variant that is encoded as its index
+    padding: u32,
+}
+
+impl From<&Duration> for DurationV1 {
+    fn from(duration: &Duration) -> Self {
+        Self {
+            seconds: duration.seconds,
+            nanoseconds: duration.nanoseconds,
+            padding: 0,
+        }
+    }
+}
+
+impl From<DurationV1> for Duration {
+    fn from(duration: DurationV1) -> Self {
+        Self {
+            seconds: duration.seconds,
+            nanoseconds: duration.nanoseconds,
+            padding: Padding::Optimize,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct HawV1<P> {
+    watermark: u64,
+    milliseconds_wheel: Option<MaybeWheelV1<P>>,
+    seconds_wheel: MaybeWheelV1<P>,
+    minutes_wheel: MaybeWheelV1<P>,
+    hours_wheel: MaybeWheelV1<P>,
+    days_wheel: MaybeWheelV1<P>,
+    weeks_wheel: MaybeWheelV1<P>,
+    years_wheel: MaybeWheelV1<P>,
+    calendar_wheel: Option<CalendarWheelV1<P>>,
+    window_managers: Vec<WindowManagerV1<P>>,
+    next_window_id: u64,
+    conf: HawConfV1,
+    delta: DeltaStateV1<P>,
+}
+
+impl<A: Aggregator> Snapshot for Haw<A> {
+    type V1 = HawV1<A::PartialAggregate>;
+
+    fn to_v1(&self) -> Self::V1 {
+        HawV1 {
+            watermark: self.watermark,
+            milliseconds_wheel: self.milliseconds_wheel.as_ref().map(Into::into),
+            seconds_wheel: (&self.seconds_wheel).into(),
+            minutes_wheel: (&self.minutes_wheel).into(),
+            hours_wheel: (&self.hours_wheel).into(),
+            days_wheel: (&self.days_wheel).into(),
+            weeks_wheel: (&self.weeks_wheel).into(),
+            years_wheel: (&self.years_wheel).into(),
+            calendar_wheel: self.calendar_wheel.as_ref().map(Into::into),
+            window_managers: self.window_managers.iter().map(Into::into).collect(),
+            next_window_id: self.next_window_id,
+            conf: (&self.conf).into(),
+            delta: (&self.delta).into(),
+        }
+    }
+
+    fn from_v1(haw: Self::V1) -> Self {
+        // starts from a fresh wheel so that state outside of the snapshot is initialised
+        let mut restored = Haw::new(haw.conf.into());
+        restored.watermark = haw.watermark;
+        restored.milliseconds_wheel = haw.milliseconds_wheel.map(Into::into);
+        restored.seconds_wheel = haw.seconds_wheel.into();
+        restored.minutes_wheel = haw.minutes_wheel.into();
+        restored.hours_wheel = haw.hours_wheel.into();
+        restored.days_wheel = haw.days_wheel.into();
+        restored.weeks_wheel = haw.weeks_wheel.into();
+        restored.years_wheel = haw.years_wheel.into();
+        restored.calendar_wheel = haw.calendar_wheel.map(Into::into);
+        restored.window_managers = haw.window_managers.into_iter().map(Into::into).collect();
+        restored.next_window_id = haw.next_window_id;
+        restored.delta = haw.delta.into();
+        restored
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MaybeWheelV1<P> {
+    conf: WheelConfV1,
+    inner: Option<WheelV1<P>>,
+}
+
+impl<A: Aggregator> From<&MaybeWheel<A>> for MaybeWheelV1<A::PartialAggregate> {
+    fn from(wheel: &MaybeWheel<A>) -> Self {
+        Self {
+            conf: (&wheel.conf).into(),
+            inner: wheel.inner.as_ref().map(Into::into),
+        }
+    }
+}
+
+impl<A: Aggregator> From<MaybeWheelV1<A::PartialAggregate>> for MaybeWheel<A> {
+    fn from(wheel: MaybeWheelV1<A::PartialAggregate>) -> Self {
+        Self {
+            conf: wheel.conf.into(),
+            inner: wheel.inner.map(Into::into),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WheelV1<P> {
+    capacity: usize,
+    num_slots: usize,
+    total: Option<P>,
+    watermark: u64,
+    tick_size_ms: u64,
+    retention: RetentionPolicyV1,
+    mode: WheelModeV1,
+    data: DataV1<P>,
+    rotation_count: usize,
+}
+
+impl<A: Aggregator> From<&Wheel<A>> for WheelV1<A::PartialAggregate> {
+    fn from(wheel: &Wheel<A>) -> Self {
+        Self {
+            capacity: wheel.capacity,
+            num_slots: wheel.num_slots,
+            total: wheel.total.clone(),
+            watermark: wheel.watermark,
+            tick_size_ms: wheel.tick_size_ms,
+            retention: wheel.retention.into(),
+            mode: wheel.mode.into(),
+            data: (&wheel.data).into(),
+            rotation_count: wheel.rotation_count,
+        }
+    }
+}
+
+impl<A: Aggregator> From<WheelV1<A::PartialAggregate>> for Wheel<A> {
+    fn from(wheel: WheelV1<A::PartialAggregate>) -> Self {
+        Self {
+            capacity: wheel.capacity,
+            num_slots: wheel.num_slots,
+            total: wheel.total,
+            watermark: wheel.watermark,
+            tick_size_ms: wheel.tick_size_ms,
+            retention: wheel.retention.into(),
+            mode: wheel.mode.into(),
+            data: wheel.data.into(),
+            rotation_count: wheel.rotation_count,
+            #[cfg(test)]
+            total_ticks: 0,
+            #[cfg(feature = "profiler")]
+            stats: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum DataV1<P> {
+    Deque(Vec<P>),
+    PrefixDeque {
+        slots: Vec<P>,
+        prefix: Vec<P>,
+    },
+    CompressedDeque {
+        buffer: Vec<P>,
+        chunks: Vec<Vec<u8>>,
+        chunk_size: usize,
+    },
+}
+
+impl<A: Aggregator> From<&Data<A>> for DataV1<A::PartialAggregate> {
+    fn from(data: &Data<A>) -> Self {
+        let partials = |deque: &MutablePartialDeque<A>| deque.inner.iter().cloned().collect();
+        match data {
+            Data::Deque(deque) => Self::Deque(partials(deque)),
+            Data::PrefixDeque(deque) => Self::PrefixDeque {
+                slots: partials(&deque.slots),
+                prefix: partials(&deque.prefix),
+            },
+            Data::CompressedDeque(deque) => Self::CompressedDeque {
+                buffer: partials(&deque.buffer),
+                chunks: deque.chunks.iter().cloned().collect(),
+                chunk_size: deque.chunk_size,
+            },
+        }
+    }
+}
+
+impl<A: Aggregator> From<DataV1<A::PartialAggregate>> for Data<A> {
+    fn from(data: DataV1<A::PartialAggregate>) -> Self {
+        match data {
+            DataV1::Deque(slots) => Self::Deque(MutablePartialDeque::from_vec(slots)),
+            DataV1::PrefixDeque { slots, prefix } => Self::PrefixDeque(PrefixDeque {
+                slots: MutablePartialDeque::from_vec(slots),
+                prefix: MutablePartialDeque::from_vec(prefix),
+            }),
+            DataV1::CompressedDeque {
+                buffer,
+                chunks,
+                chunk_size,
+            } => Self::CompressedDeque(CompressedDeque {
+                buffer: MutablePartialDeque::from_vec(buffer),
+                chunks: chunks.into(),
+                chunk_size,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CalendarWheelV1<P> {
+    conf: CalendarConfV1,
+    utc_offset: i32,
+    current_month: i32,
+    next_month_start: u64,
+    current: P,
+    oldest_month: i32,
+    months: Vec<P>,
+    oldest_quarter: i32,
+    quarters: Vec<P>,
+}
+
+impl<A: Aggregator> From<&CalendarWheel<A>> for CalendarWheelV1<A::PartialAggregate> {
+    fn from(wheel: &CalendarWheel<A>) -> Self {
+        Self {
+            conf: (&wheel.conf).into(),
+            utc_offset: wheel.utc_offset,
+            current_month: wheel.current_month,
+            next_month_start: wheel.next_month_start,
+            current: wheel.current.clone(),
+            oldest_month: wheel.oldest_month,
+            months: wheel.months.iter().cloned().collect(),
+            oldest_quarter: wheel.oldest_quarter,
+            quarters: wheel.quarters.iter().cloned().collect(),
+        }
+    }
+}
+
+impl<A: Aggregator> From<CalendarWheelV1<A::PartialAggregate>> for CalendarWheel<A> {
+    fn from(wheel: CalendarWheelV1<A::PartialAggregate>) -> Self {
+        Self {
+            conf: wheel.conf.into(),
+            utc_offset: wheel.utc_offset,
+            current_month: wheel.current_month,
+            next_month_start: wheel.next_month_start,
+            current: wheel.current,
+            oldest_month: wheel.oldest_month,
+            months: wheel.months.into(),
+            oldest_quarter: wheel.oldest_quarter,
+            quarters: wheel.quarters.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DeltaStateV1<P> {
+    oldest_ts: u64,
+    deltas: Vec<Option<P>>,
+}
+
+impl<P: Clone> From<&DeltaState<P>> for DeltaStateV1<P> {
+    fn from(state: &DeltaState<P>) -> Self {
+        Self {
+            oldest_ts: state.oldest_ts,
+            deltas: state.deltas.clone(),
+        }
+    }
+}
+
+impl<P> From<DeltaStateV1<P>> for DeltaState<P> {
+    fn from(state: DeltaStateV1<P>) -> Self {
+        DeltaState::new(state.oldest_ts, state.deltas)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WindowManagerV1<P> {
+    id: u64,
+    aggregator: WindowAggregatorV1<P>,
+    window: WindowV1,
+    allowed_lateness: u64,
+    emitted: Vec<EmittedWindowV1<P>>,
+    late: Vec<(u64, P)>,
+}
+
+impl<A: Aggregator> From<&WindowManager<A>> for WindowManagerV1<A::PartialAggregate> {
+    fn from(manager: &WindowManager<A>) -> Self {
+        Self {
+            id: manager.id.0,
+            aggregator: (&manager.aggregator).into(),
+            window: (&manager.window).into(),
+            allowed_lateness: manager.allowed_lateness,
+            emitted: manager.emitted.iter().map(Into::into).collect(),
+            late: manager.late.clone(),
+        }
+    }
+}
+
+impl<A: Aggregator> From<WindowManagerV1<A::PartialAggregate>> for WindowManager<A> {
+    fn from(manager: WindowManagerV1<A::PartialAggregate>) -> Self {
+        Self {
+            id: WindowId(manager.id),
+            aggregator: manager.aggregator.into(),
+            window: manager.window.into(),
+            allowed_lateness: manager.allowed_lateness,
+            emitted: manager.emitted.into_iter().map(Into::into).collect(),
+            late: manager.late,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum WindowV1 {
+    Tumbling {
+        range: DurationV1,
+    },
+    Sliding {
+        range: DurationV1,
+        slide: DurationV1,
+    },
+    Session {
+        timeout: DurationV1,
+    },
+}
+
+impl From<&Window> for WindowV1 {
+    fn from(window: &Window) -> Self {
+        match window {
+            Window::Tumbling { range } => Self::Tumbling {
+                range: range.into(),
+            },
+            Window::Sliding { range, slide } => Self::Sliding {
+                range: range.into(),
+                slide: slide.into(),
+            },
+            Window::Session { timeout } => Self::Session {
+                timeout: timeout.into(),
+            },
+        }
+    }
+}
+
+impl From<WindowV1> for Window {
+    fn from(window: WindowV1) -> Self {
+        match window {
+            WindowV1::Tumbling { range } => Self::Tumbling {
+                range: range.into(),
+            },
+            WindowV1::Sliding { range, slide } => Self::Sliding {
+                range: range.into(),
+                slide: slide.into(),
+            },
+            WindowV1::Session { timeout } => Self::Session {
+                timeout: timeout.into(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EmittedWindowV1<P> {
+    window_id: u64,
+    window_start_ms: u64,
+    window_end_ms: u64,
+    aggregate: P,
+    kind: WindowAggregateKindV1,
+    retracted: Option<P>,
+}
+
+impl<P: Clone> From<&EmittedWindow<P>> for EmittedWindowV1<P> {
+    fn from(emitted: &EmittedWindow<P>) -> Self {
+        let window = &emitted.window;
+        Self {
+            window_id: window.window_id.0,
+            window_start_ms: window.window_start_ms,
+            window_end_ms: window.window_end_ms,
+            aggregate: window.aggregate.clone(),
+            kind: window.kind.into(),
+            retracted: emitted.retracted.clone(),
+        }
+    }
+}
+
+impl<P> From<EmittedWindowV1<P>> for EmittedWindow<P> {
+    fn from(emitted: EmittedWindowV1<P>) -> Self {
+        Self {
+            window: WindowAggregate {
+                window_id: WindowId(emitted.window_id),
+                window_start_ms: emitted.window_start_ms,
+                window_end_ms: emitted.window_end_ms,
+                aggregate: emitted.aggregate,
+                kind: emitted.kind.into(),
+            },
+            retracted: emitted.retracted,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum WindowAggregateKindV1 {
+    Insert,
+    Update,
+    Retraction,
+}
+
+impl From<WindowAggregateKind> for WindowAggregateKindV1 {
+    fn from(kind: WindowAggregateKind) -> Self {
+        match kind {
+            WindowAggregateKind::Insert => Self::Insert,
+            WindowAggregateKind::Update => Self::Update,
+            WindowAggregateKind::Retraction => Self::Retraction,
+        }
+    }
+}
+
+impl From<WindowAggregateKindV1> for WindowAggregateKind {
+    fn from(kind: WindowAggregateKindV1) -> Self {
+        match kind {
+            WindowAggregateKindV1::Insert => Self::Insert,
+            WindowAggregateKindV1::Update => Self::Update,
+            WindowAggregateKindV1::Retraction => Self::Retraction,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum WindowAggregatorV1<P> {
+    Slicing {
+        state: SlicingStateV1,
+        aggregator: SlicingAggregatorV1<P>,
+    },
+    Session {
+        state: SessionStateV1,
+        current: P,
+    },
+}
+
+impl<A: Aggregator> From<&WindowAggregator<A>> for WindowAggregatorV1<A::PartialAggregate> {
+    fn from(aggregator: &WindowAggregator<A>) -> Self {
+        match aggregator {
+            WindowAggregator::Slicing { state, aggregator } => Self::Slicing {
+                state: state.into(),
+                aggregator: aggregator.into(),
+            },
+            WindowAggregator::Session { state, aggregator } => Self::Session {
+                state: state.into(),
+                current: aggregator.current.clone(),
+            },
+        }
+    }
+}
+
+impl<A: Aggregator> From<WindowAggregatorV1<A::PartialAggregate>> for WindowAggregator<A> {
+    fn from(aggregator: WindowAggregatorV1<A::PartialAggregate>) -> Self {
+        match aggregator {
+            WindowAggregatorV1::Slicing { state, aggregator } => Self::Slicing {
+                state: state.into(),
+                aggregator: aggregator.into(),
+            },
+            WindowAggregatorV1::Session { state, current } => Self::Session {
+                state: state.into(),
+                aggregator: SessionAggregator { current },
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SlicingStateV1 {
+    range: usize,
+    slide: usize,
+    tick_size_ms: u64,
+    pair_type: PairTypeV1,
+    pair_ticks_remaining: usize,
+    current_pair_len: usize,
+    next_window_end: u64,
+    next_pair_end: u64,
+    in_p1: bool,
+}
+
+impl From<&SlicingState> for SlicingStateV1 {
+    fn from(state: &SlicingState) -> Self {
+        Self {
+            range: state.range,
+            slide: state.slide,
+            tick_size_ms: state.tick_size_ms,
+            pair_type: state.pair_type.into(),
+            pair_ticks_remaining: state.pair_ticks_remaining,
+            current_pair_len: state.current_pair_len,
+            next_window_end: state.next_window_end,
+            next_pair_end: state.next_pair_end,
+            in_p1: state.in_p1,
+        }
+    }
+}
+
+impl From<SlicingStateV1> for SlicingState {
+    fn from(state: SlicingStateV1) -> Self {
+        Self {
+            range: state.range,
+            slide: state.slide,
+            tick_size_ms: state.tick_size_ms,
+            pair_type: state.pair_type.into(),
+            pair_ticks_remaining: state.pair_ticks_remaining,
+            current_pair_len: state.current_pair_len,
+            next_window_end: state.next_window_end,
+            next_pair_end: state.next_pair_end,
+            in_p1: state.in_p1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum PairTypeV1 {
+    Even(usize),
+    Uneven(usize, usize),
+}
+
+impl From<PairType> for PairTypeV1 {
+    fn from(pair_type: PairType) -> Self {
+        match pair_type {
+            PairType::Even(slide) => Self::Even(slide),
+            PairType::Uneven(p1, p2) => Self::Uneven(p1, p2),
+        }
+    }
+}
+
+impl From<PairTypeV1> for PairType {
+    fn from(pair_type: PairTypeV1) -> Self {
+        match pair_type {
+            PairTypeV1::Even(slide) => Self::Even(slide),
+            PairTypeV1::Uneven(p1, p2) => Self::Uneven(p1, p2),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionStateV1 {
+    session_gap: DurationV1,
+    inactive_period: DurationV1,
+    last_window_start: Option<u64>,
+}
+
+impl From<&SessionState> for SessionStateV1 {
+    fn from(state: &SessionState) -> Self {
+        Self {
+            session_gap: (&state.session_gap).into(),
+            inactive_period: (&state.inactive_period).into(),
+            last_window_start: state.last_window_start,
+        }
+    }
+}
+
+impl From<SessionStateV1> for SessionState {
+    fn from(state: SessionStateV1) -> Self {
+        Self {
+            session_gap: state.session_gap.into(),
+            inactive_period: state.inactive_period.into(),
+            last_window_start: state.last_window_start,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum SlicingAggregatorV1<P> {
+    Soe {
+        stack: Vec<P>,
+        agg: P,
+    },
+    TwoStacks {
+        front: Vec<(P, P)>,
+        back: Vec<(P, P)>,
+    },
+}
+
+impl<A: Aggregator> From<&SlicingAggregator<A>> for SlicingAggregatorV1<A::PartialAggregate> {
+    fn from(aggregator: &SlicingAggregator<A>) -> Self {
+        let values = |stack: &[Value<A>]| {
+            stack
+                .iter()
+                .map(|value| (value.agg.clone(), value.val.clone()))
+                .collect()
+        };
+        match aggregator {
+            SlicingAggregator::Soe(soe) => Self::Soe {
+                stack: soe.stack.iter().cloned().collect(),
+                agg: soe.agg.clone(),
+            },
+            SlicingAggregator::TwoStacks(stacks) => Self::TwoStacks {
+                front: values(&stacks.front),
+                back: values(&stacks.back),
+            },
+        }
+    }
+}
+
+impl<A: Aggregator> From<SlicingAggregatorV1<A::PartialAggregate>> for SlicingAggregator<A> {
+    fn from(aggregator: SlicingAggregatorV1<A::PartialAggregate>) -> Self {
+        let values = |stack: Vec<(A::PartialAggregate, A::PartialAggregate)>| {
+            stack
+                .into_iter()
+                .map(|(agg, val)| Value::new(agg, val))
+                .collect()
+        };
+        match aggregator {
+            SlicingAggregatorV1::Soe { stack, agg } => Self::Soe(SubtractOnEvict {
+                stack: stack.into(),
+                agg,
+            }),
+            SlicingAggregatorV1::TwoStacks { front, back } => Self::TwoStacks(TwoStacks {
+                front: values(front),
+                back: values(back),
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(
+    bound = "P: Serialize + DeserializeOwned, M: Serialize + DeserializeOwned, I: Serialize + DeserializeOwned"
+)]
+pub(crate) struct RwWheelV1<P, M, I> {
+    writer: WriterWheelV1<M, I>,
+    reader: HawV1<P>,
+}
+
+impl<A: Aggregator> Snapshot for RwWheel<A> {
+    type V1 = RwWheelV1<A::PartialAggregate, A::MutablePartialAggregate, A::Input>;
+
+    fn to_v1(&self) -> Self::V1 {
+        RwWheelV1 {
+            writer: (&self.writer).into(),
+            reader: self.reader.as_ref().to_v1(),
+        }
+    }
+
+    fn from_v1(wheel: Self::V1) -> Self {
+        Self {
+            writer: wheel.writer.into(),
+            reader: ReaderWheel::from(Haw::from_v1(wheel.reader)),
+            #[cfg(feature = "profiler")]
+            stats: Default::default(),
+        }
+    }
+}
+
+impl<A: Aggregator> Snapshot for (u64, RwWheel<A>) {
+    type V1 = (u64, <RwWheel<A> as Snapshot>::V1);
+
+    fn to_v1(&self) -> Self::V1 {
+        (self.0, self.1.to_v1())
+    }
+
+    fn from_v1((lsn, wheel): Self::V1) -> Self {
+        (lsn, RwWheel::from_v1(wheel))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "M: Serialize + DeserializeOwned, I: Serialize + DeserializeOwned")]
+struct WriterWheelV1<M, I> {
+    watermark: u64,
+    slot_size_ms: u64,
+    num_slots: usize,
+    capacity: usize,
+    overflow: RawTimerWheelV1<EntryV1<I>>,
+    slots: Vec<Option<M>>,
+    tail: usize,
+    head: usize,
+    late_policy: LatePolicyV1,
+    allowed_lateness: u64,
+    late: Vec<EntryV1<I>>,
+}
+
+impl<A: Aggregator> From<&WriterWheel<A>> for WriterWheelV1<A::MutablePartialAggregate, A::Input> {
+    fn from(wheel: &WriterWheel<A>) -> Self {
+        Self {
+            watermark: wheel.watermark,
+            slot_size_ms: wheel.slot_size_ms,
+            num_slots: wheel.num_slots,
+            capacity: wheel.capacity,
+            overflow: (&wheel.overflow).into(),
+            slots: wheel.slots.to_vec(),
+            tail: wheel.tail,
+            head: wheel.head,
+            late_policy: wheel.late_policy.into(),
+            allowed_lateness: wheel.allowed_lateness,
+            late: wheel.late.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<A: Aggregator> From<WriterWheelV1<A::MutablePartialAggregate, A::Input>> for WriterWheel<A> {
+    fn from(wheel: WriterWheelV1<A::MutablePartialAggregate, A::Input>) -> Self {
+        Self {
+            watermark: wheel.watermark,
+            slot_size_ms: wheel.slot_size_ms,
+            num_slots: wheel.num_slots,
+            capacity: wheel.capacity,
+            overflow: wheel.overflow.into(),
+            slots: wheel.slots.into_boxed_slice(),
+            tail: wheel.tail,
+            head: wheel.head,
+            late_policy: wheel.late_policy.into(),
+            allowed_lateness: wheel.allowed_lateness,
+            late: wheel.late.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum LatePolicyV1 {
+    Drop,
+    SideOutput,
+    Correct,
+}
+
+impl From<LatePolicy> for LatePolicyV1 {
+    fn from(policy: LatePolicy) -> Self {
+        match policy {
+            LatePolicy::Drop => Self::Drop,
+            LatePolicy::SideOutput => Self::SideOutput,
+            LatePolicy::Correct => Self::Correct,
+        }
+    }
+}
+
+impl From<LatePolicyV1> for LatePolicy {
+    fn from(policy: LatePolicyV1) -> Self {
+        match policy {
+            LatePolicyV1::Drop => Self::Drop,
+            LatePolicyV1::SideOutput => Self::SideOutput,
+            LatePolicyV1::Correct => Self::Correct,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct EntryV1<I> {
+    data: I,
+    timestamp: u64,
+}
+
+impl<I: Clone + core::fmt::Debug> From<&Entry<I>> for EntryV1<I> {
+    fn from(entry: &Entry<I>) -> Self {
+        Self {
+            data: entry.data.clone(),
+            timestamp: entry.timestamp,
+        }
+    }
+}
+
+impl<I: core::fmt::Debug> From<EntryV1<I>> for Entry<I> {
+    fn from(entry: EntryV1<I>) -> Self {
+        Entry::new(entry.data, entry.timestamp)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "E: Serialize + DeserializeOwned")]
+struct RawTimerWheelV1<E> {
+    primary: ByteWheelV1<E, [u8; 0]>,
+    secondary: ByteWheelV1<E, [u8; 1]>,
+    tertiary: ByteWheelV1<E, [u8; 2]>,
+    quarternary: ByteWheelV1<E, [u8; 3]>,
+    overflow: Vec<(E, core::time::Duration)>,
+    pruner: PruneDecisionV1,
+    time: u64,
+}
+
+impl<I: Clone + core::fmt::Debug> From<&RawTimerWheel<Entry<I>>> for RawTimerWheelV1<EntryV1<I>> {
+    fn from(wheel: &RawTimerWheel<Entry<I>>) -> Self {
+        let timer = &wheel.timer;
+        Self {
+            primary: (&*timer.primary).into(),
+            secondary: (&*timer.secondary).into(),
+            tertiary: (&*timer.tertiary).into(),
+            quarternary: (&*timer.quarternary).into(),
+            overflow: timer
+                .overflow
+                .iter()
+                .map(|overflow| ((&overflow.entry).into(), overflow.remaining_delay))
+                .collect(),
+            pruner: timer.pruner.into(),
+            time: wheel.time,
+        }
+    }
+}
+
+impl<I: core::fmt::Debug> From<RawTimerWheelV1<EntryV1<I>>> for RawTimerWheel<Entry<I>> {
+    fn from(wheel: RawTimerWheelV1<EntryV1<I>>) -> Self {
+        let mut timer = QuadWheelWithOverflow::new(wheel.pruner.into());
+        timer.primary = Box::new(wheel.primary.into());
+        timer.secondary = Box::new(wheel.secondary.into());
+        timer.tertiary = Box::new(wheel.tertiary.into());
+        timer.quarternary = Box::new(wheel.quarternary.into());
+        timer.overflow = wheel
+            .overflow
+            .into_iter()
+            .map(|(entry, remaining_delay)| OverflowEntry {
+                entry: entry.into(),
+                remaining_delay,
+            })
+            .collect();
+        Self {
+            timer,
+            time: wheel.time,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum PruneDecisionV1 {
+    Keep,
+    Drop,
+}
+
+impl From<PruneDecision> for PruneDecisionV1 {
+    fn from(decision: PruneDecision) -> Self {
+        match decision {
+            PruneDecision::Keep => Self::Keep,
+            PruneDecision::Drop => Self::Drop,
+        }
+    }
+}
+
+impl From<PruneDecisionV1> for PruneDecision {
+    fn from(decision: PruneDecisionV1) -> Self {
+        match decision {
+            PruneDecisionV1::Keep => Self::Keep,
+            PruneDecisionV1::Drop => Self::Drop,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "E: Serialize + DeserializeOwned, R: Serialize + DeserializeOwned")]
+struct ByteWheelV1<E, R> {
+    #[serde(with = "BigArray")]
+    slots: [Option<Vec<(E, R)>>; 256],
+    count: u64,
+    current: u8,
+}
+
+impl<I, R> From<&ByteWheel<Entry<I>, R>> for ByteWheelV1<EntryV1<I>, R>
+where
+    I: Clone + core::fmt::Debug,
+    R: Copy,
+{
+    fn from(wheel: &ByteWheel<Entry<I>, R>) -> Self {
+        Self {
+            slots: core::array::from_fn(|index| {
+                wheel.slots[index].as_ref().map(|entries| {
+                    entries
+                        .iter()
+                        .map(|entry| ((&entry.entry).into(), entry.rest))
+                        .collect()
+                })
+            }),
+            count: wheel.count,
+            current: wheel.current,
+        }
+    }
+}
+
+impl<I: core::fmt::Debug, R> From<ByteWheelV1<EntryV1<I>, R>> for ByteWheel<Entry<I>, R> {
+    fn from(wheel: ByteWheelV1<EntryV1<I>, R>) -> Self {
+        Self {
+            slots: wheel.slots.map(|entries| {
+                entries.map(|entries| {
+                    entries
+                        .into_iter()
+                        .map(|(entry, rest)| WheelEntry {
+                            entry: entry.into(),
+                            rest,
+                        })
+                        .collect()
+                })
+            }),
+            count: wheel.count,
+            current: wheel.current,
+        }
+    }
+}
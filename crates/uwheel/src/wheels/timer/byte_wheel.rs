@@ -56,9 +56,9 @@ const NUM_SLOTS: usize = 256;
 )]
 pub struct ByteWheel<EntryType, RestType> {
     #[cfg_attr(feature = "serde", serde(with = "BigArray"))]
-    slots: [Option<WheelEntryList<EntryType, RestType>>; NUM_SLOTS],
-    count: u64,
-    current: u8,
+    pub(crate) slots: [Option<WheelEntryList<EntryType, RestType>>; NUM_SLOTS],
+    pub(crate) count: u64,
+    pub(crate) current: u8,
 }
 
 impl<EntryType, RestType> ByteWheel<EntryType, RestType> {
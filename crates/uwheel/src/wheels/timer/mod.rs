@@ -4,8 +4,8 @@
 //! and has been modified to support ``no_std``.
 //! License: MIT
 
-mod byte_wheel;
-mod quad_wheel;
+pub(crate) mod byte_wheel;
+pub(crate) mod quad_wheel;
 pub(crate) mod raw_wheel;
 
 use crate::{cfg_not_sync, cfg_sync, wheels::read::Haw};
@@ -111,7 +111,23 @@ impl<T: Debug> Display for TimerExpiredError<T> {
 #[cfg(not(feature = "std"))]
 use alloc::boxed::Box;
 
-pub type WheelFn<A> = Box<dyn Fn(&Haw<A>)>;
+cfg_not_sync! {
+    /// A user-defined function that may be scheduled on a wheel
+    pub trait TimerFn<A: Aggregator>: Fn(&Haw<A>) + 'static {}
+    impl<A: Aggregator, F> TimerFn<A> for F where F: Fn(&Haw<A>) + 'static {}
+
+    pub type WheelFn<A> = Box<dyn Fn(&Haw<A>)>;
+}
+
+cfg_sync! {
+    /// A user-defined function that may be scheduled on a wheel
+    ///
+    /// Functions must be `Send + Sync` as the wheel may be shared across threads.
+    pub trait TimerFn<A: Aggregator>: Fn(&Haw<A>) + Send + Sync + 'static {}
+    impl<A: Aggregator, F> TimerFn<A> for F where F: Fn(&Haw<A>) + Send + Sync + 'static {}
+
+    pub type WheelFn<A> = Box<dyn Fn(&Haw<A>) + Send + Sync>;
+}
 
 pub enum TimerAction<A: Aggregator> {
     Oneshot(WheelFn<A>),
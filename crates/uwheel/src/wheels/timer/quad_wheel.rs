@@ -24,9 +24,9 @@ use alloc::vec::Vec;
     serde(bound = "EntryType: serde::Serialize + serde::de::DeserializeOwned")
 )]
 #[derive(Clone)]
-struct OverflowEntry<EntryType> {
-    entry: EntryType,
-    remaining_delay: Duration,
+pub(crate) struct OverflowEntry<EntryType> {
+    pub(crate) entry: EntryType,
+    pub(crate) remaining_delay: Duration,
 }
 impl<EntryType> OverflowEntry<EntryType> {
     fn new(entry: EntryType, remaining_delay: Duration) -> Self {
@@ -85,12 +85,12 @@ pub fn no_prune<E>(_e: &E) -> PruneDecision {
     serde(bound = "EntryType: serde::Serialize + serde::de::DeserializeOwned")
 )]
 pub struct QuadWheelWithOverflow<EntryType> {
-    primary: Box<ByteWheel<EntryType, [u8; 0]>>,
-    secondary: Box<ByteWheel<EntryType, [u8; 1]>>,
-    tertiary: Box<ByteWheel<EntryType, [u8; 2]>>,
-    quarternary: Box<ByteWheel<EntryType, [u8; 3]>>,
-    overflow: Vec<OverflowEntry<EntryType>>,
-    pruner: PruneDecision,
+    pub(crate) primary: Box<ByteWheel<EntryType, [u8; 0]>>,
+    pub(crate) secondary: Box<ByteWheel<EntryType, [u8; 1]>>,
+    pub(crate) tertiary: Box<ByteWheel<EntryType, [u8; 2]>>,
+    pub(crate) quarternary: Box<ByteWheel<EntryType, [u8; 3]>>,
+    pub(crate) overflow: Vec<OverflowEntry<EntryType>>,
+    pub(crate) pruner: PruneDecision,
 }
 
 const MAX_SCHEDULE_DUR: Duration = Duration::from_millis(u32::MAX as u64);
@@ -11,8 +11,8 @@ use alloc::vec::Vec;
     serde(bound = "A: serde::Serialize + serde::de::DeserializeOwned")
 )]
 pub struct RawTimerWheel<A> {
-    timer: QuadWheelWithOverflow<A>,
-    time: u64,
+    pub(crate) timer: QuadWheelWithOverflow<A>,
+    pub(crate) time: u64,
 }
 
 impl<A> Default for RawTimerWheel<A> {
@@ -0,0 +1,496 @@
+use core::fmt::{self, Display};
+use std::{
+    fs::File,
+    io,
+    io::{Seek, SeekFrom, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    read::ReaderWheel,
+    snapshot::{self, crc32, Snapshot, SnapshotError, SnapshotKind},
+    InsertOutcome,
+    RwWheel,
+};
+use crate::{
+    aggregator::Aggregator,
+    duration::Duration,
+    window::{Window, WindowAggregate, WindowId},
+    Entry,
+};
+
+// lsn + payload length + checksum
+const RECORD_PREFIX_LEN: usize = 8 + 4 + 4;
+
+/// An append-only storage for write-ahead log records
+pub trait WalSink {
+    /// Appends an encoded record to the log
+    ///
+    /// The record must be durable once the call returns.
+    fn append(&mut self, record: &[u8]) -> io::Result<()>;
+    /// Shortens the log to its first `len` bytes
+    ///
+    /// Subsequent records must be appended directly after the retained bytes.
+    fn truncate_to(&mut self, len: u64) -> io::Result<()>;
+    /// Removes all records from the log
+    fn truncate(&mut self) -> io::Result<()> {
+        self.truncate_to(0)
+    }
+}
+
+impl WalSink for Vec<u8> {
+    fn append(&mut self, record: &[u8]) -> io::Result<()> {
+        self.extend_from_slice(record);
+        Ok(())
+    }
+    fn truncate_to(&mut self, len: u64) -> io::Result<()> {
+        self.truncate(len as usize);
+        Ok(())
+    }
+}
+
+impl WalSink for File {
+    fn append(&mut self, record: &[u8]) -> io::Result<()> {
+        self.write_all(record)?;
+        self.sync_data()
+    }
+    fn truncate_to(&mut self, len: u64) -> io::Result<()> {
+        self.set_len(len)?;
+        self.seek(SeekFrom::Start(len))?;
+        self.sync_all()
+    }
+}
+
+/// Errors that may occur while logging or recovering a [DurableRwWheel]
+#[derive(Debug)]
+pub enum WalError {
+    /// The write-ahead log could not be written
+    Io(io::Error),
+    /// The snapshot could not be loaded
+    Snapshot(SnapshotError),
+}
+
+impl Display for WalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalError::Io(err) => write!(f, "write-ahead log error: {err}"),
+            WalError::Snapshot(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for WalError {}
+
+impl From<io::Error> for WalError {
+    fn from(err: io::Error) -> Self {
+        WalError::Io(err)
+    }
+}
+
+impl From<SnapshotError> for WalError {
+    fn from(err: SnapshotError) -> Self {
+        WalError::Snapshot(err)
+    }
+}
+
+// An operation that modifies the state of a RwWheel
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(bound = "")]
+enum WalRecord<A: Aggregator> {
+    Insert(Entry<A::Input>),
+    AdvanceTo(u64),
+    Window(Window),
+    RemoveWindow(WindowId),
+    DrainLate,
+}
+
+/// A [RwWheel] that logs every modification to an append-only write-ahead log
+///
+/// Each insert, advance and window change is appended to a [WalSink] before being applied to the wheel.
+/// After a crash, the wheel is restored from its latest snapshot and the log is replayed on top of it,
+/// reconstructing the writer wheel, the [crate::Haw] and any installed windows exactly.
+///
+/// Every record carries a log sequence number (LSN) that is also stored in the snapshot,
+/// meaning records already covered by a snapshot are skipped during replay.
+///
+/// # Example
+///
+/// ```
+/// use uwheel::{aggregator::sum::U32SumAggregator, wal::DurableRwWheel, Entry, RwWheel};
+///
+/// let mut wheel: DurableRwWheel<U32SumAggregator, Vec<u8>> =
+///     DurableRwWheel::new(RwWheel::new(0), Vec::new());
+/// wheel.insert(Entry::new(1, 500)).unwrap();
+///
+/// // persist a snapshot and truncate the log
+/// let mut snapshot = Vec::new();
+/// wheel
+///     .checkpoint(|bytes| {
+///         snapshot = bytes.to_vec();
+///         Ok(())
+///     })
+///     .unwrap();
+///
+/// wheel.insert(Entry::new(2, 1500)).unwrap();
+/// wheel.advance_to(2000).unwrap();
+///
+/// // crash and recover from the snapshot and the log
+/// let log = wheel.sink().clone();
+/// let mut recovered: DurableRwWheel<U32SumAggregator, Vec<u8>> =
+///     DurableRwWheel::from_snapshot(&snapshot, log.clone()).unwrap();
+/// assert_eq!(recovered.replay(&log).unwrap(), 2);
+/// assert_eq!(recovered.read().landmark(), Some(3));
+/// ```
+pub struct DurableRwWheel<A: Aggregator, S: WalSink> {
+    wheel: RwWheel<A>,
+    sink: S,
+    /// Sequence number of the last logged or replayed record
+    lsn: u64,
+}
+
+impl<A: Aggregator, S: WalSink> DurableRwWheel<A, S> {
+    /// Creates a durable wheel from a wheel and an empty log
+    ///
+    /// Until the first [Self::checkpoint], recovery requires a wheel created with the same configuration.
+    pub fn new(wheel: RwWheel<A>, sink: S) -> Self {
+        Self {
+            wheel,
+            sink,
+            lsn: 0,
+        }
+    }
+
+    /// Restores a durable wheel from a snapshot created by [Self::checkpoint]
+    ///
+    /// Records logged after the snapshot should be applied using [Self::replay].
+    pub fn from_snapshot(bytes: &[u8], sink: S) -> Result<Self, WalError> {
+        let (lsn, wheel) = snapshot::decode::<A, (u64, RwWheel<A>)>(SnapshotKind::Durable, bytes)?;
+        Ok(Self { wheel, sink, lsn })
+    }
+
+    /// Replays the records of a write-ahead log onto the wheel
+    ///
+    /// `log` must be the current content of the wheel's [WalSink]. Records already covered by the
+    /// wheel are skipped and replay stops at the first incomplete or corrupted record, which may be
+    /// left behind by a crash during an append. The sink is then truncated to the valid records so
+    /// that new records are not appended after the damaged ones.
+    ///
+    /// Window results are not emitted again. Returns the number of replayed records.
+    pub fn replay(&mut self, log: &[u8]) -> Result<usize, WalError> {
+        let mut replayed = 0;
+        let mut rest = log;
+        while rest.len() >= RECORD_PREFIX_LEN {
+            let lsn = u64::from_le_bytes(rest[..8].try_into().unwrap());
+            let len = u32::from_le_bytes(rest[8..12].try_into().unwrap()) as usize;
+            let checksum = u32::from_le_bytes(rest[12..16].try_into().unwrap());
+
+            let Some(payload) = rest.get(RECORD_PREFIX_LEN..RECORD_PREFIX_LEN + len) else {
+                break;
+            };
+            if crc32(&rest[..8]) ^ crc32(payload) != checksum {
+                break;
+            }
+            let Ok(record) = postcard::from_bytes::<WalRecord<A>>(payload) else {
+                break;
+            };
+            rest = &rest[RECORD_PREFIX_LEN + len..];
+
+            if lsn <= self.lsn {
+                continue;
+            }
+            self.apply(record);
+            self.lsn = lsn;
+            replayed += 1;
+        }
+
+        if !rest.is_empty() {
+            self.sink.truncate_to((log.len() - rest.len()) as u64)?;
+        }
+        Ok(replayed)
+    }
+
+    /// Logs and inserts an entry into the wheel
+    ///
+    /// See [RwWheel::insert] for more information.
+    pub fn insert(&mut self, e: impl Into<Entry<A::Input>>) -> Result<InsertOutcome, WalError> {
+        let entry = e.into();
+        self.log(&WalRecord::Insert(entry))?;
+        Ok(self.wheel.insert(entry))
+    }
+
+    /// Logs and advances the watermark of the wheel by the given [Duration]
+    pub fn advance(
+        &mut self,
+        duration: Duration,
+    ) -> Result<Vec<WindowAggregate<A::PartialAggregate>>, WalError> {
+        let to = self.wheel.watermark() + duration.whole_milliseconds() as u64;
+        self.advance_to(to)
+    }
+
+    /// Logs and advances the watermark of the wheel to the given watermark
+    pub fn advance_to(
+        &mut self,
+        watermark: u64,
+    ) -> Result<Vec<WindowAggregate<A::PartialAggregate>>, WalError> {
+        self.log(&WalRecord::AdvanceTo(watermark))?;
+        Ok(self.wheel.advance_to(watermark))
+    }
+
+    /// Logs and installs a window into the wheel
+    ///
+    /// See [RwWheel::window] for more information.
+    pub fn window(&mut self, window: impl Into<Window>) -> Result<WindowId, WalError> {
+        let window = window.into();
+        self.log(&WalRecord::Window(window))?;
+        Ok(self.wheel.window(window))
+    }
+
+    /// Logs and removes an installed window from the wheel
+    pub fn remove_window(&mut self, id: WindowId) -> Result<bool, WalError> {
+        self.log(&WalRecord::RemoveWindow(id))?;
+        Ok(self.wheel.remove_window(id))
+    }
+
+    /// Logs and drains all late entries buffered in the side output
+    pub fn drain_late(&mut self) -> Result<Vec<Entry<A::Input>>, WalError> {
+        self.log(&WalRecord::DrainLate)?;
+        Ok(self.wheel.drain_late())
+    }
+
+    /// Writes a snapshot of the wheel and truncates the write-ahead log
+    ///
+    /// The `persist` function must store the snapshot durably before returning. The log is only
+    /// truncated if it succeeds. A crash between the two steps is safe as the snapshot records the
+    /// sequence number of the last applied record.
+    pub fn checkpoint(
+        &mut self,
+        persist: impl FnOnce(&[u8]) -> io::Result<()>,
+    ) -> Result<(), WalError> {
+        let bytes = snapshot::encode::<A, (u64, RwWheel<A>)>(
+            SnapshotKind::Durable,
+            self.wheel.read().as_ref().conf(),
+            &(self.lsn, self.wheel.to_v1()),
+        );
+        persist(&bytes)?;
+        self.sink.truncate()?;
+        Ok(())
+    }
+
+    /// Returns the sequence number of the last logged or replayed record
+    #[inline]
+    pub fn lsn(&self) -> u64 {
+        self.lsn
+    }
+
+    /// Returns the current watermark of the wheel
+    #[inline]
+    pub fn watermark(&self) -> u64 {
+        self.wheel.watermark()
+    }
+
+    /// Returns a reference to the underlying [RwWheel]
+    #[inline]
+    pub fn wheel(&self) -> &RwWheel<A> {
+        &self.wheel
+    }
+
+    /// Returns a reference to the reader wheel
+    #[inline]
+    pub fn read(&self) -> &ReaderWheel<A> {
+        self.wheel.read()
+    }
+
+    /// Returns a reference to the write-ahead log sink
+    #[inline]
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    // Appends a record to the log before it is applied
+    fn log(&mut self, record: &WalRecord<A>) -> Result<(), WalError> {
+        let lsn = self.lsn + 1;
+        let payload = postcard::to_allocvec(record).expect("failed to encode wal record");
+        let lsn_bytes = lsn.to_le_bytes();
+
+        let mut bytes = Vec::with_capacity(RECORD_PREFIX_LEN + payload.len());
+        bytes.extend_from_slice(&lsn_bytes);
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(crc32(&lsn_bytes) ^ crc32(&payload)).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+
+        self.sink.append(&bytes)?;
+        self.lsn = lsn;
+        Ok(())
+    }
+
+    fn apply(&mut self, record: WalRecord<A>) {
+        match record {
+            WalRecord::Insert(entry) => {
+                let _ = self.wheel.insert(entry);
+            }
+            WalRecord::AdvanceTo(watermark) => {
+                let _ = self.wheel.advance_to(watermark);
+            }
+            WalRecord::Window(window) => {
+                let _ = self.wheel.window(window);
+            }
+            WalRecord::RemoveWindow(id) => {
+                let _ = self.wheel.remove_window(id);
+            }
+            WalRecord::DrainLate => {
+                let _ = self.wheel.drain_late();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aggregator::sum::U32SumAggregator, Conf, HawConf, LatePolicy, NumericalDuration};
+
+    type Durable = DurableRwWheel<U32SumAggregator, Vec<u8>>;
+
+    #[test]
+    fn wal_replay_test() {
+        let mut wheel: Durable = DurableRwWheel::new(RwWheel::new(0), Vec::new());
+        let id = wheel.window(Window::tumbling(2.seconds())).unwrap();
+        wheel.insert(Entry::new(1, 500)).unwrap();
+        wheel.insert(Entry::new(2, 1500)).unwrap();
+        wheel.advance_to(1000).unwrap();
+        assert_eq!(wheel.lsn(), 4);
+
+        // recover the full state without any snapshot
+        let log = wheel.sink().clone();
+        let mut recovered: Durable = DurableRwWheel::new(RwWheel::new(0), log.clone());
+        assert_eq!(recovered.replay(&log).unwrap(), 4);
+        assert_eq!(recovered.lsn(), 4);
+        assert_eq!(recovered.watermark(), 1000);
+        assert_eq!(recovered.read().landmark(), Some(1));
+
+        // the writer wheel and the installed window are restored
+        let results = recovered.advance_to(2000).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].window_id, id);
+        assert_eq!(results[0].aggregate, 3);
+
+        // replaying the same log again is a no-op
+        assert_eq!(recovered.replay(&log).unwrap(), 0);
+    }
+
+    #[test]
+    fn wal_checkpoint_test() {
+        let conf = Conf::default()
+            .with_haw_conf(HawConf::default().with_watermark(10000))
+            .with_late_policy(LatePolicy::SideOutput);
+        let mut wheel: Durable = DurableRwWheel::new(RwWheel::with_conf(conf), Vec::new());
+        wheel.insert(Entry::new(1, 10500)).unwrap();
+        wheel.insert(Entry::new(7, 5000)).unwrap();
+        assert_eq!(wheel.drain_late().unwrap().len(), 1);
+
+        let mut snapshot = Vec::new();
+        wheel
+            .checkpoint(|bytes| {
+                snapshot = bytes.to_vec();
+                Ok(())
+            })
+            .unwrap();
+        assert!(wheel.sink().is_empty());
+
+        wheel.advance(1.seconds()).unwrap();
+        wheel.insert(Entry::new(2, 11000)).unwrap();
+        let log = wheel.sink().clone();
+
+        let mut recovered: Durable = DurableRwWheel::from_snapshot(&snapshot, log.clone()).unwrap();
+        assert_eq!(recovered.lsn(), 3);
+        assert_eq!(recovered.replay(&log).unwrap(), 2);
+        assert_eq!(recovered.lsn(), 5);
+        assert_eq!(recovered.read().landmark(), Some(1));
+        assert!(recovered.wheel.drain_late().is_empty());
+
+        recovered.advance(1.seconds()).unwrap();
+        assert_eq!(recovered.read().landmark(), Some(3));
+    }
+
+    #[test]
+    fn wal_checkpoint_crash_test() {
+        let mut wheel: Durable = DurableRwWheel::new(RwWheel::new(0), Vec::new());
+        wheel.insert(Entry::new(1, 500)).unwrap();
+        let log = wheel.sink().clone();
+
+        // the snapshot is persisted but the log is not truncated
+        let mut snapshot = Vec::new();
+        let result = wheel.checkpoint(|bytes| {
+            snapshot = bytes.to_vec();
+            Err(io::Error::other("crash"))
+        });
+        assert!(matches!(result, Err(WalError::Io(_))));
+        assert_eq!(wheel.sink(), &log);
+
+        // records covered by the snapshot are not applied twice
+        let mut recovered: Durable = DurableRwWheel::from_snapshot(&snapshot, log.clone()).unwrap();
+        assert_eq!(recovered.replay(&log).unwrap(), 0);
+        recovered.advance_to(1000).unwrap();
+        assert_eq!(recovered.read().landmark(), Some(1));
+    }
+
+    #[test]
+    fn wal_torn_record_test() {
+        let mut wheel: Durable = DurableRwWheel::new(RwWheel::new(0), Vec::new());
+        wheel.insert(Entry::new(1, 500)).unwrap();
+        wheel.insert(Entry::new(2, 600)).unwrap();
+
+        // a crash during the last append leaves an incomplete record behind
+        let log = wheel.sink();
+        let torn = log[..log.len() - 1].to_vec();
+        let mut recovered: Durable = DurableRwWheel::new(RwWheel::new(0), torn.clone());
+        assert_eq!(recovered.replay(&torn).unwrap(), 1);
+
+        // a corrupted record stops the replay
+        let mut corrupted = log.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        let mut recovered: Durable = DurableRwWheel::new(RwWheel::new(0), corrupted.clone());
+        assert_eq!(recovered.replay(&corrupted).unwrap(), 1);
+        recovered.advance_to(1000).unwrap();
+        assert_eq!(recovered.read().landmark(), Some(1));
+    }
+
+    fn append_after_torn_replay<S: WalSink>(sink: S, log: impl Fn(&S) -> Vec<u8>) {
+        let mut wheel = DurableRwWheel::<U32SumAggregator, S>::new(RwWheel::new(0), sink);
+        wheel.insert(Entry::new(1, 500)).unwrap();
+        // a crash during an append leaves a torn record behind
+        wheel.sink.append(&[1, 2, 3]).unwrap();
+
+        let mut recovered = DurableRwWheel::<U32SumAggregator, S>::new(RwWheel::new(0), wheel.sink);
+        let torn = log(&recovered.sink);
+        assert_eq!(recovered.replay(&torn).unwrap(), 1);
+        recovered.insert(Entry::new(10, 600)).unwrap();
+        recovered.advance_to(1000).unwrap();
+        assert_eq!(recovered.read().landmark(), Some(11));
+
+        // records appended after the torn one are recovered as well
+        let mut recovered =
+            DurableRwWheel::<U32SumAggregator, S>::new(RwWheel::new(0), recovered.sink);
+        let log = log(&recovered.sink);
+        assert_eq!(recovered.replay(&log).unwrap(), 3);
+        assert_eq!(recovered.read().landmark(), Some(11));
+    }
+
+    #[test]
+    fn wal_append_after_torn_record_test() {
+        append_after_torn_replay(Vec::new(), |sink| sink.clone());
+
+        let path = std::env::temp_dir().join(format!("uwheel-wal-{}.log", std::process::id()));
+        let file = File::options()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        append_after_torn_replay(file, |_| std::fs::read(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use core::{mem, time::Duration as CoreDuration};
+use core::mem;
 
 use crate::{aggregator::Aggregator, duration::Duration, Entry};
 
@@ -7,9 +7,39 @@ use super::{timer::RawTimerWheel, wheel_ext::WheelExt};
 /// Number of write ahead slots
 pub const DEFAULT_WRITE_AHEAD_SLOTS: usize = 64;
 
+/// Default time span covered by a single write-ahead slot in milliseconds
+pub const DEFAULT_SLOT_SIZE_MS: u64 = 1000;
+
 #[cfg(not(feature = "std"))]
 use alloc::{boxed::Box, vec::Vec};
 
+/// Policy for handling entries with timestamps below the current low watermark
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LatePolicy {
+    /// Late entries are dropped (default)
+    #[default]
+    Drop,
+    /// Late entries are buffered in a side output that can be drained through [WriterWheel::drain_late]
+    SideOutput,
+    /// Late entries are combined into the already-frozen slots of the reader wheel
+    ///
+    /// Only has an effect when used through the Reader-Writer Wheel and cannot be combined with
+    /// generated deltas, as the corrections would not reach checkpoints or replicas.
+    Correct,
+}
+
+/// The outcome of inserting an entry into a [WriterWheel]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// The entry was aggregated into a write-ahead slot
+    WriteAhead,
+    /// The entry was scheduled in the overflow wheel to be aggregated later on
+    Overflow,
+    /// The entry was below the low watermark and handled by the given [LatePolicy]
+    Late(LatePolicy),
+}
+
 /// A writer wheel optimized for single-threaded ingestion of aggregates.
 ///
 /// Note that you do not have to interact manually with this wheel if you are using the
@@ -20,21 +50,29 @@ use alloc::{boxed::Box, vec::Vec};
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 pub struct WriterWheel<A: Aggregator> {
     /// Current low watermark
-    watermark: u64,
+    pub(crate) watermark: u64,
+    /// Time span covered by a single write-ahead slot in milliseconds
+    pub(crate) slot_size_ms: u64,
     /// Defines the number actual slots used for the write-ahead wheel
     ///
     /// This value may be different than capacity if capacity is not a power of two.
-    num_slots: usize,
+    pub(crate) num_slots: usize,
     /// Defines the capacity of the write-ahead wheel
-    capacity: usize,
+    pub(crate) capacity: usize,
     /// A Hierarchical Timing Wheel for managing future entries that do not fit within the write-ahead wheel
-    overflow: RawTimerWheel<Entry<A::Input>>,
+    pub(crate) overflow: RawTimerWheel<Entry<A::Input>>,
     /// Pre-allocated memory for mutable write-ahead aggregation
-    slots: Box<[Option<A::MutablePartialAggregate>]>,
+    pub(crate) slots: Box<[Option<A::MutablePartialAggregate>]>,
     /// The current tail of the write-ahead section
-    tail: usize,
+    pub(crate) tail: usize,
     /// The current head of the write-ahead section
-    head: usize,
+    pub(crate) head: usize,
+    /// Policy for handling late entries
+    pub(crate) late_policy: LatePolicy,
+    /// Late entries within this bound (in milliseconds) are always corrected
+    pub(crate) allowed_lateness: u64,
+    /// Side output for late entries
+    pub(crate) late: Vec<Entry<A::Input>>,
 }
 impl<A: Aggregator> Default for WriterWheel<A> {
     fn default() -> Self {
@@ -54,6 +92,7 @@ impl<A: Aggregator> WriterWheel<A> {
             num_slots,
             capacity,
             watermark,
+            slot_size_ms: DEFAULT_SLOT_SIZE_MS,
             overflow: RawTimerWheel::new(watermark),
             slots: (0..capacity)
                 .map(|_| None)
@@ -61,8 +100,67 @@ impl<A: Aggregator> WriterWheel<A> {
                 .into_boxed_slice(),
             head: 0,
             tail: 0,
+            late_policy: LatePolicy::default(),
+            allowed_lateness: 0,
+            late: Vec::new(),
         }
     }
+    /// Configures the time span covered by each write-ahead slot
+    ///
+    /// Defaults to a second and should match the tick size of the reader wheel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot size is less than a millisecond.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{aggregator::sum::U32SumAggregator, wheels::WriterWheel, NumericalDuration};
+    ///
+    /// let wheel: WriterWheel<U32SumAggregator> = WriterWheel::default().with_slot_size(100.milliseconds());
+    /// assert_eq!(wheel.slot_size_ms(), 100);
+    /// ```
+    pub fn with_slot_size(mut self, slot_size: Duration) -> Self {
+        let slot_size_ms = slot_size.whole_milliseconds() as u64;
+        assert!(slot_size_ms > 0, "Slot size must be at least a millisecond");
+        self.slot_size_ms = slot_size_ms;
+        self
+    }
+    /// Returns the time span covered by each write-ahead slot in milliseconds
+    pub fn slot_size_ms(&self) -> u64 {
+        self.slot_size_ms
+    }
+    /// Configures the [LatePolicy] of the wheel
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use uwheel::{aggregator::sum::U32SumAggregator, wheels::WriterWheel, LatePolicy};
+    ///
+    /// let wheel: WriterWheel<U32SumAggregator> = WriterWheel::default().with_late_policy(LatePolicy::SideOutput);
+    /// ```
+    pub fn with_late_policy(mut self, policy: LatePolicy) -> Self {
+        self.late_policy = policy;
+        self
+    }
+    /// Configures the allowed lateness of the wheel
+    ///
+    /// Late entries within the allowed lateness are reported as [LatePolicy::Correct] regardless of the configured [LatePolicy].
+    pub fn with_allowed_lateness(mut self, lateness: Duration) -> Self {
+        self.allowed_lateness = lateness.whole_milliseconds() as u64;
+        self
+    }
+    /// Returns the configured [LatePolicy]
+    pub fn late_policy(&self) -> LatePolicy {
+        self.late_policy
+    }
+    /// Drains all late entries buffered in the side output
+    ///
+    /// Entries are only buffered if the wheel is configured with [LatePolicy::SideOutput].
+    pub fn drain_late(&mut self) -> Vec<Entry<A::Input>> {
+        mem::take(&mut self.late)
+    }
     /// Returns the current low watermark
     pub fn watermark(&self) -> u64 {
         self.watermark
@@ -88,8 +186,8 @@ impl<A: Aggregator> WriterWheel<A> {
     /// ```
     #[inline]
     pub fn tick(&mut self) -> Option<A::MutablePartialAggregate> {
-        // bump the watermark by 1 second as millis
-        self.watermark += Duration::SECOND.whole_milliseconds() as u64;
+        // bump the watermark by a single slot
+        self.watermark += self.slot_size_ms;
 
         // bump head + tail
         self.head = self.wrap_add(self.head, 1);
@@ -145,35 +243,45 @@ impl<A: Aggregator> WriterWheel<A> {
     ///
     /// Note that you don't need to use this function directly if you are using the `Reader-Writer Wheel`.
     ///
-    /// # Safety
-    /// - The entry will be dropped if its timestamp is below the current watermark.
+    /// # Late entries
+    /// Entries with timestamps below the current watermark are handled by the configured [LatePolicy]
+    /// and are never aggregated into the write-ahead slots.
     ///
     /// # Example
     ///
     /// ```
-    /// use uwheel::{Entry, aggregator::sum::U32SumAggregator, wheels::WriterWheel};
+    /// use uwheel::{Entry, aggregator::sum::U32SumAggregator, wheels::WriterWheel, InsertOutcome};
     ///
     /// // Creates a wheel with time 0 and default write-ahead capacity
     /// let mut wheel: WriterWheel<U32SumAggregator> = WriterWheel::default();
     /// // Insert an entry at time 0
-    /// wheel.insert(Entry::new(10, 0));
+    /// assert_eq!(wheel.insert(Entry::new(10, 0)), InsertOutcome::WriteAhead);
     /// ```
     #[inline]
-    pub fn insert(&mut self, e: impl Into<Entry<A::Input>>) {
+    pub fn insert(&mut self, e: impl Into<Entry<A::Input>>) -> InsertOutcome {
         let entry = e.into();
         let watermark = self.watermark;
 
         if entry.timestamp >= watermark {
             let diff = entry.timestamp - self.watermark;
-            let seconds = CoreDuration::from_millis(diff).as_secs();
-            if self.can_write_ahead(seconds) {
-                self.write_ahead(seconds, entry.data);
+            let addend = diff / self.slot_size_ms;
+            if self.can_write_ahead(addend) {
+                self.write_ahead(addend, entry.data);
+                InsertOutcome::WriteAhead
             } else {
                 // Overflows: schedule it to be aggregated later on
                 // TODO: batch as many entries at possible into the same overflow slot
-                let schedule_ts = watermark + seconds * 1000; // convert back to milliseconds
+                let schedule_ts = watermark + addend * self.slot_size_ms; // convert back to milliseconds
                 self.overflow.schedule_at(schedule_ts, entry).unwrap();
+                InsertOutcome::Overflow
             }
+        } else if entry.timestamp.saturating_add(self.allowed_lateness) >= watermark {
+            InsertOutcome::Late(LatePolicy::Correct)
+        } else {
+            if self.late_policy == LatePolicy::SideOutput {
+                self.late.push(entry);
+            }
+            InsertOutcome::Late(self.late_policy)
         }
     }
 }
@@ -282,6 +390,48 @@ mod tests {
         assert_eq!(wheel.tick(), Some(10));
     }
 
+    #[test]
+    fn late_side_output_test() {
+        let mut wheel: WriterWheel<U64SumAggregator> =
+            WriterWheel::with_capacity_and_watermark(8, 0).with_late_policy(LatePolicy::SideOutput);
+        assert_eq!(
+            wheel.insert(Entry::new(10, 1000)),
+            InsertOutcome::WriteAhead
+        );
+        assert_eq!(wheel.insert(Entry::new(10, 20000)), InsertOutcome::Overflow);
+        assert_eq!(wheel.tick(), None);
+
+        assert_eq!(
+            wheel.insert(Entry::new(5, 500)),
+            InsertOutcome::Late(LatePolicy::SideOutput)
+        );
+        assert_eq!(wheel.at(0), Some(&10));
+
+        let late = wheel.drain_late();
+        assert_eq!(late.len(), 1);
+        assert_eq!(late[0].data, 5);
+        assert_eq!(late[0].timestamp, 500);
+        assert!(wheel.drain_late().is_empty());
+    }
+
+    #[test]
+    fn allowed_lateness_test() {
+        let mut wheel: WriterWheel<U64SumAggregator> =
+            WriterWheel::with_capacity_and_watermark(8, 10000)
+                .with_late_policy(LatePolicy::SideOutput)
+                .with_allowed_lateness(Duration::seconds(5));
+
+        assert_eq!(
+            wheel.insert(Entry::new(5, 5000)),
+            InsertOutcome::Late(LatePolicy::Correct)
+        );
+        assert_eq!(
+            wheel.insert(Entry::new(5, 4999)),
+            InsertOutcome::Late(LatePolicy::SideOutput)
+        );
+        assert_eq!(wheel.drain_late().len(), 1);
+    }
+
     #[test]
     fn overflow_test() {
         let mut watermark = 0;
@@ -303,4 +453,24 @@ mod tests {
             time += 1000;
         }
     }
+
+    #[test]
+    fn slot_size_test() {
+        let mut wheel: WriterWheel<U64SumAggregator> =
+            WriterWheel::with_capacity_and_watermark(4, 0)
+                .with_slot_size(Duration::milliseconds(100));
+
+        assert_eq!(wheel.insert(Entry::new(1, 50)), InsertOutcome::WriteAhead);
+        assert_eq!(wheel.insert(Entry::new(2, 150)), InsertOutcome::WriteAhead);
+        assert_eq!(wheel.insert(Entry::new(3, 399)), InsertOutcome::WriteAhead);
+        assert_eq!(wheel.insert(Entry::new(4, 450)), InsertOutcome::Overflow);
+
+        assert_eq!(wheel.tick(), Some(1));
+        assert_eq!(wheel.watermark(), 100);
+        assert_eq!(wheel.tick(), Some(2));
+        assert_eq!(wheel.tick(), None);
+        assert_eq!(wheel.tick(), Some(3));
+        assert_eq!(wheel.tick(), Some(4));
+        assert_eq!(wheel.watermark(), 500);
+    }
 }
@@ -1,7 +1,8 @@
 pub mod state;
-mod util;
+pub(crate) mod util;
 
 use crate::{aggregator::Aggregator, duration::Duration};
+use core::mem;
 use state::{SessionState, SlicingState};
 
 #[cfg(not(feature = "std"))]
@@ -14,15 +15,53 @@ use std::collections::VecDeque;
 
 use self::util::pairs_space;
 
+/// Identifier of an installed window
+///
+/// Returned when installing a window (see [RwWheel::window](crate::RwWheel::window)) and used to tag
+/// its [WindowAggregate] results.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub struct WindowId(pub(crate) u64);
+
 /// Window Aggregation Result
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub struct WindowAggregate<T> {
+    /// The identifier of the window that produced the result
+    pub window_id: WindowId,
     /// The start time of the window in milliseconds since unix epoch
     pub window_start_ms: u64,
     /// The end time of the window in milliseconds since unix epoch
     pub window_end_ms: u64,
     /// The aggregate result for the window
     pub aggregate: T,
+    /// Describes whether this is the first result of the window or a correction of it
+    pub kind: WindowAggregateKind,
+}
+
+impl<T> WindowAggregate<T> {
+    #[inline]
+    fn contains(&self, timestamp: u64) -> bool {
+        self.window_start_ms <= timestamp && timestamp < self.window_end_ms
+    }
+}
+
+/// The kind of a [WindowAggregate]
+///
+/// Windows are first emitted as [WindowAggregateKind::Insert]. If the wheel is configured with an allowed lateness
+/// (see [HawConf::with_allowed_lateness](crate::HawConf::with_allowed_lateness)), late entries that fall into an already emitted
+/// window cause a [WindowAggregateKind::Retraction] of the previously emitted aggregate followed by a
+/// [WindowAggregateKind::Update] with the corrected aggregate.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub enum WindowAggregateKind {
+    /// The first result of the window
+    #[default]
+    Insert,
+    /// A corrected result of a previously emitted window
+    Update,
+    /// Retracts a previously emitted result of the window
+    Retraction,
 }
 
 /// Different window variants supported by µWheel
@@ -106,12 +145,27 @@ impl Window {
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 pub struct WindowManager<A: Aggregator> {
+    pub(crate) id: WindowId,
     pub(crate) aggregator: WindowAggregator<A>,
     pub(crate) window: Window,
+    /// Allowed lateness in milliseconds
+    pub(crate) allowed_lateness: u64,
+    /// Emitted windows that may still be corrected by late entries
+    pub(crate) emitted: VecDeque<EmittedWindow<A::PartialAggregate>>,
+    /// Late partial aggregates whose pairs have already been inserted into the slicing aggregator
+    pub(crate) late: Vec<(u64, A::PartialAggregate)>,
 }
 impl<A: Aggregator> WindowManager<A> {
-    /// Creates a new window manager with the given watermark and window type
-    pub fn new(watermark: u64, window: Window) -> Self {
+    /// Creates a new window manager with the given id, watermark, tick size, window type and allowed lateness
+    ///
+    /// The tick size and allowed lateness are given in milliseconds.
+    pub fn new(
+        id: WindowId,
+        watermark: u64,
+        tick_size_ms: u64,
+        window: Window,
+        allowed_lateness: u64,
+    ) -> Self {
         let to_ms = |d: Duration| d.whole_milliseconds() as usize;
 
         // Create window aggregator and state based on the window type
@@ -119,14 +173,14 @@ impl<A: Aggregator> WindowManager<A> {
             Window::Tumbling { range } => {
                 let pairs = pairs_space(to_ms(range), to_ms(range));
                 WindowAggregator::Slicing {
-                    state: SlicingState::new(watermark, to_ms(range), to_ms(range)),
+                    state: SlicingState::new(watermark, tick_size_ms, to_ms(range), to_ms(range)),
                     aggregator: SlicingAggregator::with_capacity(pairs),
                 }
             }
             Window::Sliding { range, slide } => {
                 let pairs = pairs_space(to_ms(range), to_ms(slide));
                 WindowAggregator::Slicing {
-                    state: SlicingState::new(watermark, to_ms(range), to_ms(slide)),
+                    state: SlicingState::new(watermark, tick_size_ms, to_ms(range), to_ms(slide)),
                     aggregator: SlicingAggregator::with_capacity(pairs),
                 }
             }
@@ -135,7 +189,124 @@ impl<A: Aggregator> WindowManager<A> {
                 aggregator: SessionAggregator::default(),
             },
         };
-        Self { aggregator, window }
+        Self {
+            id,
+            aggregator,
+            window,
+            allowed_lateness,
+            emitted: VecDeque::new(),
+            late: Vec::new(),
+        }
+    }
+
+    /// Applies a late partial aggregate to the windows covering the given timestamp
+    ///
+    /// Already emitted windows are corrected and returned through [Self::drain_corrections] while windows
+    /// that have yet to be emitted will include the partial once they are emitted.
+    ///
+    /// Late entries older than the allowed lateness and session windows are ignored.
+    pub fn apply_late(&mut self, watermark: u64, timestamp: u64, partial: A::PartialAggregate) {
+        if timestamp.saturating_add(self.allowed_lateness) < watermark {
+            return;
+        }
+        let WindowAggregator::Slicing { state, .. } = &self.aggregator else {
+            return;
+        };
+
+        for emitted in self.emitted.iter_mut() {
+            if emitted.window.contains(timestamp) {
+                emitted.correct::<A>(partial.clone());
+            }
+        }
+
+        // The current pair is queried from the wheel once it is complete and thus already includes the correction.
+        let current_pair_start = state.next_pair_end - state.current_pair_len as u64;
+        if timestamp < current_pair_start {
+            self.late.push((timestamp, partial));
+        }
+    }
+
+    /// Emits a window aggregate computed by the slicing aggregator
+    pub fn emit(
+        &mut self,
+        mut window: WindowAggregate<A::PartialAggregate>,
+        windows: &mut Vec<WindowAggregate<A::PartialAggregate>>,
+    ) {
+        if self.allowed_lateness > 0 {
+            // include late partials that are not reflected in the slicing aggregator
+            for (timestamp, partial) in self.late.iter() {
+                if window.contains(*timestamp) {
+                    window.aggregate =
+                        A::combine(mem::take(&mut window.aggregate), partial.clone());
+                }
+            }
+            let (state, _) = self.aggregator.slicing_as_mut();
+            let next_window_start = state.next_window_end - state.range as u64;
+            self.late
+                .retain(|(timestamp, _)| *timestamp >= next_window_start);
+
+            self.prune(window.window_end_ms);
+            self.emitted.push_back(EmittedWindow {
+                window: window.clone(),
+                retracted: None,
+            });
+        }
+        windows.push(window);
+    }
+
+    /// Drains corrections of previously emitted windows and drops windows that can no longer be corrected
+    pub fn drain_corrections(
+        &mut self,
+        watermark: u64,
+        windows: &mut Vec<WindowAggregate<A::PartialAggregate>>,
+    ) {
+        for emitted in self.emitted.iter_mut() {
+            if let Some(retracted) = emitted.retracted.take() {
+                windows.push(WindowAggregate {
+                    aggregate: retracted,
+                    kind: WindowAggregateKind::Retraction,
+                    ..emitted.window.clone()
+                });
+                windows.push(WindowAggregate {
+                    kind: WindowAggregateKind::Update,
+                    ..emitted.window.clone()
+                });
+            }
+        }
+        self.prune(watermark);
+    }
+
+    // drops emitted windows that are beyond the allowed lateness
+    fn prune(&mut self, watermark: u64) {
+        while let Some(emitted) = self.emitted.front() {
+            if emitted.window.window_end_ms + self.allowed_lateness <= watermark
+                && emitted.retracted.is_none()
+            {
+                self.emitted.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// An emitted window that may be corrected by late entries
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone)]
+pub(crate) struct EmittedWindow<T> {
+    /// The latest emitted result of the window including pending corrections
+    pub(crate) window: WindowAggregate<T>,
+    /// The previously emitted aggregate if the window has pending corrections
+    pub(crate) retracted: Option<T>,
+}
+
+impl<T: Clone + Default> EmittedWindow<T> {
+    #[inline]
+    fn correct<A: Aggregator<PartialAggregate = T>>(&mut self, partial: T) {
+        if self.retracted.is_none() {
+            self.retracted = Some(self.window.aggregate.clone());
+        }
+        self.window.aggregate = A::combine(mem::take(&mut self.window.aggregate), partial);
     }
 }
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -169,7 +340,7 @@ impl<A: Aggregator> WindowAggregator<A> {
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 pub struct SessionAggregator<A: Aggregator> {
-    current: A::PartialAggregate,
+    pub(crate) current: A::PartialAggregate,
 }
 
 impl<A: Aggregator> Default for SessionAggregator<A> {
@@ -182,12 +353,10 @@ impl<A: Aggregator> Default for SessionAggregator<A> {
 impl<A: Aggregator> SessionAggregator<A> {
     /// Aggregate a partial aggregate into the current session aggregate
     pub fn aggregate_session(&mut self, partial: A::PartialAggregate) {
-        self.current = A::combine(self.current, partial);
+        self.current = A::combine(mem::take(&mut self.current), partial);
     }
     pub fn get_and_reset(&mut self) -> A::PartialAggregate {
-        let current = self.current;
-        self.current = A::PartialAggregate::default();
-        current
+        mem::take(&mut self.current)
     }
 }
 
@@ -253,8 +422,8 @@ impl<A: Aggregator> Default for SubtractOnEvict<A> {
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 pub struct SubtractOnEvict<A: Aggregator> {
-    stack: VecDeque<A::PartialAggregate>,
-    agg: A::PartialAggregate,
+    pub(crate) stack: VecDeque<A::PartialAggregate>,
+    pub(crate) agg: A::PartialAggregate,
 }
 
 impl<A: Aggregator> SubtractOnEvict<A> {
@@ -274,24 +443,24 @@ impl<A: Aggregator> SubtractOnEvict<A> {
     fn pop(&mut self) {
         if let Some(top) = self.stack.pop_front() {
             let inverse_combine = A::combine_inverse().unwrap();
-            self.agg = inverse_combine(self.agg, top);
+            self.agg = inverse_combine(mem::take(&mut self.agg), top);
         }
     }
     fn query(&self) -> A::PartialAggregate {
-        self.agg
+        self.agg.clone()
     }
     fn push(&mut self, agg: A::PartialAggregate) {
-        self.agg = A::combine(self.agg, agg);
+        self.agg = A::combine(mem::take(&mut self.agg), agg.clone());
         self.stack.push_back(agg);
     }
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Value<A: Aggregator> {
-    agg: A::PartialAggregate,
-    val: A::PartialAggregate,
+    pub(crate) agg: A::PartialAggregate,
+    pub(crate) val: A::PartialAggregate,
 }
 impl<A: Aggregator> Value<A> {
     #[inline]
@@ -304,8 +473,8 @@ impl<A: Aggregator> Value<A> {
 #[cfg_attr(feature = "serde", serde(bound = "A: Default"))]
 #[derive(Default)]
 pub struct TwoStacks<A: Aggregator> {
-    front: Vec<Value<A>>,
-    back: Vec<Value<A>>,
+    pub(crate) front: Vec<Value<A>>,
+    pub(crate) back: Vec<Value<A>>,
 }
 
 impl<A: Aggregator> TwoStacks<A> {
@@ -321,7 +490,7 @@ impl<A: Aggregator> TwoStacks<A> {
     #[inline(always)]
     fn agg(stack: &[Value<A>]) -> A::PartialAggregate {
         if let Some(top) = stack.last() {
-            top.agg
+            top.agg.clone()
         } else {
             A::IDENTITY
         }
@@ -330,7 +499,7 @@ impl<A: Aggregator> TwoStacks<A> {
         if self.front.is_empty() {
             while let Some(top) = self.back.pop() {
                 self.front.push(Value::new(
-                    A::combine(top.val, Self::agg(&self.front)),
+                    A::combine(top.val.clone(), Self::agg(&self.front)),
                     top.val,
                 ));
             }
@@ -343,8 +512,10 @@ impl<A: Aggregator> TwoStacks<A> {
     }
     #[inline]
     fn push(&mut self, agg: A::PartialAggregate) {
-        self.back
-            .push(Value::new(A::combine(Self::agg(&self.back), agg), agg));
+        self.back.push(Value::new(
+            A::combine(Self::agg(&self.back), agg.clone()),
+            agg,
+        ));
     }
 }
 
@@ -352,10 +523,14 @@ impl<A: Aggregator> TwoStacks<A> {
 mod tests {
     use super::Window;
     use crate::{
-        aggregator::sum::U64SumAggregator,
-        window::WindowAggregate,
+        aggregator::{max::U64MaxAggregator, sum::U64SumAggregator},
+        window::{WindowAggregate, WindowAggregateKind, WindowId},
+        Conf,
         Duration,
         Entry,
+        HawConf,
+        InsertOutcome,
+        LatePolicy,
         NumericalDuration,
         RwWheel,
     };
@@ -380,14 +555,18 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 1533081600000,
                     window_end_ms: 1533081630000,
-                    aggregate: 600
+                    aggregate: 600,
+                    kind: WindowAggregateKind::Insert,
                 }, // Sum of entries at 1533081605000, 1533081615000, 1533081625000
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 1533081630000,
                     window_end_ms: 1533081660000,
-                    aggregate: 900
+                    aggregate: 900,
+                    kind: WindowAggregateKind::Insert,
                 }, // Sum of entries at 1533081635000, 1533081645000
             ]
         );
@@ -412,14 +591,18 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 0,
                     window_end_ms: 60000,
                     aggregate: 30,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 60000,
                     window_end_ms: 120000,
                     aggregate: 70,
+                    kind: WindowAggregateKind::Insert,
                 },
             ]
         );
@@ -445,19 +628,25 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 0,
                     window_end_ms: 120000,
-                    aggregate: 300
+                    aggregate: 300,
+                    kind: WindowAggregateKind::Insert,
                 }, // Sum of entries at 30000 and 90000
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 120000,
                     window_end_ms: 240000,
-                    aggregate: 300
+                    aggregate: 300,
+                    kind: WindowAggregateKind::Insert,
                 }, // Sum of entries at 150000 and 210000
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 240000,
                     window_end_ms: 360000,
-                    aggregate: 900
+                    aggregate: 900,
+                    kind: WindowAggregateKind::Insert,
                 }, // Sum of entries at 270000 and 330000
             ]
         );
@@ -490,9 +679,11 @@ mod tests {
         assert_eq!(
             results,
             [WindowAggregate {
+                window_id: WindowId(0),
                 window_start_ms: 1533081600000,
                 window_end_ms: 1533081630000,
-                aggregate: 2845
+                aggregate: 2845,
+                kind: WindowAggregateKind::Insert,
             }]
         )
     }
@@ -515,44 +706,60 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 0,
                     window_end_ms: 60000,
-                    aggregate: 5
+                    aggregate: 5,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 10000,
                     window_end_ms: 70000,
-                    aggregate: 7
+                    aggregate: 7,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 20000,
                     window_end_ms: 80000,
-                    aggregate: 11
+                    aggregate: 11,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 30000,
                     window_end_ms: 90000,
-                    aggregate: 10
+                    aggregate: 10,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 40000,
                     window_end_ms: 100000,
-                    aggregate: 9
+                    aggregate: 9,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 50000,
                     window_end_ms: 110000,
-                    aggregate: 9
+                    aggregate: 9,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 60000,
                     window_end_ms: 120000,
-                    aggregate: 18
+                    aggregate: 18,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 70000,
                     window_end_ms: 130000,
-                    aggregate: 15
+                    aggregate: 15,
+                    kind: WindowAggregateKind::Insert,
                 }
             ]
         );
@@ -594,29 +801,39 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 0,
                     window_end_ms: 120000,
                     aggregate: 23,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 10000,
                     window_end_ms: 130000,
                     aggregate: 25,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 20000,
                     window_end_ms: 140000,
                     aggregate: 24,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 30000,
                     window_end_ms: 150000,
                     aggregate: 23,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 40000,
                     window_end_ms: 160000,
                     aggregate: 22,
+                    kind: WindowAggregateKind::Insert,
                 },
             ]
         );
@@ -651,29 +868,39 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 0,
                     window_end_ms: 10000,
-                    aggregate: 55
+                    aggregate: 55,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 3000,
                     window_end_ms: 13000,
-                    aggregate: 85
+                    aggregate: 85,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 6000,
                     window_end_ms: 16000,
-                    aggregate: 115
+                    aggregate: 115,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 9000,
                     window_end_ms: 19000,
-                    aggregate: 145
+                    aggregate: 145,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 12000,
                     window_end_ms: 22000,
-                    aggregate: 175
+                    aggregate: 175,
+                    kind: WindowAggregateKind::Insert,
                 },
             ]
         );
@@ -693,9 +920,11 @@ mod tests {
         assert_eq!(
             results,
             [WindowAggregate {
+                window_id: WindowId(0),
                 window_start_ms: 1533081600000,
                 window_end_ms: 1533081630000,
-                aggregate: 600
+                aggregate: 600,
+                kind: WindowAggregateKind::Insert,
             }]
         );
     }
@@ -714,9 +943,11 @@ mod tests {
         assert_eq!(
             results,
             [WindowAggregate {
+                window_id: WindowId(0),
                 window_start_ms: 1533081600000,
                 window_end_ms: 1533081610000,
-                aggregate: 100
+                aggregate: 100,
+                kind: WindowAggregateKind::Insert,
             }]
         );
     }
@@ -731,9 +962,11 @@ mod tests {
         assert_eq!(
             results,
             [WindowAggregate {
+                window_id: WindowId(0),
                 window_start_ms: 1533081600000,
                 window_end_ms: 1533081630000,
-                aggregate: 0
+                aggregate: 0,
+                kind: WindowAggregateKind::Insert,
             }]
         );
     }
@@ -774,19 +1007,25 @@ mod tests {
             results,
             [
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 1000,
                     window_end_ms: 18000,
-                    aggregate: 600
+                    aggregate: 600,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 20000,
                     window_end_ms: 35000,
-                    aggregate: 400
+                    aggregate: 400,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 40000,
                     window_end_ms: 50000,
-                    aggregate: 500
+                    aggregate: 500,
+                    kind: WindowAggregateKind::Insert,
                 },
             ]
         );
@@ -819,19 +1058,25 @@ mod tests {
             [
                 // First session
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 1000,
                     window_end_ms: 8000,
-                    aggregate: 300
+                    aggregate: 300,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 10000,
                     window_end_ms: 17000,
                     aggregate: 700,
+                    kind: WindowAggregateKind::Insert,
                 },
                 WindowAggregate {
+                    window_id: WindowId(0),
                     window_start_ms: 20000,
                     window_end_ms: 25000,
-                    aggregate: 500
+                    aggregate: 500,
+                    kind: WindowAggregateKind::Insert,
                 },
             ]
         );
@@ -849,9 +1094,11 @@ mod tests {
         assert_eq!(
             results,
             [WindowAggregate {
+                window_id: WindowId(0),
                 window_start_ms: 1000,
                 window_end_ms: 6000,
-                aggregate: 100
+                aggregate: 100,
+                kind: WindowAggregateKind::Insert,
             }]
         );
     }
@@ -864,4 +1111,240 @@ mod tests {
         let results = wheel.advance_to(10000);
         assert!(results.is_empty());
     }
+
+    #[test]
+    fn window_tumbling_allowed_lateness_test() {
+        let haw_conf = HawConf::default().with_allowed_lateness(Duration::seconds(15));
+        let mut wheel: RwWheel<U64SumAggregator> =
+            RwWheel::with_conf(Conf::default().with_haw_conf(haw_conf));
+        wheel.window(Window::tumbling(Duration::seconds(10)));
+
+        wheel.insert(Entry::new(10, 1000));
+        wheel.insert(Entry::new(20, 12000));
+
+        let results = wheel.advance_to(20000);
+        assert_eq!(
+            results,
+            [
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 0,
+                    window_end_ms: 10000,
+                    aggregate: 10,
+                    kind: WindowAggregateKind::Insert,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 10000,
+                    window_end_ms: 20000,
+                    aggregate: 20,
+                    kind: WindowAggregateKind::Insert,
+                },
+            ]
+        );
+
+        // late entries within the allowed lateness
+        wheel.insert(Entry::new(5, 9000));
+        wheel.insert(Entry::new(5, 8000));
+        wheel.insert(Entry::new(1, 15000));
+
+        let results = wheel.advance_to(20000);
+        assert_eq!(
+            results,
+            [
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 0,
+                    window_end_ms: 10000,
+                    aggregate: 10,
+                    kind: WindowAggregateKind::Retraction,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 0,
+                    window_end_ms: 10000,
+                    aggregate: 20,
+                    kind: WindowAggregateKind::Update,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 10000,
+                    window_end_ms: 20000,
+                    aggregate: 20,
+                    kind: WindowAggregateKind::Retraction,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 10000,
+                    window_end_ms: 20000,
+                    aggregate: 21,
+                    kind: WindowAggregateKind::Update,
+                },
+            ]
+        );
+        // the underlying wheel has been corrected as well
+        assert_eq!(wheel.read().interval(20.seconds()), Some(41));
+
+        // the first window is beyond the allowed lateness
+        wheel.advance_to(26000);
+        wheel.insert(Entry::new(5, 11000));
+        assert_eq!(
+            wheel.insert(Entry::new(5, 9000)),
+            InsertOutcome::Late(LatePolicy::Drop)
+        );
+        let results = wheel.advance_to(30000);
+        assert_eq!(
+            results,
+            [
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 10000,
+                    window_end_ms: 20000,
+                    aggregate: 21,
+                    kind: WindowAggregateKind::Retraction,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 10000,
+                    window_end_ms: 20000,
+                    aggregate: 26,
+                    kind: WindowAggregateKind::Update,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 20000,
+                    window_end_ms: 30000,
+                    aggregate: 0,
+                    kind: WindowAggregateKind::Insert,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn window_sliding_allowed_lateness_test() {
+        let haw_conf = HawConf::default().with_allowed_lateness(Duration::seconds(10));
+        let mut wheel: RwWheel<U64MaxAggregator> =
+            RwWheel::with_conf(Conf::default().with_haw_conf(haw_conf));
+        wheel.window(Window::sliding(Duration::seconds(10), Duration::seconds(5)));
+
+        wheel.insert(Entry::new(1, 1000));
+        wheel.insert(Entry::new(2, 7000));
+
+        let results = wheel.advance_to(10000);
+        assert_eq!(
+            results,
+            [WindowAggregate {
+                window_id: WindowId(0),
+                window_start_ms: 0,
+                window_end_ms: 10000,
+                aggregate: 2,
+                kind: WindowAggregateKind::Insert,
+            }]
+        );
+
+        // late entry that falls into the emitted window [0, 10) and the upcoming window [5, 15)
+        wheel.insert(Entry::new(10, 6000));
+
+        let results = wheel.advance_to(15000);
+        assert_eq!(
+            results,
+            [
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 0,
+                    window_end_ms: 10000,
+                    aggregate: 2,
+                    kind: WindowAggregateKind::Retraction,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 0,
+                    window_end_ms: 10000,
+                    aggregate: 10,
+                    kind: WindowAggregateKind::Update,
+                },
+                WindowAggregate {
+                    window_id: WindowId(0),
+                    window_start_ms: 5000,
+                    window_end_ms: 15000,
+                    aggregate: 10,
+                    kind: WindowAggregateKind::Insert,
+                },
+            ]
+        );
+
+        // the next window no longer includes the late entry
+        let results = wheel.advance_to(20000);
+        assert_eq!(
+            results,
+            [WindowAggregate {
+                window_id: WindowId(0),
+                window_start_ms: 10000,
+                window_end_ms: 20000,
+                aggregate: 0,
+                kind: WindowAggregateKind::Insert,
+            }]
+        );
+    }
+
+    #[test]
+    fn multiple_windows_test() {
+        let mut wheel: RwWheel<U64SumAggregator> = RwWheel::new(0);
+        let tumbling = wheel.window(Window::tumbling(Duration::seconds(10)));
+        // shares its pairs with the tumbling window since the slide is equal to its range
+        let sliding = wheel.window(Window::sliding(
+            Duration::seconds(20),
+            Duration::seconds(10),
+        ));
+        assert_ne!(tumbling, sliding);
+
+        wheel.insert(Entry::new(1, 5000));
+        wheel.insert(Entry::new(2, 15000));
+        wheel.insert(Entry::new(3, 25000));
+
+        let results = wheel.advance_to(20000);
+        assert_eq!(
+            results,
+            [
+                WindowAggregate {
+                    window_id: tumbling,
+                    window_start_ms: 0,
+                    window_end_ms: 10000,
+                    aggregate: 1,
+                    kind: WindowAggregateKind::Insert,
+                },
+                WindowAggregate {
+                    window_id: tumbling,
+                    window_start_ms: 10000,
+                    window_end_ms: 20000,
+                    aggregate: 2,
+                    kind: WindowAggregateKind::Insert,
+                },
+                WindowAggregate {
+                    window_id: sliding,
+                    window_start_ms: 0,
+                    window_end_ms: 20000,
+                    aggregate: 3,
+                    kind: WindowAggregateKind::Insert,
+                },
+            ]
+        );
+
+        // deregister the tumbling window
+        assert!(wheel.remove_window(tumbling));
+        assert!(!wheel.remove_window(tumbling));
+
+        let results = wheel.advance_to(30000);
+        assert_eq!(
+            results,
+            [WindowAggregate {
+                window_id: sliding,
+                window_start_ms: 10000,
+                window_end_ms: 30000,
+                aggregate: 5,
+                kind: WindowAggregateKind::Insert,
+            }]
+        );
+    }
 }
@@ -1,6 +1,39 @@
 use super::util::{create_pair_type, PairType};
 use crate::duration::Duration;
 
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Pairs computed during a single tick that may be shared across slicing windows
+///
+/// All pairs that complete within the same tick end at the current watermark and thus
+/// windows whose slides result in the same pair length can share the same pair.
+#[derive(Debug)]
+pub struct SharedPairs<T> {
+    /// Computed pairs keyed by their length in milliseconds
+    pairs: Vec<(usize, T)>,
+}
+
+impl<T> Default for SharedPairs<T> {
+    fn default() -> Self {
+        Self { pairs: Vec::new() }
+    }
+}
+
+impl<T: Clone> SharedPairs<T> {
+    /// Returns the pair of the given length or computes it using `f`
+    pub fn get_or_insert_with(&mut self, pair_len: usize, f: impl FnOnce() -> T) -> T {
+        match self.pairs.iter().find(|(len, _)| *len == pair_len) {
+            Some((_, pair)) => pair.clone(),
+            None => {
+                let pair = f();
+                self.pairs.push((pair_len, pair.clone()));
+                pair
+            }
+        }
+    }
+}
+
 /// Stream Slicing State using the pairs technique
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Debug, Clone)]
@@ -8,6 +41,8 @@ use crate::duration::Duration;
 pub struct SlicingState {
     pub(crate) range: usize,
     pub slide: usize,
+    /// Tick size of the underlying wheel in milliseconds
+    pub(crate) tick_size_ms: u64,
     /// Type of Pair (Uneven, Even).
     pub pair_type: PairType,
     /// How many ticks are left until the next pair is complete
@@ -22,7 +57,7 @@ pub struct SlicingState {
     pub in_p1: bool,
 }
 impl SlicingState {
-    pub fn new(time: u64, range: usize, slide: usize) -> Self {
+    pub fn new(time: u64, tick_size_ms: u64, range: usize, slide: usize) -> Self {
         let pair_type = create_pair_type(range, slide);
         let current_pair_len = match pair_type {
             PairType::Even(slide) => slide,
@@ -32,8 +67,9 @@ impl SlicingState {
         Self {
             range,
             slide,
+            tick_size_ms,
             current_pair_len,
-            pair_ticks_remaining: current_pair_len / 1000, // to seconds
+            pair_ticks_remaining: current_pair_len / tick_size_ms as usize, // to ticks
             pair_type,
             next_window_end: time + range as u64,
             next_pair_end,
@@ -63,7 +99,8 @@ impl SlicingState {
     pub fn update_state(&mut self, watermark: u64) {
         self.update_pair_len();
         self.next_pair_end = watermark + self.current_pair_len as u64;
-        self.pair_ticks_remaining = self.current_pair_duration().whole_seconds() as usize;
+        self.pair_ticks_remaining =
+            (self.current_pair_duration().whole_milliseconds() as u64 / self.tick_size_ms) as usize;
     }
 }
 
@@ -72,11 +109,11 @@ impl SlicingState {
 #[derive(Debug, Clone)]
 pub struct SessionState {
     /// Static session gap
-    session_gap: Duration,
+    pub(crate) session_gap: Duration,
     /// Current time of inactivity
     pub(crate) inactive_period: Duration,
     /// Timestamp of the last window start
-    last_window_start: Option<u64>,
+    pub(crate) last_window_start: Option<u64>,
 }
 
 impl SessionState {
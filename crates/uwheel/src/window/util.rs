@@ -26,7 +26,7 @@ pub fn create_pair_type(range: usize, slide: usize) -> PairType {
 
 #[inline]
 const fn ceil_div(a: usize, b: usize) -> usize {
-    (a + (b - 1)) / b
+    a.div_ceil(b)
 }
 
 /// Based on a Range and Slide, generate number of slots required using the Pairs technique
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::sync::{Arc, Mutex};
 
 use uwheel::{
     aggregator::sum::U32SumAggregator,
@@ -10,7 +10,7 @@ use uwheel::{
 
 fn main() {
     let mut wheel: RwWheel<U32SumAggregator> = RwWheel::new(0);
-    let sum = Rc::new(RefCell::new(0));
+    let sum = Arc::new(Mutex::new(0));
     let inner_sum = sum.clone();
 
     // schedule a repeat action
@@ -19,7 +19,7 @@ fn main() {
         .schedule_repeat(5000, 5.seconds(), move |read: &Haw<_>| {
             if let Some(last_five) = read.interval(5.seconds()) {
                 println!("Last five {}", last_five);
-                *inner_sum.borrow_mut() += last_five;
+                *inner_sum.lock().unwrap() += last_five;
             }
         });
 
@@ -29,7 +29,7 @@ fn main() {
 
     // trigger first timer to add sum of last 5 seconds
     wheel.advance(5.seconds());
-    assert_eq!(*sum.borrow(), 1000);
+    assert_eq!(*sum.lock().unwrap(), 1000);
 
     for i in 5..8u64 {
         wheel.insert(Entry::new(250, i * 1000));
@@ -37,5 +37,5 @@ fn main() {
 
     // trigger second timer to add sum of last 5 seconds
     wheel.advance(5.seconds());
-    assert_eq!(*sum.borrow(), 1750);
+    assert_eq!(*sum.lock().unwrap(), 1750);
 }